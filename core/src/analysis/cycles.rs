@@ -0,0 +1,249 @@
+use crate::entity::Composition;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+/// Feedback loop between components
+#[derive(Debug, Clone, Serialize)]
+pub struct Cycle {
+    // Components (and compositions) of strongly connected component, sorted by id
+    pub components: Vec<usize>,
+    // Connections between components of cycle
+    pub connections: Vec<usize>,
+    // Ports of connections of cycle
+    pub ports: Vec<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Cycles {
+    pub cycles: Vec<Cycle>,
+    // True if there are no cycles (graph of components is directed acyclic graph)
+    pub dag: bool,
+}
+
+// Connection between components: (connection, out port, in port)
+type Link = (usize, usize, usize);
+
+/// Directed graph of components (from `joint_out` to `joint_in`) of all levels.
+/// Delegation connections (between composition and its content) aren't included,
+/// because they don't form feedback loops.
+struct Graph {
+    nodes: Vec<usize>,
+    edges: HashMap<usize, Vec<(usize, Link)>>,
+}
+
+impl Graph {
+    fn new(composition: &Composition) -> Self {
+        fn add(graph: &mut Graph, composition: &Composition) {
+            composition
+                .components
+                .iter()
+                .for_each(|component| graph.nodes.push(component.sig().id));
+            composition.connections.iter().for_each(|connection| {
+                let connection = connection.origin();
+                let (out, inp) = (&connection.joint_out, &connection.joint_in);
+                if out.component == composition.sig.id || inp.component == composition.sig.id {
+                    return;
+                }
+                graph
+                    .edges
+                    .entry(out.component)
+                    .or_default()
+                    .push((inp.component, (connection.sig.id, out.port, inp.port)));
+            });
+            composition
+                .compositions
+                .iter()
+                .for_each(|nested| add(graph, nested.origin()));
+        }
+        let mut graph = Self {
+            nodes: Vec::new(),
+            edges: HashMap::new(),
+        };
+        add(&mut graph, composition);
+        // Compositions are present as components, but connections could refer to
+        // components, which aren't in list
+        graph.edges.iter().for_each(|(from, to)| {
+            graph.nodes.push(*from);
+            graph.nodes.extend(to.iter().map(|(id, _)| *id));
+        });
+        graph.nodes.sort();
+        graph.nodes.dedup();
+        graph
+    }
+
+    fn targets(&self, id: &usize) -> &[(usize, Link)] {
+        self.edges.get(id).map_or(&[], Vec::as_slice)
+    }
+
+    /// Tarjan's algorithm (iterative) of search of strongly connected components
+    fn get_sccs(&self) -> Vec<Vec<usize>> {
+        let mut indexes: HashMap<usize, usize> = HashMap::new();
+        let mut lowlinks: HashMap<usize, usize> = HashMap::new();
+        let mut stack: Vec<usize> = Vec::new();
+        let mut on_stack: HashSet<usize> = HashSet::new();
+        let mut sccs: Vec<Vec<usize>> = Vec::new();
+        let mut next = 0;
+        for root in self.nodes.iter() {
+            if indexes.contains_key(root) {
+                continue;
+            }
+            // (node, position of next target to visit)
+            let mut calls: Vec<(usize, usize)> = vec![(*root, 0)];
+            while let Some((node, pos)) = calls.pop() {
+                if pos == 0 {
+                    indexes.insert(node, next);
+                    lowlinks.insert(node, next);
+                    next += 1;
+                    stack.push(node);
+                    on_stack.insert(node);
+                } else {
+                    // Returned from target; propagate its lowlink
+                    let (target, _) = self.targets(&node)[pos - 1];
+                    let low = lowlinks[&node].min(lowlinks[&target]);
+                    lowlinks.insert(node, low);
+                }
+                let targets = self.targets(&node);
+                let mut pos = pos;
+                let mut descended = false;
+                while pos < targets.len() {
+                    let (target, _) = targets[pos];
+                    pos += 1;
+                    if !indexes.contains_key(&target) {
+                        calls.push((node, pos));
+                        calls.push((target, 0));
+                        descended = true;
+                        break;
+                    } else if on_stack.contains(&target) {
+                        let low = lowlinks[&node].min(indexes[&target]);
+                        lowlinks.insert(node, low);
+                    }
+                }
+                if descended || lowlinks[&node] != indexes[&node] {
+                    continue;
+                }
+                let mut scc: Vec<usize> = Vec::new();
+                while let Some(id) = stack.pop() {
+                    on_stack.remove(&id);
+                    scc.push(id);
+                    if id == node {
+                        break;
+                    }
+                }
+                sccs.push(scc);
+            }
+        }
+        sccs
+    }
+}
+
+/// Finds feedback loops in graph of components: strongly connected components with more
+/// than one component or with connection of component to itself. Cycles are sorted by
+/// smallest id of component.
+pub fn find(composition: &Composition) -> Cycles {
+    let graph = Graph::new(composition);
+    let mut cycles = graph
+        .get_sccs()
+        .into_iter()
+        .filter_map(|mut components| {
+            components.sort();
+            let members = components.iter().copied().collect::<HashSet<usize>>();
+            let links = components
+                .iter()
+                .flat_map(|id| graph.targets(id).iter())
+                .filter(|(target, _)| members.contains(target))
+                .map(|(_, link)| *link)
+                .collect::<Vec<Link>>();
+            if links.is_empty() {
+                return None;
+            }
+            let mut connections = links.iter().map(|link| link.0).collect::<Vec<usize>>();
+            connections.sort();
+            let mut ports = links
+                .iter()
+                .flat_map(|link| [link.1, link.2])
+                .collect::<Vec<usize>>();
+            ports.sort();
+            ports.dedup();
+            Some(Cycle {
+                components,
+                connections,
+                ports,
+            })
+        })
+        .collect::<Vec<Cycle>>();
+    cycles.sort_by_key(|cycle| cycle.components.first().copied());
+    Cycles {
+        dag: cycles.is_empty(),
+        cycles,
+    }
+}
+
+/// Returns true if graph of components has no cycles
+pub fn is_dag(composition: &Composition) -> bool {
+    find(composition).dag
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::{dummy::SignatureProducer, Connection, Joint};
+
+    // Composition with connections between given components (from, to); port of
+    // connection N is 1000 + N on out side and 2000 + N on in side. Id of composition
+    // is out of range of components, so connections aren't taken as delegation ones.
+    fn graph(links: &[(usize, usize)]) -> Composition {
+        let mut producer = SignatureProducer::new(usize::MAX / 2);
+        let mut composition = Composition::new(producer.next());
+        links.iter().enumerate().for_each(|(n, (from, to))| {
+            let mut sig = producer.next();
+            sig.id = n;
+            composition.push_connection(Connection::new(
+                sig,
+                Joint::new(2000 + n, *to),
+                Joint::new(1000 + n, *from),
+            ));
+        });
+        composition
+    }
+
+    #[test]
+    fn finds_loops_and_self_connections() {
+        let cycles = find(&graph(&[(3, 4), (4, 5), (5, 3), (5, 6), (7, 7), (8, 9)]));
+        assert!(!cycles.dag);
+        let found = cycles
+            .cycles
+            .iter()
+            .map(|cycle| (&cycle.components, &cycle.connections, &cycle.ports))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            found,
+            vec![
+                (
+                    &vec![3, 4, 5],
+                    &vec![0, 1, 2],
+                    &vec![1000, 1001, 1002, 2000, 2001, 2002]
+                ),
+                (&vec![7], &vec![4], &vec![1004, 2004]),
+            ]
+        );
+    }
+
+    #[test]
+    fn detects_dag() {
+        assert!(is_dag(&graph(&[])));
+        assert!(is_dag(&graph(&[(1, 2), (1, 3), (2, 4), (3, 4)])));
+        assert!(!is_dag(&graph(&[(1, 2), (2, 1)])));
+    }
+
+    #[test]
+    fn handles_long_chains() {
+        // Recursive search would overflow stack on such depth
+        let len = 50_000;
+        let mut links = (0..len).map(|i| (i, i + 1)).collect::<Vec<_>>();
+        assert!(is_dag(&graph(&links)));
+        links.push((len, 0));
+        let cycles = find(&graph(&links));
+        assert_eq!(cycles.cycles.len(), 1);
+        assert_eq!(cycles.cycles[0].components.len(), len + 1);
+    }
+}
@@ -0,0 +1,728 @@
+use crate::entity::{Composition, Port, Ports, Signature};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+    Moved,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum EntityKind {
+    Composition,
+    Component,
+    Port,
+    Connection,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Change {
+    pub kind: ChangeKind,
+    pub entity: EntityKind,
+    // Id in changed model; for removed entities - id in origin model
+    pub id: usize,
+    pub path: String,
+    // List of changed attributes ("attr: before -> after")
+    pub details: Vec<String>,
+    #[serde(skip)]
+    container: Option<usize>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct Summary {
+    pub added: usize,
+    pub removed: usize,
+    pub changed: usize,
+    pub moved: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Diff {
+    pub summary: Summary,
+    pub changes: Vec<Change>,
+    // Matched entities: id in origin model => id in changed model
+    #[serde(skip)]
+    matched: HashMap<usize, usize>,
+    // Ids used in changed model
+    #[serde(skip)]
+    used: HashSet<usize>,
+    // Max id used in both models
+    #[serde(skip)]
+    max_id: usize,
+}
+
+struct Node {
+    id: usize,
+    kind: EntityKind,
+    path: String,
+    // Key of parent (owner of port, holder of component/connection)
+    parent: Option<String>,
+    parent_id: Option<usize>,
+    attrs: Vec<(&'static str, String)>,
+}
+
+struct Flat {
+    nodes: Vec<Node>,
+    index: HashMap<(EntityKind, String), usize>,
+    max_id: usize,
+}
+
+impl Flat {
+    fn new(composition: &Composition, by_path: bool) -> Self {
+        let mut flat = Flat {
+            nodes: Vec::new(),
+            index: HashMap::new(),
+            max_id: 0,
+        };
+        let mut paths: HashMap<usize, String> = HashMap::new();
+        collect_port_paths(composition, "", &mut paths);
+        flat.scan(composition, None, String::new(), by_path, &paths);
+        flat
+    }
+
+    fn push(&mut self, mut node: Node, key: String) {
+        self.max_id = self.max_id.max(node.id);
+        // Paths of entities are unique (see `get_names`), except of connections between
+        // same ports; such connections are numbered
+        let mut unique = (key.clone(), node.path.clone());
+        let mut occurrence = 1;
+        while self.index.contains_key(&(node.kind, unique.0.clone())) {
+            occurrence += 1;
+            unique = (
+                format!("{key}#{occurrence}"),
+                format!("{}#{occurrence}", node.path),
+            );
+        }
+        node.path = unique.1;
+        self.index.insert((node.kind, unique.0), self.nodes.len());
+        self.nodes.push(node);
+    }
+
+    fn scan_ports(&mut self, ports: &Ports, owner: (usize, &str, String), by_path: bool) {
+        let (owner_id, owner_path, owner_key) = owner;
+        let names = get_names(ports.iter().map(|p| p.sig()));
+        ports.iter().zip(names).for_each(|(port, name)| {
+            let origin = port.origin();
+            let path = format!("{owner_path}/{name}");
+            let key = entity_key(origin.sig.id, &path, by_path);
+            self.push(
+                Node {
+                    id: origin.sig.id,
+                    kind: EntityKind::Port,
+                    path,
+                    parent: Some(owner_key.clone()),
+                    parent_id: Some(owner_id),
+                    attrs: port_attrs(origin),
+                },
+                key,
+            );
+        });
+    }
+
+    fn scan(
+        &mut self,
+        composition: &Composition,
+        parent: Option<(usize, String)>,
+        path: String,
+        by_path: bool,
+        port_paths: &HashMap<usize, String>,
+    ) {
+        let key = entity_key(composition.sig.id, &path, by_path);
+        self.push(
+            Node {
+                id: composition.sig.id,
+                kind: EntityKind::Composition,
+                path: path.clone(),
+                parent: parent.as_ref().map(|(_, key)| key.clone()),
+                parent_id: parent.as_ref().map(|(id, _)| *id),
+                attrs: vec![
+                    ("class_name", composition.sig.class_name.clone()),
+                    ("short_name", composition.sig.short_name.clone()),
+                ],
+            },
+            key.clone(),
+        );
+        self.scan_ports(
+            composition.ports.origin(),
+            (composition.sig.id, &path, key.clone()),
+            by_path,
+        );
+        let mut names = get_names(
+            composition
+                .components
+                .iter()
+                .map(|c| c.sig())
+                .chain(composition.compositions.iter().map(|c| c.sig())),
+        )
+        .into_iter();
+        composition.components.iter().for_each(|component| {
+            let origin = component.origin();
+            let comp_path = format!("{path}/{}", names.next().unwrap_or_default());
+            let comp_key = entity_key(origin.sig.id, &comp_path, by_path);
+            self.push(
+                Node {
+                    id: origin.sig.id,
+                    kind: EntityKind::Component,
+                    path: comp_path.clone(),
+                    parent: Some(key.clone()),
+                    parent_id: Some(composition.sig.id),
+                    attrs: vec![
+                        ("class_name", origin.sig.class_name.clone()),
+                        ("short_name", origin.sig.short_name.clone()),
+                    ],
+                },
+                comp_key.clone(),
+            );
+            self.scan_ports(
+                origin.ports.origin(),
+                (origin.sig.id, &comp_path, comp_key),
+                by_path,
+            );
+        });
+        composition.connections.iter().for_each(|connection| {
+            let origin = connection.origin();
+            let out_path = port_paths
+                .get(&origin.joint_out.port)
+                .cloned()
+                .unwrap_or(origin.joint_out.port.to_string());
+            let in_path = port_paths
+                .get(&origin.joint_in.port)
+                .cloned()
+                .unwrap_or(origin.joint_in.port.to_string());
+            let conn_path = format!("{out_path}->{in_path}");
+            let conn_key = entity_key(origin.sig.id, &conn_path, by_path);
+            self.push(
+                Node {
+                    id: origin.sig.id,
+                    kind: EntityKind::Connection,
+                    path: conn_path,
+                    parent: Some(key.clone()),
+                    parent_id: Some(composition.sig.id),
+                    attrs: vec![
+                        ("out", entity_key(origin.joint_out.port, &out_path, by_path)),
+                        ("in", entity_key(origin.joint_in.port, &in_path, by_path)),
+                    ],
+                },
+                conn_key,
+            );
+        });
+        composition.compositions.iter().for_each(|nested| {
+            self.scan(
+                nested.origin(),
+                Some((composition.sig.id, key.clone())),
+                format!("{path}/{}", names.next().unwrap_or_default()),
+                by_path,
+                port_paths,
+            );
+        });
+    }
+}
+
+fn entity_key(id: usize, path: &str, by_path: bool) -> String {
+    if by_path {
+        path.to_owned()
+    } else {
+        id.to_string()
+    }
+}
+
+fn port_attrs(port: &Port) -> Vec<(&'static str, String)> {
+    vec![
+        ("short_name", port.sig.short_name.clone()),
+        ("class_name", port.sig.class_name.clone()),
+        ("port_type", format!("{:?}", port.port_type)),
+        (
+            "direction",
+            port.direction()
+                .map(|d| format!("{d:?}"))
+                .unwrap_or_default(),
+        ),
+        (
+            "interface",
+            port.interface()
+                .map(|sig| sig.class_name.clone())
+                .unwrap_or_default(),
+        ),
+    ]
+}
+
+/// Returns names of siblings for paths. Same-named siblings are numbered by occurrence
+/// ("Port", "Port#2"), so paths of entities are unique.
+fn get_names<'a>(sigs: impl Iterator<Item = &'a Signature>) -> Vec<String> {
+    let mut occurrences: HashMap<String, usize> = HashMap::new();
+    sigs.map(|sig| {
+        let name = sig.to_string();
+        let occurrence = occurrences.entry(name.clone()).or_insert(0);
+        *occurrence += 1;
+        if *occurrence == 1 {
+            name
+        } else {
+            format!("{name}#{occurrence}")
+        }
+    })
+    .collect()
+}
+
+fn collect_port_paths(composition: &Composition, path: &str, paths: &mut HashMap<usize, String>) {
+    let mut add = |path: &str, ports: &Ports| {
+        let names = get_names(ports.iter().map(|p| p.sig()));
+        ports.iter().zip(names).for_each(|(p, name)| {
+            paths.insert(p.sig().id, format!("{path}/{name}"));
+        });
+    };
+    add(path, composition.ports.origin());
+    let names = get_names(
+        composition
+            .components
+            .iter()
+            .map(|c| c.sig())
+            .chain(composition.compositions.iter().map(|c| c.sig())),
+    );
+    let mut names = names.iter();
+    composition.components.iter().for_each(|c| {
+        let name = names.next().cloned().unwrap_or_default();
+        add(&format!("{path}/{name}"), c.origin().ports.origin());
+    });
+    composition.compositions.iter().for_each(|c| {
+        let name = names.next().cloned().unwrap_or_default();
+        collect_port_paths(c.origin(), &format!("{path}/{name}"), paths);
+    });
+}
+
+/// Returns paths of all entities of composition (id => path)
+pub fn get_paths(composition: &Composition) -> HashMap<usize, String> {
+    Flat::new(composition, false)
+        .nodes
+        .into_iter()
+        .map(|node| (node.id, node.path))
+        .collect()
+}
+
+/// Replaces path prefixes of moved entities (path in origin => path in changed model);
+/// both sides of connection path are replaced.
+fn translate(path: &str, prefixes: &[(String, String)]) -> String {
+    path.split("->")
+        .map(|part| {
+            prefixes
+                .iter()
+                .rev()
+                .find(|(from, _)| {
+                    part == from
+                        || part
+                            .strip_prefix(from.as_str())
+                            .is_some_and(|rest| rest.starts_with('/'))
+                })
+                .map_or(part.to_owned(), |(from, to)| {
+                    format!("{to}{}", &part[from.len()..])
+                })
+        })
+        .collect::<Vec<String>>()
+        .join("->")
+}
+
+/// In path mode moved entity has different key, so it isn't matched by main pass. Unmatched
+/// components and compositions are matched by id, then by short name (if it's unique among
+/// unmatched entities); descendants of moved entities are matched by translated paths.
+/// Returns moved prefixes (path in origin, path in changed model).
+fn match_moved(
+    before: &Flat,
+    after: &Flat,
+    pairs: &mut HashMap<usize, usize>,
+) -> Vec<(String, String)> {
+    let movable = [EntityKind::Component, EntityKind::Composition];
+    let mut taken: HashSet<usize> = pairs.values().copied().collect();
+    let short_name = |node: &Node| {
+        node.attrs
+            .iter()
+            .find(|(name, _)| *name == "short_name")
+            .map(|(_, value)| value.to_owned())
+            .unwrap_or_default()
+    };
+    let mut by_id: HashMap<(EntityKind, usize), usize> = HashMap::new();
+    let mut by_name: HashMap<(EntityKind, String), Vec<usize>> = HashMap::new();
+    after
+        .nodes
+        .iter()
+        .enumerate()
+        .filter(|(pos, node)| movable.contains(&node.kind) && !taken.contains(pos))
+        .for_each(|(pos, node)| {
+            by_id.insert((node.kind, node.id), pos);
+            by_name
+                .entry((node.kind, short_name(node)))
+                .or_default()
+                .push(pos);
+        });
+    let mut names: HashMap<(EntityKind, String), usize> = HashMap::new();
+    before
+        .nodes
+        .iter()
+        .enumerate()
+        .filter(|(pos, node)| movable.contains(&node.kind) && !pairs.contains_key(pos))
+        .for_each(|(_, node)| *names.entry((node.kind, short_name(node))).or_default() += 1);
+    let mut prefixes: Vec<(String, String)> = Vec::new();
+    // Nodes are ordered from parents to children, so prefixes of parents are known already
+    for (pos, node) in before.nodes.iter().enumerate() {
+        if pairs.contains_key(&pos) {
+            continue;
+        }
+        let translated = translate(&node.path, &prefixes);
+        let found = after
+            .index
+            .get(&(node.kind, translated))
+            .copied()
+            .filter(|found| !taken.contains(found));
+        let found = found.or_else(|| {
+            if !movable.contains(&node.kind) {
+                return None;
+            }
+            let name = (node.kind, short_name(node));
+            by_id
+                .get(&(node.kind, node.id))
+                .copied()
+                .filter(|found| !taken.contains(found))
+                .or_else(|| match (names.get(&name), by_name.get(&name)) {
+                    (Some(1), Some(candidates)) if candidates.len() == 1 => {
+                        Some(candidates[0]).filter(|found| !taken.contains(found))
+                    }
+                    _ => None,
+                })
+                .inspect(|found| {
+                    prefixes.push((node.path.clone(), after.nodes[*found].path.clone()));
+                })
+        });
+        if let Some(found) = found {
+            pairs.insert(pos, found);
+            taken.insert(found);
+        }
+    }
+    prefixes
+}
+
+impl Diff {
+    /// Compares two models. Entities are matched by ids or by paths (path is built from
+    /// short names of owners, like "/Composition/Component/Port"). In path mode moved
+    /// components and compositions are matched by id or unique short name.
+    pub fn new(origin: &Composition, changed: &Composition, by_path: bool) -> Self {
+        let before = Flat::new(origin, by_path);
+        let after = Flat::new(changed, by_path);
+        // Matched nodes: position in origin => position in changed
+        let mut pairs: HashMap<usize, usize> = HashMap::new();
+        after.index.iter().for_each(|(key, pos)| {
+            if let Some(prev) = before.index.get(key) {
+                pairs.insert(*prev, *pos);
+            }
+        });
+        let prefixes = if by_path {
+            match_moved(&before, &after, &mut pairs)
+        } else {
+            Vec::new()
+        };
+        let reversed: HashMap<usize, usize> = pairs.iter().map(|(a, b)| (*b, *a)).collect();
+        let mut changes: Vec<Change> = Vec::new();
+        let is_parent_reported = |flat: &Flat, node: &Node, matched: &HashMap<usize, usize>| {
+            // Entity is reported as a part of added/removed parent
+            node.parent.as_ref().is_some_and(|parent| {
+                [EntityKind::Composition, EntityKind::Component]
+                    .iter()
+                    .any(|kind| {
+                        flat.index
+                            .get(&(*kind, parent.clone()))
+                            .is_some_and(|pos| !matched.contains_key(pos))
+                    })
+            })
+        };
+        for (_, pos) in sorted(&after.index) {
+            let node = &after.nodes[pos];
+            if let Some(prev) = reversed.get(&pos) {
+                let prev = &before.nodes[*prev];
+                let details = node
+                    .attrs
+                    .iter()
+                    .zip(prev.attrs.iter())
+                    .map(|((name, a), (_, b))| {
+                        let b = if node.kind == EntityKind::Connection {
+                            translate(b, &prefixes)
+                        } else {
+                            b.to_owned()
+                        };
+                        (name, a, b)
+                    })
+                    .filter(|(_, a, b)| *a != b)
+                    .map(|(name, a, b)| format!("{name}: {b} -> {a}"))
+                    .collect::<Vec<String>>();
+                let parent = prev.parent.as_ref().map(|p| translate(p, &prefixes));
+                let change = if parent != node.parent {
+                    Some(ChangeKind::Moved)
+                } else if !details.is_empty() {
+                    Some(ChangeKind::Changed)
+                } else {
+                    None
+                };
+                if let Some(change) = change {
+                    changes.push(Change {
+                        kind: change,
+                        entity: node.kind,
+                        id: node.id,
+                        path: node.path.clone(),
+                        details,
+                        container: node.parent_id,
+                    });
+                }
+            } else if !is_parent_reported(&after, node, &reversed) {
+                changes.push(Change {
+                    kind: ChangeKind::Added,
+                    entity: node.kind,
+                    id: node.id,
+                    path: node.path.clone(),
+                    details: Vec::new(),
+                    container: node.parent_id,
+                });
+            }
+        }
+        for (_, pos) in sorted(&before.index) {
+            let node = &before.nodes[pos];
+            if !pairs.contains_key(&pos) && !is_parent_reported(&before, node, &pairs) {
+                changes.push(Change {
+                    kind: ChangeKind::Removed,
+                    entity: node.kind,
+                    id: node.id,
+                    path: node.path.clone(),
+                    details: Vec::new(),
+                    container: node.parent_id,
+                });
+            }
+        }
+        let mut summary = Summary::default();
+        changes.iter().for_each(|change| match change.kind {
+            ChangeKind::Added => summary.added += 1,
+            ChangeKind::Removed => summary.removed += 1,
+            ChangeKind::Changed => summary.changed += 1,
+            ChangeKind::Moved => summary.moved += 1,
+        });
+        Diff {
+            summary,
+            changes,
+            matched: pairs
+                .iter()
+                .map(|(a, b)| (before.nodes[*a].id, after.nodes[*b].id))
+                .collect(),
+            used: after.nodes.iter().map(|node| node.id).collect(),
+            max_id: before.max_id.max(after.max_id),
+        }
+    }
+
+    /// Moves removed entities from origin model into changed model. Ids of origin model,
+    /// which are used in changed model by other entities, are remapped to avoid collisions.
+    /// Returns list of changed entities (ids are valid for union model).
+    pub fn union(
+        &self,
+        mut origin: Composition,
+        changed: &mut Composition,
+    ) -> Vec<(usize, ChangeKind)> {
+        let mut ids: HashMap<usize, usize> = self.matched.clone();
+        let mut next = self.max_id;
+        let used = &self.used;
+        let mut remap = |id: usize| -> usize {
+            *ids.entry(id).or_insert_with(|| {
+                if used.contains(&id) {
+                    next += 1;
+                    next
+                } else {
+                    id
+                }
+            })
+        };
+        remap_composition(&mut origin, &mut remap);
+        let mut marks: Vec<(usize, ChangeKind)> = Vec::new();
+        self.changes.iter().for_each(|change| {
+            if !matches!(change.kind, ChangeKind::Removed) {
+                marks.push((change.id, change.kind.clone()));
+                return;
+            }
+            let id = remap(change.id);
+            let Some(container) = change.container.map(&mut remap) else {
+                return;
+            };
+            let moved = match change.entity {
+                EntityKind::Component => origin.take_component(&id).and_then(|component| {
+                    changed
+                        .find_composition_mut(&container)
+                        .map(|holder| holder.components.push(component))
+                }),
+                EntityKind::Composition => {
+                    origin.take_composition(&id).and_then(|mut composition| {
+                        composition.origin_mut().parent = Some(container);
+                        changed
+                            .find_composition_mut(&container)
+                            .map(|holder| holder.compositions.push(composition))
+                    })
+                }
+                EntityKind::Port => origin.take_port(&container, &id).and_then(|port| {
+                    changed
+                        .find_ports_mut(&container)
+                        .map(|ports| ports.add(port, None))
+                }),
+                EntityKind::Connection => origin.take_connection(&id).and_then(|connection| {
+                    changed
+                        .find_composition_mut(&container)
+                        .map(|holder| holder.connections.push(connection))
+                }),
+            };
+            if moved.is_some() {
+                marks.push((id, ChangeKind::Removed));
+            }
+        });
+        origin.interfaces.into_iter().for_each(|interface| {
+            if changed.find_interface(&interface.sig.id).is_none() {
+                changed.interfaces.push(interface);
+            }
+        });
+        marks
+    }
+}
+
+fn sorted(index: &HashMap<(EntityKind, String), usize>) -> Vec<((EntityKind, String), usize)> {
+    // Keep order of entities in model
+    let mut entries = index
+        .iter()
+        .map(|(key, pos)| (key.clone(), *pos))
+        .collect::<Vec<((EntityKind, String), usize)>>();
+    entries.sort_by_key(|(_, pos)| *pos);
+    entries
+}
+
+fn remap_ports<F: FnMut(usize) -> usize>(ports: &mut Ports, remap: &mut F) {
+    ports.ports.iter_mut().for_each(|port| {
+        let port = port.origin_mut();
+        port.sig.id = remap(port.sig.id);
+        port.contains = port.contains.iter().map(|id| remap(*id)).collect();
+        port.connected = port
+            .connected
+            .drain()
+            .map(|(id, count)| (remap(id), count))
+            .collect();
+    });
+}
+
+fn remap_composition<F: FnMut(usize) -> usize>(composition: &mut Composition, remap: &mut F) {
+    composition.sig.id = remap(composition.sig.id);
+    composition.parent = composition.parent.map(&mut *remap);
+    remap_ports(composition.ports.origin_mut(), remap);
+    composition.components.iter_mut().for_each(|component| {
+        let component = component.origin_mut();
+        component.sig.id = remap(component.sig.id);
+        remap_ports(component.ports.origin_mut(), remap);
+    });
+    composition.connections.iter_mut().for_each(|connection| {
+        let connection = connection.origin_mut();
+        connection.sig.id = remap(connection.sig.id);
+        connection.joint_in.port = remap(connection.joint_in.port);
+        connection.joint_in.component = remap(connection.joint_in.component);
+        connection.joint_out.port = remap(connection.joint_out.port);
+        connection.joint_out.component = remap(connection.joint_out.component);
+    });
+    composition
+        .compositions
+        .iter_mut()
+        .for_each(|nested| remap_composition(nested.origin_mut(), remap));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::{
+        dummy::{Dummy, SignatureProducer},
+        Component,
+    };
+
+    // Composition of three components with two ports each; first two components have
+    // same short name "Door"
+    fn model() -> Composition {
+        let mut producer = SignatureProducer::new(0);
+        let mut composition = Composition::new(producer.next());
+        (0..3).for_each(|_| composition.push_component(Component::dummy(&mut producer, 2..=2)));
+        composition.components[..2]
+            .iter_mut()
+            .for_each(|c| c.origin_mut().sig.short_name = "Door".to_string());
+        composition
+    }
+
+    fn copy(composition: &Composition) -> Composition {
+        serde_json::from_value(serde_json::to_value(composition).unwrap()).unwrap()
+    }
+
+    fn changes(diff: &Diff) -> Vec<(ChangeKind, EntityKind, usize, &str)> {
+        diff.changes
+            .iter()
+            .map(|c| (c.kind.clone(), c.entity, c.id, c.path.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn finds_no_changes_in_same_models() {
+        let model = model();
+        assert!(Diff::new(&model, &copy(&model), false).changes.is_empty());
+        assert!(Diff::new(&model, &copy(&model), true).changes.is_empty());
+    }
+
+    #[test]
+    fn reports_changed_attributes() {
+        let origin = model();
+        let mut changed = copy(&origin);
+        let component = changed.components[2].origin_mut();
+        let before = std::mem::replace(&mut component.sig.short_name, "Lock".to_string());
+        let id = component.sig.id;
+        let diff = Diff::new(&origin, &changed, false);
+        assert_eq!(
+            changes(&diff),
+            vec![(ChangeKind::Changed, EntityKind::Component, id, "/Lock")]
+        );
+        assert_eq!(
+            diff.changes[0].details,
+            vec![format!("short_name: {before} -> Lock")]
+        );
+    }
+
+    #[test]
+    fn reports_removed_port() {
+        let origin = model();
+        let mut changed = copy(&origin);
+        let ports = changed.components[2].origin_mut().ports.origin_mut();
+        let id = ports.get(1).sig.id;
+        ports.take(&id);
+        for by_path in [false, true] {
+            let diff = Diff::new(&origin, &changed, by_path);
+            assert_eq!(diff.summary.removed, 1);
+            assert_eq!(diff.changes.len(), 1);
+            assert_eq!(diff.changes[0].id, id);
+            assert_eq!(diff.changes[0].entity, EntityKind::Port);
+        }
+    }
+
+    #[test]
+    fn numbers_same_named_siblings() {
+        let origin = model();
+        let ids = origin.components[..2]
+            .iter()
+            .map(|c| c.sig().id)
+            .collect::<Vec<usize>>();
+        let paths = get_paths(&origin);
+        assert_eq!(paths[&ids[0]], "/Door");
+        assert_eq!(paths[&ids[1]], "/Door#2");
+        // Removed second component is matched by its own path, not by path of first one
+        let mut changed = copy(&origin);
+        changed.components.remove(1);
+        assert_eq!(
+            changes(&Diff::new(&origin, &changed, true)),
+            vec![(
+                ChangeKind::Removed,
+                EntityKind::Component,
+                ids[1],
+                "/Door#2"
+            )]
+        );
+    }
+}
@@ -0,0 +1,131 @@
+use crate::{entity::Composition, error::E};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// Components affected by change of component or port
+#[derive(Debug, Default, Serialize)]
+pub struct Impact {
+    // Affected components and compositions: (id, distance in hops from origin)
+    pub components: Vec<(usize, usize)>,
+    // Connections, which propagate impact
+    pub connections: Vec<usize>,
+    // Ports of connections, which propagate impact
+    pub ports: Vec<usize>,
+}
+
+// Connection between components: (connection, out port, in port)
+type Link = (usize, usize, usize);
+
+/// Directed graph of components (including nested components and compositions)
+struct Graph {
+    // component -> [(linked component, connection)]
+    downstream: HashMap<usize, Vec<(usize, Link)>>,
+    upstream: HashMap<usize, Vec<(usize, Link)>>,
+    // Owner of port
+    owners: HashMap<usize, usize>,
+    components: HashSet<usize>,
+}
+
+impl Graph {
+    fn new(composition: &Composition) -> Self {
+        fn add(graph: &mut Graph, composition: &Composition) {
+            graph.components.insert(composition.sig.id);
+            composition.ports.origin().iter().for_each(|port| {
+                graph.owners.insert(port.sig().id, composition.sig.id);
+            });
+            composition.components.iter().for_each(|component| {
+                graph.components.insert(component.sig().id);
+                component.origin().ports.origin().iter().for_each(|port| {
+                    graph.owners.insert(port.sig().id, component.sig().id);
+                });
+            });
+            composition.connections.iter().for_each(|connection| {
+                let connection = connection.origin();
+                let (out, inp) = (&connection.joint_out, &connection.joint_in);
+                let link = (connection.sig.id, out.port, inp.port);
+                graph
+                    .downstream
+                    .entry(out.component)
+                    .or_default()
+                    .push((inp.component, link));
+                graph
+                    .upstream
+                    .entry(inp.component)
+                    .or_default()
+                    .push((out.component, link));
+            });
+            composition
+                .compositions
+                .iter()
+                .for_each(|nested| add(graph, nested.origin()));
+        }
+        let mut graph = Self {
+            downstream: HashMap::new(),
+            upstream: HashMap::new(),
+            owners: HashMap::new(),
+            components: HashSet::new(),
+        };
+        add(&mut graph, composition);
+        graph
+    }
+}
+
+/// Collects components transitively affected by given component or port. Impact goes
+/// from `joint_out` to `joint_in` of connections (downstream) or back (upstream). If
+/// origin is port, only connections of this port are followed on first step. `depth`
+/// limits count of hops from origin.
+pub fn analyze(
+    composition: &Composition,
+    origin: usize,
+    downstream: bool,
+    depth: Option<usize>,
+) -> Result<Impact, E> {
+    let graph = Graph::new(composition);
+    let edges = if downstream {
+        &graph.downstream
+    } else {
+        &graph.upstream
+    };
+    // Port of link, from which impact goes
+    let source = |link: &Link| if downstream { link.1 } else { link.2 };
+    let (start, port) = if let Some(owner) = graph.owners.get(&origin) {
+        (*owner, Some(origin))
+    } else if graph.components.contains(&origin) {
+        (origin, None)
+    } else {
+        return Err(E::NotFound(origin));
+    };
+    let mut distances: HashMap<usize, usize> = HashMap::from([(start, 0)]);
+    let mut order: Vec<usize> = vec![start];
+    let mut connections: Vec<usize> = Vec::new();
+    let mut propagated: HashSet<usize> = HashSet::new();
+    let mut ports: HashSet<usize> = HashSet::new();
+    let mut queue: VecDeque<usize> = VecDeque::from([start]);
+    while let Some(component) = queue.pop_front() {
+        let distance = distances[&component];
+        if depth.is_some_and(|depth| distance >= depth) {
+            continue;
+        }
+        for (next, link) in edges.get(&component).into_iter().flatten() {
+            if distance == 0 && port.is_some_and(|port| source(link) != port) {
+                continue;
+            }
+            if propagated.insert(link.0) {
+                connections.push(link.0);
+                ports.extend([link.1, link.2]);
+            }
+            if !distances.contains_key(next) {
+                distances.insert(*next, distance + 1);
+                order.push(*next);
+                queue.push_back(*next);
+            }
+        }
+    }
+    let mut ports = ports.into_iter().collect::<Vec<usize>>();
+    ports.sort();
+    Ok(Impact {
+        components: order.into_iter().map(|id| (id, distances[&id])).collect(),
+        connections,
+        ports,
+    })
+}
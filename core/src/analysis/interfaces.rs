@@ -0,0 +1,127 @@
+use crate::{
+    entity::{Composition, Connection, InterfaceKind, Port, PortDirection},
+    render::{entity::ConnectionData, Render},
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum MismatchReason {
+    RequiredToRequired,
+    ProvidedToProvided,
+    // (out, in)
+    DifferentKinds(InterfaceKind, InterfaceKind),
+    // (out, in)
+    DifferentClasses(String, String),
+    // Same class, but different data elements or operations
+    IncompatibleInterfaces,
+}
+
+/// Reason of rejection of new connection between two ports
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Rejection {
+    NotFound(usize),
+    SamePort,
+    SameComponent,
+    // Grouped ports are created by render and cannot be connected directly
+    GroupedPort(usize),
+    AlreadyConnected,
+    Mismatch(MismatchReason),
+}
+
+#[derive(Debug, Serialize)]
+pub struct InterfaceMismatch {
+    pub connection: usize,
+    // Id of connection, which represents mismatched connection on board (it could be
+    // connection between grouped ports)
+    pub visible: usize,
+    pub out: ConnectionData,
+    #[serde(rename = "in")]
+    pub inp: ConnectionData,
+    pub reason: MismatchReason,
+}
+
+/// Checks a pair of ports. Returns None if ports are compatible or there is no data to
+/// make a decision (for example port doesn't have an interface).
+pub fn check(a: &Port, b: &Port, composition: &Composition) -> Option<MismatchReason> {
+    match (a.direction(), b.direction()) {
+        (Some(PortDirection::Required), Some(PortDirection::Required)) => {
+            return Some(MismatchReason::RequiredToRequired)
+        }
+        (Some(PortDirection::Provided), Some(PortDirection::Provided)) => {
+            return Some(MismatchReason::ProvidedToProvided)
+        }
+        _ => {}
+    }
+    if let (Some(kind_a), Some(kind_b)) = (a.interface_kind.as_ref(), b.interface_kind.as_ref()) {
+        if kind_a != kind_b {
+            return Some(MismatchReason::DifferentKinds(
+                kind_a.clone(),
+                kind_b.clone(),
+            ));
+        }
+    }
+    let (Some(sig_a), Some(sig_b)) = (a.interface(), b.interface()) else {
+        return None;
+    };
+    if sig_a.class_name != sig_b.class_name {
+        return Some(MismatchReason::DifferentClasses(
+            sig_a.class_name.to_owned(),
+            sig_b.class_name.to_owned(),
+        ));
+    }
+    if let (Some(interface_a), Some(interface_b)) = (
+        composition.find_interface(&sig_a.id),
+        composition.find_interface(&sig_b.id),
+    ) {
+        if !interface_a.is_compatible(interface_b) {
+            return Some(MismatchReason::IncompatibleInterfaces);
+        }
+    }
+    None
+}
+
+fn check_connection(
+    composition: &Render<Composition>,
+    root: &Composition,
+    connection: &Connection,
+) -> Option<MismatchReason> {
+    // Use original ports, because grouped ports don't have interfaces
+    let port_out = composition.get_port(connection.joint_out.port)?;
+    let port_in = composition.get_port(connection.joint_in.port)?;
+    if !port_out.contains.is_empty() || !port_in.contains.is_empty() {
+        return None;
+    }
+    check(port_out, port_in, root)
+}
+
+/// Walks all connections of composition (including nested compositions) and collects
+/// connections with incompatible ports
+pub fn get_mismatches(composition: &Render<Composition>) -> Vec<InterfaceMismatch> {
+    fn scan(
+        composition: &Render<Composition>,
+        root: &Composition,
+        found: &mut Vec<InterfaceMismatch>,
+    ) {
+        composition.origin().connections.iter().for_each(|conn| {
+            let connection = conn.origin();
+            if let Some(reason) = check_connection(composition, root, connection) {
+                let (out, inp) = composition.connection_to_connection_data(connection);
+                found.push(InterfaceMismatch {
+                    connection: connection.sig.id,
+                    visible: composition.get_visible_connection(connection),
+                    out,
+                    inp,
+                    reason,
+                });
+            }
+        });
+        composition.origin().compositions.iter().for_each(|nested| {
+            if let Ok(nested) = nested.render() {
+                scan(nested, root, found);
+            }
+        });
+    }
+    let mut found: Vec<InterfaceMismatch> = Vec::new();
+    scan(composition, composition.origin(), &mut found);
+    found
+}
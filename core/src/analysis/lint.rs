@@ -0,0 +1,179 @@
+use crate::{
+    analysis::diff::EntityKind,
+    entity::{Composition, Port, PortDirection, Ports, Signature},
+    export::csv,
+};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum PortStatus {
+    // Port doesn't have connections at all
+    Unconnected,
+    // Port doesn't have connections in own scope, but it's connected in other compositions
+    // (ids of compositions, which hold connections)
+    ConnectedOuter(Vec<usize>),
+}
+
+/// Port without connections in composition, where it's used
+#[derive(Debug, Serialize)]
+pub struct UnconnectedPort {
+    pub port: usize,
+    pub short_name: String,
+    pub owner: usize,
+    pub owner_name: String,
+    pub owner_entity: EntityKind,
+    // Composition, where owner is placed
+    pub holder: usize,
+    pub interface: Option<String>,
+    pub direction: Option<PortDirection>,
+    pub status: PortStatus,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct PortsReport {
+    pub ports: Vec<UnconnectedPort>,
+    // Count of ports without any connection
+    pub unconnected: usize,
+    // Count of ports connected in other compositions only
+    pub connected_outer: usize,
+}
+
+const CSV_HEADER: [&str; 9] = [
+    "port",
+    "short_name",
+    "owner",
+    "owner_name",
+    "owner_entity",
+    "holder",
+    "interface",
+    "direction",
+    "status",
+];
+
+impl PortsReport {
+    pub fn to_csv(&self) -> String {
+        let rows = self
+            .ports
+            .iter()
+            .map(|port| {
+                vec![
+                    port.port.to_string(),
+                    port.short_name.to_owned(),
+                    port.owner.to_string(),
+                    port.owner_name.to_owned(),
+                    format!("{:?}", port.owner_entity),
+                    port.holder.to_string(),
+                    port.interface.clone().unwrap_or_default(),
+                    port.direction
+                        .as_ref()
+                        .map(|direction| format!("{direction:?}"))
+                        .unwrap_or_default(),
+                    match &port.status {
+                        PortStatus::Unconnected => String::from("Unconnected"),
+                        PortStatus::ConnectedOuter(holders) => format!(
+                            "ConnectedOuter({})",
+                            holders
+                                .iter()
+                                .map(|id| id.to_string())
+                                .collect::<Vec<String>>()
+                                .join(" ")
+                        ),
+                    },
+                ]
+            })
+            .collect::<Vec<Vec<String>>>();
+        csv::write(&CSV_HEADER, &rows)
+    }
+}
+
+// Owner of ports: (signature, ports, entity, scopes); scopes - compositions, where
+// connections of ports are expected
+type Owner<'a> = (&'a Signature, &'a Ports, EntityKind, Vec<usize>);
+
+/// Collects ports, which don't have connections in own scope: component ports in holder
+/// composition, composition ports in parent composition or inside of composition
+/// (delegation). Ports connected in other compositions only (see `Port.connected`) are
+/// reported with `ConnectedOuter` status.
+pub fn get_unconnected_ports(composition: &Composition) -> PortsReport {
+    let mut owners: Vec<Owner> = vec![(
+        &composition.sig,
+        composition.ports.origin(),
+        EntityKind::Composition,
+        vec![composition.sig.id],
+    )];
+    // port -> compositions, which hold connections of port
+    let mut holders: HashMap<usize, HashSet<usize>> = HashMap::new();
+    fn walk<'a>(
+        composition: &'a Composition,
+        owners: &mut Vec<Owner<'a>>,
+        holders: &mut HashMap<usize, HashSet<usize>>,
+    ) {
+        let id = composition.sig.id;
+        composition
+            .components
+            .iter()
+            .map(|c| c.origin())
+            .filter(|c| !c.composition)
+            .for_each(|c| {
+                owners.push((&c.sig, c.ports.origin(), EntityKind::Component, vec![id]));
+            });
+        composition.connections.iter().for_each(|c| {
+            c.origin().get_ports().into_iter().for_each(|port| {
+                holders.entry(*port).or_default().insert(id);
+            });
+        });
+        composition.compositions.iter().for_each(|nested| {
+            let nested = nested.origin();
+            owners.push((
+                &nested.sig,
+                nested.ports.origin(),
+                EntityKind::Composition,
+                vec![id, nested.sig.id],
+            ));
+            walk(nested, owners, holders);
+        });
+    }
+    walk(composition, &mut owners, &mut holders);
+    let mut report = PortsReport::default();
+    owners.iter().for_each(|(sig, ports, entity, scopes)| {
+        ports.iter().map(|p| p.origin()).for_each(|port: &Port| {
+            let mut outer = holders
+                .get(&port.sig.id)
+                .into_iter()
+                .flatten()
+                .chain(
+                    port.connected
+                        .iter()
+                        .filter(|(_, count)| **count > 0)
+                        .map(|(holder, _)| holder),
+                )
+                .copied()
+                .collect::<Vec<usize>>();
+            if outer.iter().any(|holder| scopes.contains(holder)) {
+                return;
+            }
+            outer.sort();
+            outer.dedup();
+            let status = if outer.is_empty() {
+                report.unconnected += 1;
+                PortStatus::Unconnected
+            } else {
+                report.connected_outer += 1;
+                PortStatus::ConnectedOuter(outer)
+            };
+            report.ports.push(UnconnectedPort {
+                port: port.sig.id,
+                short_name: port.sig.short_name.to_owned(),
+                owner: sig.id,
+                owner_name: sig.short_name.to_owned(),
+                owner_entity: *entity,
+                holder: scopes[0],
+                interface: port.interface().map(|i| i.class_name.to_owned()),
+                direction: port.direction(),
+                status,
+            });
+        });
+    });
+    report
+}
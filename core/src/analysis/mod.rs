@@ -0,0 +1,7 @@
+pub mod cycles;
+pub mod diff;
+pub mod impact;
+pub mod interfaces;
+pub mod lint;
+pub mod paths;
+pub mod statistics;
@@ -0,0 +1,266 @@
+use crate::{
+    analysis::diff::EntityKind,
+    entity::{Composition, Port, PortDirection, Ports},
+    error::E,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+// Max count of returned paths, if limit isn't defined
+const DEFAULT_LIMIT: usize = 1;
+
+/// Rule of passing of signal through component (from one port of component to another)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, Serialize)]
+pub enum PassThrough {
+    // Signal doesn't pass through components; path goes by connections and ports of
+    // compositions only
+    #[default]
+    None,
+    // Signal comes into component by required port and goes out by provided port. Ports
+    // with provided-required interface or without interface work in both directions.
+    Flow,
+    // Signal passes from any port of component to any other port
+    Any,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PathOptions {
+    #[serde(default)]
+    pub pass_through: PassThrough,
+    // Connections are followed only from provided to required port. Delegation connections
+    // (between composition and its content) are followed in both directions.
+    #[serde(default)]
+    pub directed: bool,
+    // Max count of returned shortest paths
+    pub limit: Option<usize>,
+}
+
+/// Element of signal path
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Step {
+    pub id: usize,
+    pub entity: EntityKind,
+}
+
+// Transition from port: (port, connection); connection is None if signal passes
+// through component
+type Edge = (usize, Option<usize>);
+
+/// Graph of ports of composition including ports of nested compositions
+struct Graph {
+    edges: HashMap<usize, Vec<Edge>>,
+    // Owner of port: component or composition
+    owners: HashMap<usize, Step>,
+    // Ports of component or composition
+    ports: HashMap<usize, Vec<usize>>,
+}
+
+fn can_receive(port: &Port) -> bool {
+    !matches!(port.direction(), Some(PortDirection::Provided))
+}
+
+fn can_send(port: &Port) -> bool {
+    !matches!(port.direction(), Some(PortDirection::Required))
+}
+
+impl Graph {
+    fn new(composition: &Composition, options: &PathOptions) -> Self {
+        let mut graph = Self {
+            edges: HashMap::new(),
+            owners: HashMap::new(),
+            ports: HashMap::new(),
+        };
+        graph.add(composition, options);
+        graph
+    }
+
+    fn edge(&mut self, from: usize, to: usize, connection: Option<usize>) {
+        self.edges.entry(from).or_default().push((to, connection));
+    }
+
+    fn add_owner(&mut self, owner: Step, ports: &Ports) {
+        ports.iter().for_each(|port| {
+            self.owners.insert(port.sig().id, owner.clone());
+        });
+        self.ports
+            .insert(owner.id, ports.iter().map(|port| port.sig().id).collect());
+    }
+
+    fn add(&mut self, composition: &Composition, options: &PathOptions) {
+        self.add_owner(
+            Step {
+                id: composition.sig.id,
+                entity: EntityKind::Composition,
+            },
+            composition.ports.origin(),
+        );
+        composition.components.iter().for_each(|component| {
+            let component = component.origin();
+            let ports = component.ports.origin();
+            self.add_owner(
+                Step {
+                    id: component.sig.id,
+                    entity: EntityKind::Component,
+                },
+                ports,
+            );
+            if options.pass_through == PassThrough::None {
+                return;
+            }
+            let passes = |a: &Port, b: &Port| {
+                a.sig.id != b.sig.id
+                    && (options.pass_through == PassThrough::Any || (can_receive(a) && can_send(b)))
+            };
+            for a in ports.iter().map(|p| p.origin()) {
+                for b in ports.iter().map(|p| p.origin()) {
+                    if passes(a, b) || (!options.directed && passes(b, a)) {
+                        self.edge(a.sig.id, b.sig.id, None);
+                    }
+                }
+            }
+        });
+        composition.connections.iter().for_each(|connection| {
+            let connection = connection.origin();
+            let (from, to) = (connection.joint_out.port, connection.joint_in.port);
+            let delegation = connection.joint_out.component == composition.sig.id
+                || connection.joint_in.component == composition.sig.id;
+            self.edge(from, to, Some(connection.sig.id));
+            if !options.directed || delegation {
+                self.edge(to, from, Some(connection.sig.id));
+            }
+        });
+        composition
+            .compositions
+            .iter()
+            .for_each(|nested| self.add(nested.origin(), options));
+    }
+
+    /// Returns ports of entity: entity itself if it's port or ports of component
+    fn get_ports(&self, id: &usize) -> Result<Vec<usize>, E> {
+        if let Some(ports) = self.ports.get(id) {
+            Ok(ports.clone())
+        } else if self.owners.contains_key(id) {
+            Ok(vec![*id])
+        } else {
+            Err(E::NotFound(*id))
+        }
+    }
+
+    /// Converts sequence of ports into steps; owner of ports is added before its first port
+    fn get_steps(&self, path: &[Edge]) -> Vec<Step> {
+        let mut steps: Vec<Step> = Vec::new();
+        let mut owner: Option<&Step> = None;
+        path.iter().for_each(|(port, connection)| {
+            if let Some(connection) = connection {
+                steps.push(Step {
+                    id: *connection,
+                    entity: EntityKind::Connection,
+                });
+            }
+            let current = self.owners.get(port);
+            if current.is_some() && current != owner {
+                steps.extend(current.cloned());
+                owner = current;
+            }
+            steps.push(Step {
+                id: *port,
+                entity: EntityKind::Port,
+            });
+        });
+        steps
+    }
+}
+
+/// Finds shortest paths of signal between two entities (components, compositions or
+/// ports). Each path is ordered list of components, ports and connections from first
+/// entity to second one. Paths go through nested compositions by their ports.
+pub fn find(
+    composition: &Composition,
+    from: usize,
+    to: usize,
+    options: &PathOptions,
+) -> Result<Vec<Vec<Step>>, E> {
+    let graph = Graph::new(composition, options);
+    let sources = graph.get_ports(&from)?;
+    let targets = graph
+        .get_ports(&to)?
+        .into_iter()
+        .collect::<HashSet<usize>>();
+    // Breadth-first search; all shortest ways to port are stored to restore several paths
+    let mut distances: HashMap<usize, usize> = HashMap::new();
+    let mut ways: HashMap<usize, Vec<Edge>> = HashMap::new();
+    let mut queue: VecDeque<usize> = VecDeque::new();
+    sources.iter().for_each(|port| {
+        distances.insert(*port, 0);
+        queue.push_back(*port);
+    });
+    let mut reached: Option<usize> = None;
+    while let Some(port) = queue.pop_front() {
+        let distance = distances[&port];
+        if reached.is_some_and(|reached| distance >= reached) {
+            break;
+        }
+        if targets.contains(&port) {
+            reached = Some(distance);
+            continue;
+        }
+        for (next, connection) in graph.edges.get(&port).into_iter().flatten() {
+            match distances.get(next) {
+                None => {
+                    distances.insert(*next, distance + 1);
+                    ways.insert(*next, vec![(port, *connection)]);
+                    queue.push_back(*next);
+                }
+                Some(known) if *known == distance + 1 => {
+                    ways.entry(*next).or_default().push((port, *connection));
+                }
+                _ => {}
+            }
+        }
+    }
+    let Some(reached) = reached else {
+        return Ok(Vec::new());
+    };
+    let limit = options.limit.unwrap_or(DEFAULT_LIMIT);
+    let mut ends = targets
+        .iter()
+        .filter(|port| distances.get(port) == Some(&reached))
+        .copied()
+        .collect::<Vec<usize>>();
+    ends.sort();
+    let mut paths: Vec<Vec<Step>> = Vec::new();
+    // Restores paths from end to start; path holds ports with connections, which lead to
+    // them from previous ports
+    fn restore(
+        path: &mut Vec<Edge>,
+        ways: &HashMap<usize, Vec<Edge>>,
+        graph: &Graph,
+        paths: &mut Vec<Vec<Step>>,
+        limit: usize,
+    ) {
+        if paths.len() >= limit {
+            return;
+        }
+        let (port, _) = path[path.len() - 1];
+        match ways.get(&port) {
+            None => {
+                let ordered = path.iter().rev().copied().collect::<Vec<Edge>>();
+                paths.push(graph.get_steps(&ordered));
+            }
+            Some(previous) => {
+                for (prev, connection) in previous.iter() {
+                    if let Some(last) = path.last_mut() {
+                        last.1 = *connection;
+                    }
+                    path.push((*prev, None));
+                    restore(path, ways, graph, paths, limit);
+                    path.pop();
+                }
+            }
+        }
+    }
+    for end in ends {
+        restore(&mut vec![(end, None)], &ways, &graph, &mut paths, limit);
+    }
+    Ok(paths)
+}
@@ -0,0 +1,231 @@
+use crate::{
+    analysis::diff::EntityKind,
+    entity::{Composition, InterfaceKind, Port, Ports, Signature},
+};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Metrics of component or nested composition
+#[derive(Debug, Serialize)]
+pub struct ComponentStatistics {
+    pub id: usize,
+    pub entity: EntityKind,
+    pub short_name: String,
+    pub class_name: String,
+    // Count of components, which send data to component
+    pub fan_in: usize,
+    // Count of components, which receive data from component
+    pub fan_out: usize,
+    // Count of incoming (component is `joint_in`) and outgoing connections
+    pub connections_in: usize,
+    pub connections_out: usize,
+    pub ports: usize,
+    pub connected_ports: usize,
+    pub unconnected_ports: usize,
+    // Ports hidden in grouped ports on board
+    pub grouped_ports: usize,
+}
+
+/// Usage of interface (by class name of interface)
+#[derive(Debug, Serialize)]
+pub struct InterfaceUsage {
+    pub class_name: String,
+    pub kind: Option<InterfaceKind>,
+    pub ports: usize,
+    pub provided: usize,
+    pub required: usize,
+    pub provided_required: usize,
+    // Count of connections, which have port with interface on one of sides at least
+    pub connections: usize,
+}
+
+/// Composition-wide totals (all levels)
+#[derive(Debug, Default, Serialize)]
+pub struct Totals {
+    pub components: usize,
+    // Nested compositions (root composition isn't counted)
+    pub compositions: usize,
+    pub connections: usize,
+    pub ports: usize,
+    pub connected_ports: usize,
+    pub unconnected_ports: usize,
+    pub grouped_ports: usize,
+    // Count of levels of nested compositions; 0 if there are no nested compositions
+    pub depth: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Statistics {
+    pub components: Vec<ComponentStatistics>,
+    pub interfaces: Vec<InterfaceUsage>,
+    pub totals: Totals,
+}
+
+// (connection, out component, in component, out port, in port)
+type Link = (usize, usize, usize, usize, usize);
+
+/// Collects metrics of composition. `composition` is expected to be clean model (without
+/// ports created by render); `grouped` - ports hidden in grouped ports on board.
+pub fn collect(composition: &Composition, grouped: &HashSet<usize>) -> Statistics {
+    let mut links: Vec<Link> = Vec::new();
+    // (ports, owner, entity)
+    let mut owners: Vec<(&Ports, &Signature, EntityKind)> = Vec::new();
+    let mut totals = Totals::default();
+    fn walk<'a>(
+        composition: &'a Composition,
+        level: usize,
+        links: &mut Vec<Link>,
+        owners: &mut Vec<(&'a Ports, &'a Signature, EntityKind)>,
+        totals: &mut Totals,
+    ) {
+        totals.depth = totals.depth.max(level);
+        composition
+            .components
+            .iter()
+            .map(|c| c.origin())
+            .filter(|c| !c.composition)
+            .for_each(|c| {
+                totals.components += 1;
+                owners.push((c.ports.origin(), &c.sig, EntityKind::Component));
+            });
+        composition.connections.iter().for_each(|c| {
+            let c = c.origin();
+            links.push((
+                c.sig.id,
+                c.joint_out.component,
+                c.joint_in.component,
+                c.joint_out.port,
+                c.joint_in.port,
+            ));
+        });
+        composition.compositions.iter().for_each(|nested| {
+            let nested = nested.origin();
+            totals.compositions += 1;
+            owners.push((nested.ports.origin(), &nested.sig, EntityKind::Composition));
+            walk(nested, level + 1, links, owners, totals);
+        });
+    }
+    walk(composition, 0, &mut links, &mut owners, &mut totals);
+    totals.connections = links.len();
+    let connected = links
+        .iter()
+        .flat_map(|link| [link.3, link.4])
+        .collect::<HashSet<usize>>();
+    // component -> (senders, receivers, in count, out count)
+    let mut flows: HashMap<usize, (HashSet<usize>, HashSet<usize>, usize, usize)> = HashMap::new();
+    links.iter().for_each(|(_, out, inp, _, _)| {
+        let receiver = flows.entry(*inp).or_default();
+        receiver.0.insert(*out);
+        receiver.2 += 1;
+        let sender = flows.entry(*out).or_default();
+        sender.1.insert(*inp);
+        sender.3 += 1;
+    });
+    let components = owners
+        .iter()
+        .map(|(ports, sig, entity)| {
+            let ports = ports.iter().map(|p| p.origin()).collect::<Vec<&Port>>();
+            let connected_ports = ports
+                .iter()
+                .filter(|p| connected.contains(&p.sig.id))
+                .count();
+            let grouped_ports = ports.iter().filter(|p| grouped.contains(&p.sig.id)).count();
+            let (fan_in, fan_out, connections_in, connections_out) = flows
+                .get(&sig.id)
+                .map(|(senders, receivers, inp, out)| (senders.len(), receivers.len(), *inp, *out))
+                .unwrap_or_default();
+            totals.ports += ports.len();
+            totals.connected_ports += connected_ports;
+            totals.grouped_ports += grouped_ports;
+            ComponentStatistics {
+                id: sig.id,
+                entity: *entity,
+                short_name: sig.short_name.to_owned(),
+                class_name: sig.class_name.to_owned(),
+                fan_in,
+                fan_out,
+                connections_in,
+                connections_out,
+                ports: ports.len(),
+                connected_ports,
+                unconnected_ports: ports.len() - connected_ports,
+                grouped_ports,
+            }
+        })
+        .collect::<Vec<ComponentStatistics>>();
+    // Ports of root composition are counted in totals only
+    let root = composition.ports.origin();
+    totals.ports += root.len();
+    totals.connected_ports += root
+        .iter()
+        .filter(|p| connected.contains(&p.sig().id))
+        .count();
+    totals.grouped_ports += root
+        .iter()
+        .filter(|p| grouped.contains(&p.sig().id))
+        .count();
+    totals.unconnected_ports = totals.ports - totals.connected_ports;
+    Statistics {
+        components,
+        interfaces: get_interfaces(composition, &owners, &links),
+        totals,
+    }
+}
+
+fn get_interfaces(
+    composition: &Composition,
+    owners: &[(&Ports, &Signature, EntityKind)],
+    links: &[Link],
+) -> Vec<InterfaceUsage> {
+    // Sorted by class name
+    let mut usage: BTreeMap<String, InterfaceUsage> = BTreeMap::new();
+    // port -> class name of interface
+    let mut classes: HashMap<usize, &str> = HashMap::new();
+    owners
+        .iter()
+        .map(|(ports, _, _)| *ports)
+        .chain([composition.ports.origin()])
+        .flat_map(|ports| ports.iter().map(|p| p.origin()))
+        .for_each(|port| {
+            let Some(interface) = port.interface() else {
+                return;
+            };
+            classes.insert(port.sig.id, &interface.class_name);
+            let entry = usage
+                .entry(interface.class_name.to_owned())
+                .or_insert_with(|| InterfaceUsage {
+                    class_name: interface.class_name.to_owned(),
+                    kind: None,
+                    ports: 0,
+                    provided: 0,
+                    required: 0,
+                    provided_required: 0,
+                    connections: 0,
+                });
+            entry.ports += 1;
+            if port.provided_required_interface.is_some() {
+                entry.provided_required += 1;
+            } else if port.provided_interface.is_some() {
+                entry.provided += 1;
+            } else {
+                entry.required += 1;
+            }
+            if entry.kind.is_none() {
+                entry.kind = port.interface_kind.clone();
+            }
+        });
+    links.iter().for_each(|(_, _, _, out, inp)| {
+        let mut used = [classes.get(out), classes.get(inp)]
+            .into_iter()
+            .flatten()
+            .copied()
+            .collect::<Vec<&str>>();
+        used.dedup();
+        used.into_iter().for_each(|class_name| {
+            if let Some(entry) = usage.get_mut(class_name) {
+                entry.connections += 1;
+            }
+        });
+    });
+    usage.into_values().collect()
+}
@@ -1,7 +1,7 @@
 use crate::{
     entity::{
-        Component, Connection, GetIncludedComponent, IsPortIncluded, Port, Ports, Signature,
-        SignatureEqual, SignatureGetter,
+        Component, Connection, GetIncludedComponent, Interface, IsPortIncluded, Port, Ports,
+        Signature, SignatureEqual, SignatureGetter,
     },
     render::{options::Options, Render, Representation},
 };
@@ -17,6 +17,9 @@ pub struct Composition {
     pub compositions: Vec<Representation<Composition>>,
     pub ports: Representation<Ports>,
     pub parent: Option<usize>,
+    // Interfaces used by ports of composition (including nested compositions)
+    #[serde(default)]
+    pub interfaces: Vec<Interface>,
 }
 
 impl<'a, 'b: 'a> SignatureGetter<'a, 'b> for Composition {
@@ -34,6 +37,7 @@ impl Composition {
             compositions: Vec::new(),
             ports: Representation::Origin(Ports::new()),
             parent: None,
+            interfaces: Vec::new(),
         }
     }
 
@@ -86,10 +90,262 @@ impl Composition {
             .find_map(|c| id.get_if_equal(c.origin()))
     }
 
+    /// Looks for composition (self or nested) by id
+    pub fn find_composition_mut(&mut self, id: &usize) -> Option<&mut Composition> {
+        if &self.sig.id == id {
+            return Some(self);
+        }
+        self.compositions
+            .iter_mut()
+            .find_map(|c| c.origin_mut().find_composition_mut(id))
+    }
+
+    /// Looks for ports of component or composition (self or nested) by owner id
+    pub fn find_ports(&self, owner: &usize) -> Option<&Ports> {
+        if &self.sig.id == owner {
+            return Some(self.ports.origin());
+        }
+        if let Some(component) = self.components.iter().find(|c| &c.sig().id == owner) {
+            return Some(component.origin().ports.origin());
+        }
+        self.compositions
+            .iter()
+            .find_map(|c| c.origin().find_ports(owner))
+    }
+
+    /// Looks for ports of component or composition (self or nested) by owner id
+    pub fn find_ports_mut(&mut self, owner: &usize) -> Option<&mut Ports> {
+        self.find_ports_rep_mut(owner)
+            .map(|ports| ports.origin_mut())
+    }
+
+    /// Looks for ports representation of component or composition (self or nested) by owner id
+    pub fn find_ports_rep_mut(&mut self, owner: &usize) -> Option<&mut Representation<Ports>> {
+        if &self.sig.id == owner {
+            return Some(&mut self.ports);
+        }
+        if let Some(component) = self.components.iter_mut().find(|c| &c.sig().id == owner) {
+            return Some(&mut component.origin_mut().ports);
+        }
+        self.compositions
+            .iter_mut()
+            .find_map(|c| c.origin_mut().find_ports_rep_mut(owner))
+    }
+
+    /// Removes component from self or nested compositions
+    pub fn take_component(&mut self, id: &usize) -> Option<Representation<Component>> {
+        if let Some(pos) = self.components.iter().position(|c| &c.sig().id == id) {
+            return Some(self.components.remove(pos));
+        }
+        self.compositions
+            .iter_mut()
+            .find_map(|c| c.origin_mut().take_component(id))
+    }
+
+    /// Removes nested composition
+    pub fn take_composition(&mut self, id: &usize) -> Option<Representation<Composition>> {
+        if let Some(pos) = self.compositions.iter().position(|c| &c.sig().id == id) {
+            return Some(self.compositions.remove(pos));
+        }
+        self.compositions
+            .iter_mut()
+            .find_map(|c| c.origin_mut().take_composition(id))
+    }
+
+    /// Removes connection from self or nested compositions
+    pub fn take_connection(&mut self, id: &usize) -> Option<Representation<Connection>> {
+        if let Some(pos) = self.connections.iter().position(|c| &c.sig().id == id) {
+            return Some(self.connections.remove(pos));
+        }
+        self.compositions
+            .iter_mut()
+            .find_map(|c| c.origin_mut().take_connection(id))
+    }
+
+    /// Looks for signature of any entity (self, nested compositions, components, ports
+    /// and connections)
+    pub fn find_sig_mut(&mut self, id: &usize) -> Option<&mut Signature> {
+        if &self.sig.id == id {
+            return Some(&mut self.sig);
+        }
+        if let Some(port) = self
+            .ports
+            .origin_mut()
+            .ports
+            .iter_mut()
+            .find(|p| &p.sig().id == id)
+        {
+            return Some(&mut port.origin_mut().sig);
+        }
+        for component in self.components.iter_mut() {
+            if &component.sig().id == id {
+                return Some(&mut component.origin_mut().sig);
+            }
+            if let Some(port) = component
+                .origin_mut()
+                .ports
+                .origin_mut()
+                .ports
+                .iter_mut()
+                .find(|p| &p.sig().id == id)
+            {
+                return Some(&mut port.origin_mut().sig);
+            }
+        }
+        if let Some(connection) = self.connections.iter_mut().find(|c| &c.sig().id == id) {
+            return Some(&mut connection.origin_mut().sig);
+        }
+        self.compositions
+            .iter_mut()
+            .find_map(|c| c.origin_mut().find_sig_mut(id))
+    }
+
+    /// Returns ids of all entities of composition (self, ports, components, connections
+    /// and nested compositions)
+    pub fn get_all_ids(&self) -> Vec<usize> {
+        let mut ids = vec![self.sig.id];
+        ids.extend(self.ports.origin().iter().map(|p| p.sig().id));
+        self.components.iter().for_each(|c| {
+            ids.push(c.sig().id);
+            ids.extend(c.origin().ports.origin().iter().map(|p| p.sig().id));
+        });
+        ids.extend(self.connections.iter().map(|c| c.sig().id));
+        self.compositions
+            .iter()
+            .for_each(|c| ids.extend(c.origin().get_all_ids()));
+        ids
+    }
+
+    /// Returns id of component or composition (self or nested), which owns port
+    pub fn find_port_owner(&self, port: &usize) -> Option<usize> {
+        if self.ports.origin().find(port).is_some() {
+            return Some(self.sig.id);
+        }
+        self.components
+            .iter()
+            .find(|c| c.origin().ports.origin().find(port).is_some())
+            .map(|c| c.sig().id)
+            .or_else(|| {
+                self.compositions
+                    .iter()
+                    .find_map(|c| c.origin().find_port_owner(port))
+            })
+    }
+
+    /// Returns id of composition (self or nested), which holds connection
+    pub fn find_connection_holder(&self, id: &usize) -> Option<usize> {
+        if self.connections.iter().any(|c| &c.sig().id == id) {
+            return Some(self.sig.id);
+        }
+        self.compositions
+            .iter()
+            .find_map(|c| c.origin().find_connection_holder(id))
+    }
+
+    /// Returns id of composition (self or nested), which holds component or nested composition
+    pub fn find_component_holder(&self, id: &usize) -> Option<usize> {
+        if self
+            .components
+            .iter()
+            .map(|c| c.sig())
+            .chain(self.compositions.iter().map(|c| c.sig()))
+            .any(|sig| &sig.id == id)
+        {
+            return Some(self.sig.id);
+        }
+        self.compositions
+            .iter()
+            .find_map(|c| c.origin().find_component_holder(id))
+    }
+
+    /// Removes all connections (in self and nested compositions) matched with predicate.
+    /// Returns removed connections with id of holder composition.
+    pub fn take_connections<F: Fn(&Connection) -> bool>(
+        &mut self,
+        predicate: &F,
+    ) -> Vec<(usize, Representation<Connection>)> {
+        let mut removed: Vec<(usize, Representation<Connection>)> = Vec::new();
+        let mut kept: Vec<Representation<Connection>> = Vec::new();
+        self.connections.drain(..).for_each(|c| {
+            if predicate(c.origin()) {
+                removed.push((self.sig.id, c));
+            } else {
+                kept.push(c);
+            }
+        });
+        self.connections = kept;
+        self.compositions.iter_mut().for_each(|c| {
+            removed.extend(c.origin_mut().take_connections(predicate));
+        });
+        removed
+    }
+
+    /// Updates counter of connections of port in scope of holder composition
+    pub fn count_connection(&mut self, owner: &usize, port: &usize, holder: usize, added: bool) {
+        let Some(port) = self
+            .find_ports_mut(owner)
+            .and_then(|ports| ports.find_mut(port))
+        else {
+            return;
+        };
+        let connected = &mut port.origin_mut().connected;
+        if added {
+            *connected.entry(holder).or_insert(0) += 1;
+        } else if let Some(count) = connected.get_mut(&holder) {
+            *count = count.saturating_sub(1);
+            if *count == 0 {
+                connected.remove(&holder);
+            }
+        }
+    }
+
+    /// Removes port of any component or composition
+    pub fn take_port(&mut self, owner: &usize, id: &usize) -> Option<Representation<Port>> {
+        self.find_ports_mut(owner).and_then(|ports| ports.take(id))
+    }
+
     pub fn get_port(&self, port_id: &usize) -> Option<&Port> {
         self.ports.origin().find(port_id).map(|r| r.origin())
     }
 
+    /// Looks for interface in own registry and in registries of nested compositions
+    pub fn find_interface(&self, id: &usize) -> Option<&Interface> {
+        self.interfaces
+            .iter()
+            .find(|interface| &interface.sig.id == id)
+            .or_else(|| {
+                self.compositions
+                    .iter()
+                    .find_map(|c| c.origin().find_interface(id))
+            })
+    }
+
+    /// Sets kind of interface to all ports, which refer to known interfaces
+    pub fn resolve_interfaces(&mut self, interfaces: &[Interface]) {
+        let interfaces = [interfaces, &self.interfaces].concat();
+        self.ports.origin_mut().set_interface_kinds(&interfaces);
+        self.components.iter_mut().for_each(|c| {
+            c.origin_mut()
+                .ports
+                .origin_mut()
+                .set_interface_kinds(&interfaces);
+        });
+        self.compositions.iter_mut().for_each(|c| {
+            c.origin_mut().resolve_interfaces(&interfaces);
+        });
+    }
+
+    /// Removes kinds of interfaces resolved on binding (see `resolve_interfaces`)
+    pub fn drop_resolved_kinds(&mut self) {
+        self.ports.origin_mut().drop_resolved_kinds();
+        self.components.iter_mut().for_each(|c| {
+            c.origin_mut().ports.origin_mut().drop_resolved_kinds();
+        });
+        self.compositions.iter_mut().for_each(|c| {
+            c.origin_mut().drop_resolved_kinds();
+        });
+    }
+
     pub fn get_ports_props(&self) -> EntityProps {
         let mut props = self.ports.origin().get_props();
         self.components.iter().for_each(|c| {
@@ -100,7 +100,7 @@ impl IsInputPort<Representation<Connection>> for &usize {
     }
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Joint {
     pub port: usize,
     pub component: usize,
@@ -118,7 +118,7 @@ impl Joint {
     }
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Connection {
     pub sig: Signature,
     pub joint_in: Joint,
@@ -187,9 +187,8 @@ impl Connection {
         });
         let mut components: Vec<(usize, usize, usize)> =
             map.into_values().collect::<Vec<(usize, usize, usize)>>();
-        components.sort_by(|(a, _, _), (b, _, _)| a.cmp(b));
-        components
-            .sort_by(|(_, a_in, a_out), (_, b_in, b_out)| (b_in + b_out).cmp(&(a_in + a_out)));
+        components.sort_by_key(|(id, _, _)| *id);
+        components.sort_by_key(|(_, ins, outs)| std::cmp::Reverse(ins + outs));
         components
     }
 
@@ -233,9 +232,8 @@ impl Connection {
         });
         let mut components: Vec<(usize, usize, usize)> =
             map.into_values().collect::<Vec<(usize, usize, usize)>>();
-        components.sort_by(|(a, _, _), (b, _, _)| a.cmp(b));
-        components
-            .sort_by(|(_, a_in, a_out), (_, b_in, b_out)| (b_in + b_out).cmp(&(a_in + a_out)));
+        components.sort_by_key(|(id, _, _)| *id);
+        components.sort_by_key(|(_, ins, outs)| std::cmp::Reverse(ins + outs));
         components
     }
 
@@ -23,6 +23,8 @@ impl Dummy<Port, ()> for Port {
             connected: HashMap::new(),
             visibility: true,
             label: None,
+            interface_kind: None,
+            interface_kind_resolved: false,
         }
     }
 }
@@ -0,0 +1,102 @@
+use crate::entity::{Signature, SignatureGetter};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum InterfaceKind {
+    SenderReceiver,
+    ClientServer,
+    ModeSwitch,
+    Parameter,
+    NvData,
+    Trigger,
+}
+
+impl InterfaceKind {
+    pub fn abbreviation(&self) -> &'static str {
+        match self {
+            Self::SenderReceiver => "SR",
+            Self::ClientServer => "CS",
+            Self::ModeSwitch => "MS",
+            Self::Parameter => "P",
+            Self::NvData => "NV",
+            Self::Trigger => "T",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ArgumentDirection {
+    In,
+    Out,
+    InOut,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataType {
+    pub sig: Signature,
+    // Category of type (VALUE, ARRAY, STRUCTURE, etc.)
+    pub category: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataElement {
+    pub sig: Signature,
+    pub data_type: Option<DataType>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Argument {
+    pub sig: Signature,
+    pub direction: ArgumentDirection,
+    pub data_type: Option<DataType>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Operation {
+    pub sig: Signature,
+    pub arguments: Vec<Argument>,
+}
+
+/// Port interface. Ports refer to interface by id of one of own interface
+/// signatures (provided, required or provided-required)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Interface {
+    pub sig: Signature,
+    pub kind: InterfaceKind,
+    #[serde(default)]
+    pub data_elements: Vec<DataElement>,
+    #[serde(default)]
+    pub operations: Vec<Operation>,
+}
+
+impl<'a, 'b: 'a> SignatureGetter<'a, 'b> for Interface {
+    fn sig(&'b self) -> &'a Signature {
+        &self.sig
+    }
+}
+
+impl Interface {
+    /// Interfaces are compatible if both have same kind and same class; for sender-receiver
+    /// and client-server interfaces also data elements and operations have to be matched
+    /// by names
+    pub fn is_compatible(&self, other: &Interface) -> bool {
+        if self.sig.id == other.sig.id {
+            return true;
+        }
+        if self.kind != other.kind || self.sig.class_name != other.sig.class_name {
+            return false;
+        }
+        let names = |sigs: Vec<&Signature>| {
+            let mut names = sigs
+                .iter()
+                .map(|sig| sig.short_name.to_owned())
+                .collect::<Vec<String>>();
+            names.sort();
+            names
+        };
+        names(self.data_elements.iter().map(|el| &el.sig).collect())
+            == names(other.data_elements.iter().map(|el| &el.sig).collect())
+            && names(self.operations.iter().map(|op| &op.sig).collect())
+                == names(other.operations.iter().map(|op| &op.sig).collect())
+    }
+}
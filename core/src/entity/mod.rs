@@ -2,6 +2,7 @@ mod comosition;
 mod component;
 mod connection;
 pub mod dummy;
+mod interface;
 mod port;
 
 use std::fmt::Display;
@@ -9,6 +10,7 @@ use std::fmt::Display;
 pub use comosition::*;
 pub use component::*;
 pub use connection::*;
+pub use interface::*;
 pub use port::*;
 use serde::{Deserialize, Serialize};
 
@@ -36,6 +38,46 @@ impl SignatureEqual for &usize {
         }
     }
 }
+/// Entity taken out of model with id of holder: composition for components, nested
+/// compositions and connections; owner (component or composition) for ports. Entities are
+/// kept without render parts, so they can be inserted back.
+#[derive(Debug, Deserialize, Serialize)]
+pub enum Removed {
+    Component(usize, Component),
+    Composition(usize, Composition),
+    Port(usize, Port),
+    Connection(usize, Connection),
+}
+
+impl Removed {
+    pub fn id(&self) -> usize {
+        match self {
+            Self::Component(_, component) => component.sig.id,
+            Self::Composition(_, composition) => composition.sig.id,
+            Self::Port(_, port) => port.sig.id,
+            Self::Connection(_, connection) => connection.sig.id,
+        }
+    }
+
+    /// Returns ids of entity and all entities included into it (ports, nested entities)
+    pub fn get_all_ids(&self) -> Vec<usize> {
+        match self {
+            Self::Component(_, component) => [
+                vec![component.sig.id],
+                component
+                    .ports
+                    .origin()
+                    .iter()
+                    .map(|p| p.sig().id)
+                    .collect(),
+            ]
+            .concat(),
+            Self::Composition(_, composition) => composition.get_all_ids(),
+            Self::Port(..) | Self::Connection(..) => vec![self.id()],
+        }
+    }
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Signature {
     pub id: usize,
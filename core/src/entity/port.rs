@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 
 use crate::{
-    entity::{EntityProps, Signature, SignatureGetter},
+    entity::{EntityProps, Interface, InterfaceKind, Signature, SignatureGetter},
     render::{options::Options, Representation},
 };
 use serde::{Deserialize, Serialize};
@@ -12,6 +12,13 @@ pub enum PortType {
     Right,
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum PortDirection {
+    Provided,
+    Required,
+    ProvidedRequired,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Port {
     pub sig: Signature,
@@ -24,6 +31,12 @@ pub struct Port {
     pub visibility: bool,
     #[serde(skip_serializing, skip_deserializing)]
     pub label: Option<String>,
+    // Kind of interface; resolved on binding by interface reference
+    #[serde(default)]
+    pub interface_kind: Option<InterfaceKind>,
+    // Kind of interface wasn't defined by model, but resolved on binding
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub interface_kind_resolved: bool,
 }
 
 impl<'a, 'b: 'a> SignatureGetter<'a, 'b> for Port {
@@ -42,6 +55,24 @@ impl Port {
             options.labels.port_label_max_len,
         )
     }
+    pub fn direction(&self) -> Option<PortDirection> {
+        if self.provided_required_interface.is_some() {
+            Some(PortDirection::ProvidedRequired)
+        } else if self.provided_interface.is_some() {
+            Some(PortDirection::Provided)
+        } else if self.required_interface.is_some() {
+            Some(PortDirection::Required)
+        } else {
+            None
+        }
+    }
+    /// Returns signature of interface used by port (ports refer to interface by this signature)
+    pub fn interface(&self) -> Option<&Signature> {
+        self.provided_required_interface
+            .as_ref()
+            .or(self.provided_interface.as_ref())
+            .or(self.required_interface.as_ref())
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -107,6 +138,17 @@ impl Ports {
         self.ports.iter().find(|p| &p.sig().id == port_id)
     }
 
+    pub fn find_mut(&mut self, port_id: &usize) -> Option<&mut Representation<Port>> {
+        self.ports.iter_mut().find(|p| &p.sig().id == port_id)
+    }
+
+    pub fn take(&mut self, port_id: &usize) -> Option<Representation<Port>> {
+        self.ports
+            .iter()
+            .position(|p| &p.sig().id == port_id)
+            .map(|pos| self.ports.remove(pos))
+    }
+
     pub fn get_props(&self) -> EntityProps {
         let mut props = EntityProps::default();
         self.ports.iter().for_each(|p| {
@@ -143,6 +185,14 @@ impl Ports {
         });
     }
 
+    pub fn show(&mut self, ids: &[usize]) {
+        self.ports.iter_mut().for_each(|port| {
+            if ids.contains(&port.sig().id) {
+                port.origin_mut().visibility = true;
+            }
+        });
+    }
+
     pub fn add(&mut self, port: Representation<Port>, pos: Option<usize>) {
         if let Some(pos) = pos {
             self.ports.insert(pos, port);
@@ -162,14 +212,53 @@ impl Ports {
         ports
     }
 
-    pub fn get_filtered_ports(&self, filter: &str) -> Vec<usize> {
-        fn is_filtered(filter: &str, origin: &Port) -> bool {
-            origin
-                .sig
-                .short_name
-                .to_lowercase()
-                .contains(&filter.to_lowercase())
-        }
+    /// Groups visible ports by interface id
+    pub fn get_grouped_by_interface(&self) -> Vec<(usize, Vec<usize>)> {
+        let mut ports: Vec<(usize, Vec<usize>)> = Vec::new();
+        self.ports.iter().for_each(|p| {
+            let Some(interface) = p.origin().interface() else {
+                return;
+            };
+            if let Some((_, ids)) = ports.iter_mut().find(|(id, _)| id == &interface.id) {
+                ids.push(p.sig().id);
+            } else {
+                ports.push((interface.id, vec![p.sig().id]));
+            }
+        });
+        ports
+    }
+
+    pub fn set_interface_kinds(&mut self, interfaces: &[Interface]) {
+        self.ports.iter_mut().for_each(|p| {
+            let port = p.origin_mut();
+            if port.interface_kind.is_some() {
+                return;
+            }
+            port.interface_kind = port.interface().and_then(|sig| {
+                interfaces
+                    .iter()
+                    .find(|interface| interface.sig.id == sig.id)
+                    .map(|interface| interface.kind.clone())
+            });
+            port.interface_kind_resolved = port.interface_kind.is_some();
+        });
+    }
+
+    /// Removes kinds of interfaces resolved on binding, so only kinds defined by model
+    /// are kept
+    pub fn drop_resolved_kinds(&mut self) {
+        self.ports.iter_mut().for_each(|p| {
+            let port = p.origin_mut();
+            if port.interface_kind_resolved {
+                port.interface_kind = None;
+                port.interface_kind_resolved = false;
+            }
+        });
+    }
+
+    /// Returns visible ports accepted by filter. Grouped port is accepted if any of
+    /// contained ports is accepted.
+    pub fn get_filtered_ports(&self, filter: &dyn Fn(&Port) -> bool) -> Vec<usize> {
         let ports = &self.ports;
         ports
             .iter()
@@ -178,12 +267,12 @@ impl Ports {
                 if !origin.visibility {
                     false
                 } else if origin.contains.is_empty() {
-                    is_filtered(filter, origin)
+                    filter(origin)
                 } else {
                     let mut found = false;
                     for port_id in origin.contains.iter() {
                         if let Some(port) = ports.iter().find(|p| &p.sig().id == port_id) {
-                            if is_filtered(filter, port.origin()) {
+                            if filter(port.origin()) {
                                 found = true;
                                 break;
                             }
@@ -196,28 +285,24 @@ impl Ports {
             .collect()
     }
 
-    pub fn get_filtered_ports_expanded(&self, filter: &str) -> Vec<(usize, Option<usize>)> {
-        fn is_filtered(filter: &str, origin: &Port) -> bool {
-            origin
-                .sig
-                .short_name
-                .to_lowercase()
-                .contains(&filter.to_lowercase())
-        }
+    pub fn get_filtered_ports_expanded(
+        &self,
+        filter: &dyn Fn(&Port) -> bool,
+    ) -> Vec<(usize, Option<usize>)> {
         let ports = &self.ports;
         let mut expanded = Vec::new();
         ports.iter().for_each(|port| {
             let origin = port.origin();
             if origin.visibility {
                 if origin.contains.is_empty() {
-                    if is_filtered(filter, origin) {
+                    if filter(origin) {
                         expanded.push((port.sig().id, None));
                     }
                 } else {
                     let parent = Some(port.sig().id);
                     origin.contains.iter().for_each(|port_id| {
                         if let Some(port) = ports.iter().find(|p| &p.sig().id == port_id) {
-                            if is_filtered(filter, port.origin()) {
+                            if filter(port.origin()) {
                                 expanded.push((*port_id, parent));
                             }
                         }
@@ -14,14 +14,39 @@ pub enum E {
     RenderNotInited,
     #[error("Form isn't belong to grid")]
     NotGridForm,
+    #[error("Entity {0} isn't found")]
+    NotFound(usize),
+    #[error("Entity {0} already exists")]
+    AlreadyExists(usize),
+    #[error("Entity {0} is grouped entity and cannot be modified directly")]
+    GroupedEntity(usize),
     #[error("Static error message")]
     NotSupported,
     #[error("{0}")]
     Other(String),
 }
 
+#[derive(Error, Debug)]
+pub enum SnapshotError {
+    // Snapshot is damaged (truncated, modified or isn't a snapshot at all)
+    #[error("Snapshot is corrupted: {0}")]
+    Corrupted(String),
+    // Snapshot has been created by old version, which cannot be migrated
+    #[error("Snapshot format version {0} is outdated and cannot be migrated")]
+    Outdated(u16),
+    // Snapshot has been created by newer version of board
+    #[error("Snapshot format version {0} isn't supported; latest supported version is {1}")]
+    Unsupported(u16, u16),
+}
+
 impl From<E> for std::string::String {
     fn from(value: E) -> Self {
         value.to_string()
     }
 }
+
+impl From<SnapshotError> for std::string::String {
+    fn from(value: SnapshotError) -> Self {
+        value.to_string()
+    }
+}
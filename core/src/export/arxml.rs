@@ -0,0 +1,641 @@
+use crate::{
+    entity::{
+        ArgumentDirection, Composition, Connection, DataType, Interface, InterfaceKind, Port,
+        PortDirection, PortType, Ports, Signature,
+    },
+    export::escape_xml,
+};
+use std::collections::{HashMap, HashSet};
+
+const SCHEMA: &str = "http://autosar.org/schema/r4.0";
+const ROOT_PACKAGE: &str = "Board";
+const TYPES_PACKAGE: &str = "ComponentTypes";
+const INTERFACES_PACKAGE: &str = "Interfaces";
+const DATA_TYPES_PACKAGE: &str = "DataTypes";
+
+struct Writer {
+    out: String,
+    depth: usize,
+}
+
+impl Writer {
+    fn new() -> Self {
+        Self {
+            out: String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n"),
+            depth: 0,
+        }
+    }
+
+    fn indent(&mut self) {
+        self.out.push_str(&"  ".repeat(self.depth));
+    }
+
+    fn open(&mut self, tag: &str, attrs: &[(&str, String)]) {
+        self.indent();
+        self.out.push_str(&format!("<{tag}{}>\n", as_attrs(attrs)));
+        self.depth += 1;
+    }
+
+    fn close(&mut self, tag: &str) {
+        self.depth -= 1;
+        self.indent();
+        self.out.push_str(&format!("</{tag}>\n"));
+    }
+
+    fn leaf(&mut self, tag: &str, attrs: &[(&str, String)], text: &str) {
+        self.indent();
+        self.out.push_str(&format!(
+            "<{tag}{}>{}</{tag}>\n",
+            as_attrs(attrs),
+            escape_xml(text)
+        ));
+    }
+
+    fn short_name(&mut self, sig: &Signature) {
+        self.leaf("SHORT-NAME", &[], &short_name(sig));
+    }
+}
+
+fn as_attrs(attrs: &[(&str, String)]) -> String {
+    attrs
+        .iter()
+        .map(|(name, value)| format!(" {name}=\"{}\"", escape_xml(value)))
+        .collect::<String>()
+}
+
+/// AUTOSAR short names allow only letters, digits and underscore
+fn short_name(sig: &Signature) -> String {
+    as_short_name(&sig.short_name)
+}
+
+fn as_short_name(text: &str) -> String {
+    let name = text
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect::<String>();
+    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{name}")
+    } else {
+        name
+    }
+}
+
+fn uuid(sig: &Signature) -> [(&'static str, String); 1] {
+    [("UUID", sig.id.to_string())]
+}
+
+fn interface_tag(kind: &InterfaceKind) -> &'static str {
+    match kind {
+        InterfaceKind::SenderReceiver => "SENDER-RECEIVER-INTERFACE",
+        InterfaceKind::ClientServer => "CLIENT-SERVER-INTERFACE",
+        InterfaceKind::ModeSwitch => "MODE-SWITCH-INTERFACE",
+        InterfaceKind::Parameter => "PARAMETER-INTERFACE",
+        InterfaceKind::NvData => "NV-DATA-INTERFACE",
+        InterfaceKind::Trigger => "TRIGGER-INTERFACE",
+    }
+}
+
+/// Returns tag of port prototype. Ports without interface are defined by side of port
+fn port_tag(port: &Port) -> &'static str {
+    match port.direction() {
+        Some(PortDirection::Provided) => "P-PORT-PROTOTYPE",
+        Some(PortDirection::Required) => "R-PORT-PROTOTYPE",
+        Some(PortDirection::ProvidedRequired) => "PR-PORT-PROTOTYPE",
+        None => match port.port_type {
+            PortType::Left => "R-PORT-PROTOTYPE",
+            PortType::Right => "P-PORT-PROTOTYPE",
+        },
+    }
+}
+
+/// Name of component type is taken from class of component
+fn type_name(sig: &Signature) -> String {
+    if sig.class_name.is_empty() {
+        format!("{}_Type", short_name(sig))
+    } else {
+        as_short_name(&sig.class_name)
+    }
+}
+
+/// Returns name, which isn't used in package yet; id of entity is added on collision
+fn unique(used: &mut HashSet<String>, name: String, id: usize) -> String {
+    let name = if used.contains(&name) {
+        format!("{name}_{id}")
+    } else {
+        name
+    };
+    used.insert(name.clone());
+    name
+}
+
+/// Names of packages and elements. Each composition gets own package with types of
+/// composition and its components; packages of nested compositions are placed into
+/// package of parent composition, so same-named components of different compositions
+/// don't collide. Components of same class with same ports share one type. Names of
+/// prototypes and ports are unique in scope of holder type.
+#[derive(Default)]
+struct Paths<'a> {
+    // Composition => name of package
+    packages: HashMap<usize, String>,
+    // Component or composition => (path of package, name of type)
+    types: HashMap<usize, (String, String)>,
+    // Components, which types are written (first component of each shared type)
+    defined: HashSet<usize>,
+    // Component or composition => name of prototype in holder composition
+    prototypes: HashMap<usize, String>,
+    // Port => name of port in type of owner
+    ports: HashMap<usize, String>,
+    // Interface => (name, interface)
+    interfaces: HashMap<usize, (String, &'a Interface)>,
+    // Data type => name
+    data_types: HashMap<usize, String>,
+    // Interfaces and data types in order of writing
+    interfaces_order: Vec<usize>,
+    data_types_order: Vec<&'a DataType>,
+}
+
+impl<'a> Paths<'a> {
+    fn new(composition: &'a Composition) -> Self {
+        let mut paths = Paths::default();
+        paths.collect_types(
+            composition,
+            TYPES_PACKAGE.to_owned(),
+            format!("/{ROOT_PACKAGE}/{TYPES_PACKAGE}"),
+        );
+        let mut interfaces: Vec<&Interface> = Vec::new();
+        collect_interfaces(composition, &mut interfaces);
+        let mut used: HashSet<String> = HashSet::new();
+        let mut used_data_types: HashSet<String> = HashSet::new();
+        for interface in interfaces {
+            let name = unique(&mut used, short_name(&interface.sig), interface.sig.id);
+            paths.interfaces.insert(interface.sig.id, (name, interface));
+            paths.interfaces_order.push(interface.sig.id);
+            let data_types = interface
+                .data_elements
+                .iter()
+                .filter_map(|el| el.data_type.as_ref())
+                .chain(
+                    interface
+                        .operations
+                        .iter()
+                        .flat_map(|op| op.arguments.iter())
+                        .filter_map(|arg| arg.data_type.as_ref()),
+                );
+            for data_type in data_types {
+                if paths.data_types.contains_key(&data_type.sig.id) {
+                    continue;
+                }
+                let name = unique(
+                    &mut used_data_types,
+                    short_name(&data_type.sig),
+                    data_type.sig.id,
+                );
+                paths.data_types.insert(data_type.sig.id, name);
+                paths.data_types_order.push(data_type);
+            }
+        }
+        paths
+    }
+
+    fn collect_types(&mut self, composition: &Composition, name: String, path: String) {
+        // Elements and nested packages share names space of package
+        let mut used: HashSet<String> = HashSet::new();
+        // (class, ports) => name of shared type
+        let mut shared: HashMap<(String, Vec<String>), String> = HashMap::new();
+        // Prototypes share names space of composition type
+        let mut prototypes: HashSet<String> = HashSet::new();
+        self.packages.insert(composition.sig.id, name);
+        self.collect_ports(composition.ports.origin());
+        let name = unique(&mut used, type_name(&composition.sig), composition.sig.id);
+        self.types.insert(composition.sig.id, (path.clone(), name));
+        for component in composition.components.iter() {
+            let sig = component.sig();
+            let ports = self.collect_ports(component.origin().ports.origin());
+            let prototype = unique(&mut prototypes, short_name(sig), sig.id);
+            self.prototypes.insert(sig.id, prototype);
+            let name = match shared.get(&(sig.class_name.to_owned(), ports.clone())) {
+                Some(name) => name.to_owned(),
+                None => {
+                    let name = unique(&mut used, type_name(sig), sig.id);
+                    shared.insert((sig.class_name.to_owned(), ports), name.clone());
+                    self.defined.insert(sig.id);
+                    name
+                }
+            };
+            self.types.insert(sig.id, (path.clone(), name));
+        }
+        let nested = composition
+            .compositions
+            .iter()
+            .map(|c| {
+                let sig = c.sig();
+                let prototype = unique(&mut prototypes, short_name(sig), sig.id);
+                self.prototypes.insert(sig.id, prototype);
+                (c.origin(), unique(&mut used, short_name(sig), sig.id))
+            })
+            .collect::<Vec<(&Composition, String)>>();
+        for (nested, name) in nested {
+            let nested_path = format!("{path}/{name}");
+            self.collect_types(nested, name, nested_path);
+        }
+    }
+
+    /// Names ports of owner; returns description of ports to find components, which
+    /// could share type
+    fn collect_ports(&mut self, ports: &Ports) -> Vec<String> {
+        let mut used: HashSet<String> = HashSet::new();
+        ports
+            .iter()
+            .map(|p| p.origin())
+            .map(|port| {
+                let name = unique(&mut used, short_name(&port.sig), port.sig.id);
+                self.ports.insert(port.sig.id, name.clone());
+                format!(
+                    "{name}:{}:{:?}",
+                    port_tag(port),
+                    port.interface().map(|sig| sig.id)
+                )
+            })
+            .collect()
+    }
+
+    fn port_name(&self, port: &Port) -> String {
+        self.ports
+            .get(&port.sig.id)
+            .cloned()
+            .unwrap_or_else(|| short_name(&port.sig))
+    }
+
+    fn prototype_name(&self, sig: &Signature) -> String {
+        self.prototypes
+            .get(&sig.id)
+            .cloned()
+            .unwrap_or_else(|| short_name(sig))
+    }
+
+    fn type_name(&self, sig: &Signature) -> String {
+        self.types
+            .get(&sig.id)
+            .map(|(_, name)| name.to_owned())
+            .unwrap_or_default()
+    }
+
+    fn type_path(&self, sig: &Signature) -> String {
+        self.types
+            .get(&sig.id)
+            .map(|(path, name)| format!("{path}/{name}"))
+            .unwrap_or_default()
+    }
+
+    fn data_type_path(&self, data_type: &DataType) -> Option<String> {
+        self.data_types
+            .get(&data_type.sig.id)
+            .map(|name| format!("/{ROOT_PACKAGE}/{DATA_TYPES_PACKAGE}/{name}"))
+    }
+}
+
+fn write_ports(w: &mut Writer, ports: &Ports, paths: &Paths) {
+    w.open("PORTS", &[]);
+    for port in ports.iter().map(|p| p.origin()) {
+        let tag = port_tag(port);
+        w.open(tag, &uuid(&port.sig));
+        w.leaf("SHORT-NAME", &[], &paths.port_name(port));
+        let refs = [
+            ("PROVIDED-INTERFACE-TREF", &port.provided_interface),
+            ("REQUIRED-INTERFACE-TREF", &port.required_interface),
+            (
+                "PROVIDED-REQUIRED-INTERFACE-TREF",
+                &port.provided_required_interface,
+            ),
+        ];
+        for (ref_tag, interface) in refs.iter() {
+            // Interfaces, which aren't registered in composition, aren't written, so
+            // references to it are skipped
+            let Some((name, interface)) = interface
+                .as_ref()
+                .and_then(|sig| paths.interfaces.get(&sig.id))
+            else {
+                continue;
+            };
+            w.leaf(
+                ref_tag,
+                &[("DEST", interface_tag(&interface.kind).to_owned())],
+                &format!("/{ROOT_PACKAGE}/{INTERFACES_PACKAGE}/{name}"),
+            );
+        }
+        w.close(tag);
+    }
+    w.close("PORTS");
+}
+
+fn port_ref(paths: &Paths, owner: &Signature, port: &Port) -> String {
+    format!("{}/{}", paths.type_path(owner), paths.port_name(port))
+}
+
+fn write_connector(
+    w: &mut Writer,
+    composition: &Composition,
+    connection: &Connection,
+    paths: &Paths,
+) {
+    let find = |owner: &usize, port: &usize| -> Option<(Signature, Port, bool)> {
+        if owner == &composition.sig.id {
+            return composition
+                .get_port(port)
+                .map(|p| (composition.sig.clone(), p.clone(), true));
+        }
+        composition
+            .components
+            .iter()
+            .map(|c| (c.sig(), &c.origin().ports))
+            .chain(
+                composition
+                    .compositions
+                    .iter()
+                    .map(|c| (c.sig(), &c.origin().ports)),
+            )
+            .find(|(sig, _)| &sig.id == owner)
+            .and_then(|(sig, ports)| {
+                ports
+                    .origin()
+                    .find(port)
+                    .map(|p| (sig.clone(), p.origin().clone(), false))
+            })
+    };
+    let (Some(mut out), Some(mut inp)) = (
+        find(&connection.joint_out.component, &connection.joint_out.port),
+        find(&connection.joint_in.component, &connection.joint_in.port),
+    ) else {
+        return;
+    };
+    // Provider is expected on joint_out, but directions of ports have priority
+    if matches!(out.1.direction(), Some(PortDirection::Required))
+        || matches!(inp.1.direction(), Some(PortDirection::Provided))
+    {
+        std::mem::swap(&mut out, &mut inp);
+    }
+    let target = |port: &Port| {
+        if matches!(port_tag(port), "R-PORT-PROTOTYPE") {
+            ("TARGET-R-PORT-REF", "R-PORT-PROTOTYPE")
+        } else {
+            ("TARGET-P-PORT-REF", port_tag(port))
+        }
+    };
+    let prototype_ref = |owner: &Signature| {
+        format!(
+            "{}/{}",
+            paths.type_path(&composition.sig),
+            paths.prototype_name(owner)
+        )
+    };
+    match (out.2, inp.2) {
+        (false, false) => {
+            w.open("ASSEMBLY-SW-CONNECTOR", &uuid(&connection.sig));
+            w.short_name(&connection.sig);
+            for (tag, target_tag, (owner, port, _)) in [
+                ("PROVIDER-IREF", "TARGET-P-PORT-REF", &out),
+                ("REQUESTER-IREF", "TARGET-R-PORT-REF", &inp),
+            ] {
+                let dest = port_tag(port);
+                w.open(tag, &[]);
+                w.leaf(
+                    "CONTEXT-COMPONENT-REF",
+                    &[("DEST", String::from("SW-COMPONENT-PROTOTYPE"))],
+                    &prototype_ref(owner),
+                );
+                w.leaf(
+                    target_tag,
+                    &[("DEST", dest.to_owned())],
+                    &port_ref(paths, owner, port),
+                );
+                w.close(tag);
+            }
+            w.close("ASSEMBLY-SW-CONNECTOR");
+        }
+        (outer_is_out, _) => {
+            let ((outer_owner, outer_port, _), (inner_owner, inner_port, _)) = if outer_is_out {
+                (&out, &inp)
+            } else {
+                (&inp, &out)
+            };
+            let (target_tag, dest) = target(inner_port);
+            let instance_tag = if target_tag == "TARGET-R-PORT-REF" {
+                "R-PORT-IN-COMPOSITION-INSTANCE-REF"
+            } else {
+                "P-PORT-IN-COMPOSITION-INSTANCE-REF"
+            };
+            w.open("DELEGATION-SW-CONNECTOR", &uuid(&connection.sig));
+            w.short_name(&connection.sig);
+            w.open("INNER-PORT-IREF", &[]);
+            w.open(instance_tag, &[]);
+            w.leaf(
+                "CONTEXT-COMPONENT-REF",
+                &[("DEST", String::from("SW-COMPONENT-PROTOTYPE"))],
+                &prototype_ref(inner_owner),
+            );
+            w.leaf(
+                target_tag,
+                &[("DEST", dest.to_owned())],
+                &port_ref(paths, inner_owner, inner_port),
+            );
+            w.close(instance_tag);
+            w.close("INNER-PORT-IREF");
+            w.leaf(
+                "OUTER-PORT-REF",
+                &[("DEST", port_tag(outer_port).to_owned())],
+                &port_ref(paths, outer_owner, outer_port),
+            );
+            w.close("DELEGATION-SW-CONNECTOR");
+        }
+    }
+}
+
+/// Writes package of composition: type of composition, types of components and packages
+/// of nested compositions
+fn write_package(w: &mut Writer, composition: &Composition, paths: &Paths) {
+    w.open("AR-PACKAGE", &[]);
+    w.leaf(
+        "SHORT-NAME",
+        &[],
+        paths
+            .packages
+            .get(&composition.sig.id)
+            .map_or(TYPES_PACKAGE, |name| name.as_str()),
+    );
+    w.open("ELEMENTS", &[]);
+    write_composition(w, composition, paths);
+    w.close("ELEMENTS");
+    if !composition.compositions.is_empty() {
+        w.open("AR-PACKAGES", &[]);
+        for nested in composition.compositions.iter() {
+            write_package(w, nested.origin(), paths);
+        }
+        w.close("AR-PACKAGES");
+    }
+    w.close("AR-PACKAGE");
+}
+
+fn write_composition(w: &mut Writer, composition: &Composition, paths: &Paths) {
+    w.open("COMPOSITION-SW-COMPONENT-TYPE", &uuid(&composition.sig));
+    w.leaf("SHORT-NAME", &[], &paths.type_name(&composition.sig));
+    write_ports(w, composition.ports.origin(), paths);
+    w.open("COMPONENTS", &[]);
+    for (sig, dest) in composition
+        .components
+        .iter()
+        .map(|c| (c.sig(), "APPLICATION-SW-COMPONENT-TYPE"))
+        .chain(
+            composition
+                .compositions
+                .iter()
+                .map(|c| (c.sig(), "COMPOSITION-SW-COMPONENT-TYPE")),
+        )
+    {
+        w.open("SW-COMPONENT-PROTOTYPE", &uuid(sig));
+        w.leaf("SHORT-NAME", &[], &paths.prototype_name(sig));
+        w.leaf(
+            "TYPE-TREF",
+            &[("DEST", dest.to_owned())],
+            &paths.type_path(sig),
+        );
+        w.close("SW-COMPONENT-PROTOTYPE");
+    }
+    w.close("COMPONENTS");
+    w.open("CONNECTORS", &[]);
+    for connection in composition.connections.iter() {
+        write_connector(w, composition, connection.origin(), paths);
+    }
+    w.close("CONNECTORS");
+    w.close("COMPOSITION-SW-COMPONENT-TYPE");
+    for component in composition
+        .components
+        .iter()
+        .map(|c| c.origin())
+        .filter(|c| paths.defined.contains(&c.sig.id))
+    {
+        w.open("APPLICATION-SW-COMPONENT-TYPE", &[]);
+        w.leaf("SHORT-NAME", &[], &paths.type_name(&component.sig));
+        write_ports(w, component.ports.origin(), paths);
+        w.close("APPLICATION-SW-COMPONENT-TYPE");
+    }
+}
+
+fn collect_interfaces<'a>(composition: &'a Composition, found: &mut Vec<&'a Interface>) {
+    composition.interfaces.iter().for_each(|interface| {
+        if !found.iter().any(|i| i.sig.id == interface.sig.id) {
+            found.push(interface);
+        }
+    });
+    composition
+        .compositions
+        .iter()
+        .for_each(|c| collect_interfaces(c.origin(), found));
+}
+
+fn write_data_type_ref(w: &mut Writer, data_type: &Option<DataType>, paths: &Paths) {
+    if let Some(path) = data_type
+        .as_ref()
+        .and_then(|data_type| paths.data_type_path(data_type))
+    {
+        w.leaf(
+            "TYPE-TREF",
+            &[("DEST", String::from("IMPLEMENTATION-DATA-TYPE"))],
+            &path,
+        );
+    }
+}
+
+fn write_interface(w: &mut Writer, name: &str, interface: &Interface, paths: &Paths) {
+    let tag = interface_tag(&interface.kind);
+    w.open(tag, &uuid(&interface.sig));
+    w.leaf("SHORT-NAME", &[], name);
+    if !interface.data_elements.is_empty() {
+        w.open("DATA-ELEMENTS", &[]);
+        for element in interface.data_elements.iter() {
+            w.open("VARIABLE-DATA-PROTOTYPE", &uuid(&element.sig));
+            w.short_name(&element.sig);
+            write_data_type_ref(w, &element.data_type, paths);
+            w.close("VARIABLE-DATA-PROTOTYPE");
+        }
+        w.close("DATA-ELEMENTS");
+    }
+    if !interface.operations.is_empty() {
+        w.open("OPERATIONS", &[]);
+        for operation in interface.operations.iter() {
+            w.open("CLIENT-SERVER-OPERATION", &uuid(&operation.sig));
+            w.short_name(&operation.sig);
+            w.open("ARGUMENTS", &[]);
+            for argument in operation.arguments.iter() {
+                w.open("ARGUMENT-DATA-PROTOTYPE", &uuid(&argument.sig));
+                w.short_name(&argument.sig);
+                write_data_type_ref(w, &argument.data_type, paths);
+                w.leaf(
+                    "DIRECTION",
+                    &[],
+                    match argument.direction {
+                        ArgumentDirection::In => "IN",
+                        ArgumentDirection::Out => "OUT",
+                        ArgumentDirection::InOut => "INOUT",
+                    },
+                );
+                w.close("ARGUMENT-DATA-PROTOTYPE");
+            }
+            w.close("ARGUMENTS");
+            w.close("CLIENT-SERVER-OPERATION");
+        }
+        w.close("OPERATIONS");
+    }
+    w.close(tag);
+}
+
+/// Writes composition (without entities created by render) as ARXML document. Component
+/// prototypes refer to types named by classes in package of holder composition;
+/// interfaces and data types used by them get own packages. Ids of entities are kept as
+/// UUID.
+pub fn write(composition: &Composition) -> String {
+    let paths = Paths::new(composition);
+    let mut w = Writer::new();
+    w.open("AUTOSAR", &[("xmlns", SCHEMA.to_owned())]);
+    w.open("AR-PACKAGES", &[]);
+    w.open("AR-PACKAGE", &[]);
+    w.leaf("SHORT-NAME", &[], ROOT_PACKAGE);
+    w.open("AR-PACKAGES", &[]);
+    write_package(&mut w, composition, &paths);
+    w.open("AR-PACKAGE", &[]);
+    w.leaf("SHORT-NAME", &[], INTERFACES_PACKAGE);
+    w.open("ELEMENTS", &[]);
+    for id in paths.interfaces_order.iter() {
+        if let Some((name, interface)) = paths.interfaces.get(id) {
+            write_interface(&mut w, name, interface, &paths);
+        }
+    }
+    w.close("ELEMENTS");
+    w.close("AR-PACKAGE");
+    w.open("AR-PACKAGE", &[]);
+    w.leaf("SHORT-NAME", &[], DATA_TYPES_PACKAGE);
+    w.open("ELEMENTS", &[]);
+    for data_type in paths.data_types_order.iter() {
+        w.open("IMPLEMENTATION-DATA-TYPE", &uuid(&data_type.sig));
+        w.leaf(
+            "SHORT-NAME",
+            &[],
+            paths
+                .data_types
+                .get(&data_type.sig.id)
+                .map_or("", |name| name.as_str()),
+        );
+        w.leaf(
+            "CATEGORY",
+            &[],
+            data_type.category.as_deref().unwrap_or("VALUE"),
+        );
+        w.close("IMPLEMENTATION-DATA-TYPE");
+    }
+    w.close("ELEMENTS");
+    w.close("AR-PACKAGE");
+    w.close("AR-PACKAGES");
+    w.close("AR-PACKAGE");
+    w.close("AR-PACKAGES");
+    w.close("AUTOSAR");
+    w.out
+}
@@ -0,0 +1,21 @@
+/// Quotes value if it contains separator, quotes or line breaks (RFC 4180)
+fn escape(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_owned()
+    }
+}
+
+/// Writes table as CSV document with header line
+pub fn write(header: &[&str], rows: &[Vec<String>]) -> String {
+    let mut out = String::new();
+    let mut line = |values: &mut dyn Iterator<Item = String>| {
+        out.push_str(&values.collect::<Vec<String>>().join(","));
+        out.push_str("\r\n");
+    };
+    line(&mut header.iter().map(|v| escape(v)));
+    rows.iter()
+        .for_each(|row| line(&mut row.iter().map(|v| escape(v))));
+    out
+}
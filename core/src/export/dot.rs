@@ -0,0 +1,115 @@
+use crate::{
+    entity::{Component, Composition, Connection, PortType, Ports, Signature},
+    export::{walk, Graph},
+};
+
+/// Escapes text of record label (fields and ports are separated by special characters);
+/// quotes are escaped too, because label is placed into DOT string as is
+fn escape_record(value: &str) -> String {
+    value
+        .chars()
+        .flat_map(|c| match c {
+            '{' | '}' | '|' | '<' | '>' | '\\' | '"' => vec!['\\', c],
+            _ => vec![c],
+        })
+        .collect()
+}
+
+/// Escapes DOT string; backslash is escaped first to not escape closing quote
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn indent(depth: usize) -> String {
+    "  ".repeat(depth)
+}
+
+struct Dot {
+    out: String,
+}
+
+impl Dot {
+    /// Record node: left ports, name of owner, right ports
+    fn node(&mut self, depth: usize, sig: &Signature, ports: &Ports, shape: &str) {
+        let side = |port_type: PortType| {
+            ports
+                .iter()
+                .map(|p| p.origin())
+                .filter(|p| p.port_type == port_type)
+                .map(|p| format!("<p{}> {}", p.sig.id, escape_record(&p.sig.short_name)))
+                .collect::<Vec<String>>()
+                .join("|")
+        };
+        let mut fields: Vec<String> = Vec::new();
+        let left = side(PortType::Left);
+        if !left.is_empty() {
+            fields.push(format!("{{{left}}}"));
+        }
+        fields.push(escape_record(&sig.short_name));
+        let right = side(PortType::Right);
+        if !right.is_empty() {
+            fields.push(format!("{{{right}}}"));
+        }
+        self.out.push_str(&format!(
+            "{}n{} [shape={shape}, label=\"{{{}}}\", tooltip=\"{}\"];\n",
+            indent(depth),
+            sig.id,
+            fields.join("|"),
+            escape(&sig.class_name),
+        ));
+    }
+}
+
+impl Graph for Dot {
+    // Ports of composition are represented by own node inside of cluster
+    fn composition(&mut self, composition: &Composition, depth: usize) {
+        self.node(
+            depth,
+            &composition.sig,
+            composition.ports.origin(),
+            "Mrecord",
+        );
+    }
+
+    fn component(&mut self, component: &Component, depth: usize) {
+        self.node(depth, &component.sig, component.ports.origin(), "record");
+    }
+
+    fn nested(&mut self, nested: &Composition, depth: usize) {
+        let indent = indent(depth);
+        self.out
+            .push_str(&format!("{indent}subgraph cluster_{} {{\n", nested.sig.id));
+        self.out.push_str(&format!(
+            "{indent}  label=\"{}\";\n",
+            escape(&nested.sig.short_name)
+        ));
+    }
+
+    fn nested_end(&mut self, _nested: &Composition, depth: usize) {
+        self.out.push_str(&format!("{}}}\n", indent(depth)));
+    }
+
+    fn connection(&mut self, c: &Connection, depth: usize) {
+        self.out.push_str(&format!(
+            "{}n{}:p{} -> n{}:p{} [id=\"c{}\"];\n",
+            indent(depth),
+            c.joint_out.component,
+            c.joint_out.port,
+            c.joint_in.component,
+            c.joint_in.port,
+            c.sig.id
+        ));
+    }
+}
+
+/// Writes composition as GraphViz DOT document. Components are record nodes with ports
+/// as fields, nested compositions are clusters.
+pub fn write(composition: &Composition) -> String {
+    let mut dot = Dot {
+        out: format!("digraph \"{}\" {{\n", escape(&composition.sig.short_name)),
+    };
+    dot.out.push_str("  rankdir=LR;\n  node [fontsize=10];\n");
+    walk(&mut dot, composition, 1);
+    dot.out.push_str("}\n");
+    dot.out
+}
@@ -0,0 +1,183 @@
+use crate::{
+    entity::{Component, Composition, Connection, Interface, Joint, Port, Ports, Signature},
+    render::Representation,
+};
+use std::collections::{HashMap, HashSet};
+
+/// Builds composition from selected components (and nested compositions) of given
+/// composition. Connections between selected components are kept. If `boundary` is
+/// true, connections to not selected components are kept as connections to ports of
+/// extracted composition (boundary ports); `next_sig` is used to get signatures for it.
+pub fn extract(
+    composition: Composition,
+    selected: &[usize],
+    boundary: bool,
+    next_sig: &mut dyn FnMut(&str) -> Signature,
+) -> Composition {
+    let id = composition.sig.id;
+    let mut extracted = Composition::new(composition.sig.clone());
+    extracted.parent = composition.parent;
+    let mut outer: HashMap<usize, (Signature, Port)> = HashMap::new();
+    let find_port = |owner: &usize, port: &usize| -> Option<(Signature, Port)> {
+        if owner == &id {
+            return composition
+                .get_port(port)
+                .map(|p| (composition.sig.clone(), p.clone()));
+        }
+        composition
+            .components
+            .iter()
+            .map(|c| (c.sig(), &c.origin().ports))
+            .chain(
+                composition
+                    .compositions
+                    .iter()
+                    .map(|c| (c.sig(), &c.origin().ports)),
+            )
+            .find(|(sig, _)| &sig.id == owner)
+            .and_then(|(sig, ports)| {
+                ports
+                    .origin()
+                    .find(port)
+                    .map(|p| (sig.clone(), p.origin().clone()))
+            })
+    };
+    let mut connections: Vec<Connection> = Vec::new();
+    for connection in composition.connections.iter().map(|c| c.origin()) {
+        let joints = (
+            selected.contains(&connection.joint_in.component),
+            selected.contains(&connection.joint_out.component),
+        );
+        match joints {
+            (true, true) => connections.push(connection.clone()),
+            (false, false) => {}
+            (in_selected, _) if boundary => {
+                let (inner, external) = if in_selected {
+                    (&connection.joint_in, &connection.joint_out)
+                } else {
+                    (&connection.joint_out, &connection.joint_in)
+                };
+                let (Some((owner, external_port)), Some((_, inner_port))) = (
+                    find_port(&external.component, &external.port),
+                    find_port(&inner.component, &inner.port),
+                ) else {
+                    continue;
+                };
+                // Boundary port is a copy of inner port (to keep direction and interface)
+                // named by port of not selected component
+                let (port_sig, _) = outer.entry(external.port).or_insert_with(|| {
+                    let mut sig = next_sig("boundary port");
+                    sig.class_name = external_port.sig.class_name.to_owned();
+                    sig.short_name = if owner.id == id {
+                        external_port.sig.short_name.to_owned()
+                    } else {
+                        format!("{}_{}", owner.short_name, external_port.sig.short_name)
+                    };
+                    let mut port = inner_port.clone();
+                    port.sig = sig.clone();
+                    port.contains = Vec::new();
+                    port.connected = HashMap::new();
+                    port.visibility = true;
+                    (sig, port)
+                });
+                let boundary_joint = Joint::new(port_sig.id, id);
+                let inner_joint = Joint::new(inner.port, inner.component);
+                connections.push(Connection {
+                    sig: connection.sig.clone(),
+                    joint_in: if in_selected {
+                        inner_joint.clone()
+                    } else {
+                        boundary_joint.clone()
+                    },
+                    joint_out: if in_selected {
+                        boundary_joint
+                    } else {
+                        inner_joint
+                    },
+                    visibility: true,
+                });
+            }
+            _ => {}
+        }
+    }
+    // Take selected entities
+    let Composition {
+        components,
+        compositions,
+        interfaces,
+        ..
+    } = composition;
+    extracted.components = components
+        .into_iter()
+        .filter(|c| selected.contains(&c.sig().id))
+        .collect::<Vec<Representation<Component>>>();
+    extracted.compositions = compositions
+        .into_iter()
+        .filter(|c| selected.contains(&c.sig().id))
+        .collect::<Vec<Representation<Composition>>>();
+    let mut boundary_ports = outer.into_values().collect::<Vec<(Signature, Port)>>();
+    boundary_ports.sort_by_key(|(sig, _)| sig.id);
+    boundary_ports.into_iter().for_each(|(_, port)| {
+        extracted.ports.origin_mut().push(port);
+    });
+    // Recount connections in scope of extracted composition
+    let mut counts: HashMap<(usize, usize), usize> = HashMap::new();
+    connections.iter().for_each(|c| {
+        for joint in [&c.joint_in, &c.joint_out] {
+            *counts.entry((joint.component, joint.port)).or_insert(0) += 1;
+        }
+    });
+    let owners = [
+        vec![id],
+        extracted.components.iter().map(|c| c.sig().id).collect(),
+        extracted.compositions.iter().map(|c| c.sig().id).collect(),
+    ]
+    .concat();
+    for owner in owners.iter() {
+        let Some(ports) = extracted.find_ports_mut(owner) else {
+            continue;
+        };
+        ports.ports.iter_mut().for_each(|port| {
+            let port = port.origin_mut();
+            match counts.get(&(*owner, port.sig.id)) {
+                Some(count) => {
+                    port.connected.insert(id, *count);
+                }
+                None => {
+                    port.connected.remove(&id);
+                }
+            }
+        });
+    }
+    extracted.connections = connections
+        .into_iter()
+        .map(Representation::Origin)
+        .collect::<Vec<Representation<Connection>>>();
+    // Keep only interfaces used by ports of extracted composition (all levels)
+    let mut used: HashSet<usize> = HashSet::new();
+    collect_interfaces(&extracted, &mut used);
+    extracted.interfaces = interfaces
+        .into_iter()
+        .filter(|i| used.contains(&i.sig.id))
+        .collect::<Vec<Interface>>();
+    extracted
+}
+
+fn collect_interfaces(composition: &Composition, used: &mut HashSet<usize>) {
+    let mut add = |ports: &Ports| {
+        used.extend(
+            ports
+                .iter()
+                .filter_map(|p| p.origin().interface().map(|sig| sig.id)),
+        );
+    };
+    add(composition.ports.origin());
+    composition
+        .components
+        .iter()
+        .for_each(|c| add(c.origin().ports.origin()));
+    composition
+        .compositions
+        .iter()
+        .for_each(|nested| collect_interfaces(nested.origin(), used));
+}
@@ -0,0 +1,120 @@
+use crate::{
+    entity::{Component, Composition, Connection, Ports, Signature},
+    export::{escape_xml, walk, Graph},
+};
+
+// (id, for, name)
+const KEYS: [(&str, &str, &str); 5] = [
+    ("d0", "node", "short_name"),
+    ("d1", "node", "class_name"),
+    ("d2", "node", "kind"),
+    ("d3", "port", "short_name"),
+    ("d4", "edge", "id"),
+];
+
+fn indent(depth: usize) -> String {
+    "    ".repeat(depth)
+}
+
+struct GraphMl {
+    out: String,
+    // All edges are placed into top level graph, because they could link nodes of
+    // different levels
+    edges: String,
+}
+
+impl GraphMl {
+    /// Opens node; it's closed by caller, because node of composition holds nested graph
+    fn node(&mut self, indent: &str, sig: &Signature, ports: &Ports, kind: &str) {
+        self.out
+            .push_str(&format!("{indent}<node id=\"n{}\">\n", sig.id));
+        self.out.push_str(&format!(
+            "{indent}  <data key=\"d0\">{}</data>\n",
+            escape_xml(&sig.short_name)
+        ));
+        self.out.push_str(&format!(
+            "{indent}  <data key=\"d1\">{}</data>\n",
+            escape_xml(&sig.class_name)
+        ));
+        self.out
+            .push_str(&format!("{indent}  <data key=\"d2\">{kind}</data>\n"));
+        ports.iter().map(|p| p.origin()).for_each(|port| {
+            self.out.push_str(&format!(
+                "{indent}  <port name=\"p{}\"><data key=\"d3\">{}</data></port>\n",
+                port.sig.id,
+                escape_xml(&port.sig.short_name)
+            ));
+        });
+    }
+}
+
+impl Graph for GraphMl {
+    fn composition(&mut self, composition: &Composition, depth: usize) {
+        let indent = indent(depth);
+        self.node(
+            &indent,
+            &composition.sig,
+            composition.ports.origin(),
+            "composition",
+        );
+        self.out.push_str(&format!(
+            "{indent}  <graph id=\"g{}\" edgedefault=\"directed\">\n",
+            composition.sig.id
+        ));
+    }
+
+    fn composition_end(&mut self, _composition: &Composition, depth: usize) {
+        let indent = indent(depth);
+        self.out.push_str(&format!("{indent}  </graph>\n"));
+        self.out.push_str(&format!("{indent}</node>\n"));
+    }
+
+    fn component(&mut self, component: &Component, depth: usize) {
+        let indent = indent(depth + 1);
+        self.node(
+            &indent,
+            &component.sig,
+            component.ports.origin(),
+            "component",
+        );
+        self.out.push_str(&format!("{indent}</node>\n"));
+    }
+
+    fn connection(&mut self, c: &Connection, _depth: usize) {
+        self.edges.push_str(&format!(
+            "    <edge id=\"e{}\" source=\"n{}\" sourceport=\"p{}\" target=\"n{}\" targetport=\"p{}\"><data key=\"d4\">{}</data></edge>\n",
+            c.sig.id,
+            c.joint_out.component,
+            c.joint_out.port,
+            c.joint_in.component,
+            c.joint_in.port,
+            c.sig.id
+        ));
+    }
+}
+
+/// Writes composition as GraphML document. Compositions are nodes with nested graphs,
+/// ports are ports of nodes.
+pub fn write(composition: &Composition) -> String {
+    let mut graph = GraphMl {
+        out: String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n"),
+        edges: String::new(),
+    };
+    graph
+        .out
+        .push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n");
+    KEYS.iter().for_each(|(id, target, name)| {
+        graph.out.push_str(&format!(
+            "  <key id=\"{id}\" for=\"{target}\" attr.name=\"{name}\" attr.type=\"string\"/>\n"
+        ));
+    });
+    graph
+        .out
+        .push_str("  <graph id=\"g\" edgedefault=\"directed\">\n");
+    walk(&mut graph, composition, 1);
+    let GraphMl { mut out, edges } = graph;
+    out.push_str(&edges);
+    out.push_str("  </graph>\n");
+    out.push_str("</graphml>\n");
+    out
+}
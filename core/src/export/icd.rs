@@ -0,0 +1,199 @@
+use crate::{
+    analysis::diff::get_paths,
+    entity::{Composition, Port, Ports},
+    export::csv,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Column of interface control table
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub enum Column {
+    Connection,
+    Composition,
+    SourceComponent,
+    SourceComponentId,
+    SourcePort,
+    SourcePortId,
+    Interface,
+    TargetComponent,
+    TargetComponentId,
+    TargetPort,
+    TargetPortId,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TableOptions {
+    pub sort: Option<Column>,
+    #[serde(default)]
+    pub descending: bool,
+    // Export only components matching current filter
+    #[serde(default)]
+    pub filtered: bool,
+}
+
+/// Row of interface control table; source is `joint_out`, target is `joint_in`
+#[derive(Debug, Serialize)]
+pub struct Row {
+    pub connection: usize,
+    // Path of composition, which holds connection ("/" for root composition)
+    pub composition: String,
+    pub source_component: String,
+    pub source_component_id: usize,
+    pub source_port: String,
+    pub source_port_id: usize,
+    // Class name of interface of source port (or target port, if source doesn't have it)
+    pub interface: String,
+    pub target_component: String,
+    pub target_component_id: usize,
+    pub target_port: String,
+    pub target_port_id: usize,
+}
+
+const CSV_HEADER: [&str; 11] = [
+    "connection",
+    "composition",
+    "source_component",
+    "source_component_id",
+    "source_port",
+    "source_port_id",
+    "interface",
+    "target_component",
+    "target_component_id",
+    "target_port",
+    "target_port_id",
+];
+
+impl Row {
+    /// Returns key to sort rows by column: texts are compared case insensitively, ids
+    /// as numbers
+    fn sort_key(&self, column: Column) -> (String, usize) {
+        let (text, id) = match column {
+            Column::Connection => ("", self.connection),
+            Column::Composition => (self.composition.as_str(), 0),
+            Column::SourceComponent => (self.source_component.as_str(), 0),
+            Column::SourceComponentId => ("", self.source_component_id),
+            Column::SourcePort => (self.source_port.as_str(), 0),
+            Column::SourcePortId => ("", self.source_port_id),
+            Column::Interface => (self.interface.as_str(), 0),
+            Column::TargetComponent => (self.target_component.as_str(), 0),
+            Column::TargetComponentId => ("", self.target_component_id),
+            Column::TargetPort => (self.target_port.as_str(), 0),
+            Column::TargetPortId => ("", self.target_port_id),
+        };
+        (text.to_lowercase(), id)
+    }
+}
+
+fn collect<'a>(
+    composition: &'a Composition,
+    owners: &mut HashMap<usize, &'a str>,
+    ports: &mut HashMap<usize, &'a Port>,
+) {
+    owners.insert(composition.sig.id, &composition.sig.short_name);
+    let mut add = |ports_list: &'a Ports| {
+        ports_list.iter().map(|p| p.origin()).for_each(|port| {
+            ports.insert(port.sig.id, port);
+        });
+    };
+    add(composition.ports.origin());
+    composition.components.iter().for_each(|c| {
+        let c = c.origin();
+        owners.insert(c.sig.id, &c.sig.short_name);
+        add(c.ports.origin());
+    });
+    composition
+        .compositions
+        .iter()
+        .for_each(|nested| collect(nested.origin(), owners, ports));
+}
+
+/// Builds interface control table: one row per connection of composition (all levels).
+/// `composition` is expected to be clean model (without connections created by render).
+pub fn get_rows(composition: &Composition, options: &TableOptions) -> Vec<Row> {
+    let paths = get_paths(composition);
+    let mut owners: HashMap<usize, &str> = HashMap::new();
+    let mut ports: HashMap<usize, &Port> = HashMap::new();
+    collect(composition, &mut owners, &mut ports);
+    let mut rows: Vec<Row> = Vec::new();
+    fn walk(
+        composition: &Composition,
+        paths: &HashMap<usize, String>,
+        owners: &HashMap<usize, &str>,
+        ports: &HashMap<usize, &Port>,
+        rows: &mut Vec<Row>,
+    ) {
+        let path = paths
+            .get(&composition.sig.id)
+            .filter(|path| !path.is_empty())
+            .map_or(String::from("/"), |path| path.to_owned());
+        composition.connections.iter().for_each(|c| {
+            let c = c.origin();
+            let (out, inp) = (&c.joint_out, &c.joint_in);
+            let owner = |id: &usize| owners.get(id).copied().unwrap_or_default().to_owned();
+            let port = |id: &usize| ports.get(id).copied();
+            let name = |id: &usize| port(id).map(|p| p.sig.short_name.to_owned());
+            rows.push(Row {
+                connection: c.sig.id,
+                composition: path.clone(),
+                source_component: owner(&out.component),
+                source_component_id: out.component,
+                source_port: name(&out.port).unwrap_or_default(),
+                source_port_id: out.port,
+                interface: port(&out.port)
+                    .and_then(|p| p.interface())
+                    .or_else(|| port(&inp.port).and_then(|p| p.interface()))
+                    .map(|i| i.class_name.to_owned())
+                    .unwrap_or_default(),
+                target_component: owner(&inp.component),
+                target_component_id: inp.component,
+                target_port: name(&inp.port).unwrap_or_default(),
+                target_port_id: inp.port,
+            });
+        });
+        composition
+            .compositions
+            .iter()
+            .for_each(|nested| walk(nested.origin(), paths, owners, ports, rows));
+    }
+    walk(composition, &paths, &owners, &ports, &mut rows);
+    let column = options.sort.unwrap_or(Column::Connection);
+    // Keys are calculated once per row; rows with same key are ordered by connection
+    // ascending in both directions
+    let mut keyed = rows
+        .into_iter()
+        .map(|row| (row.sort_key(column), row))
+        .collect::<Vec<((String, usize), Row)>>();
+    keyed.sort_by(|(a, a_row), (b, b_row)| {
+        let order = a.cmp(b);
+        if options.descending {
+            order.reverse()
+        } else {
+            order
+        }
+        .then_with(|| a_row.connection.cmp(&b_row.connection))
+    });
+    keyed.into_iter().map(|(_, row)| row).collect()
+}
+
+pub fn to_csv(rows: &[Row]) -> String {
+    let rows = rows
+        .iter()
+        .map(|row| {
+            vec![
+                row.connection.to_string(),
+                row.composition.to_owned(),
+                row.source_component.to_owned(),
+                row.source_component_id.to_string(),
+                row.source_port.to_owned(),
+                row.source_port_id.to_string(),
+                row.interface.to_owned(),
+                row.target_component.to_owned(),
+                row.target_component_id.to_string(),
+                row.target_port.to_owned(),
+                row.target_port_id.to_string(),
+            ]
+        })
+        .collect::<Vec<Vec<String>>>();
+    csv::write(&CSV_HEADER, &rows)
+}
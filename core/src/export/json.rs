@@ -0,0 +1,120 @@
+use crate::{
+    entity::{Component, Composition, Connection, Port, Ports},
+    render::Representation,
+};
+
+fn expand_ports(ports: &Ports) -> (Ports, Vec<usize>, Vec<usize>) {
+    // Ports created by render (grouped and unbound grouped ports)
+    let synthetic = ports
+        .iter()
+        .filter(|p| !p.origin().contains.is_empty())
+        .map(|p| p.sig().id)
+        .collect::<Vec<usize>>();
+    let contained = ports
+        .iter()
+        .flat_map(|p| p.origin().contains.iter().copied())
+        .collect::<Vec<usize>>();
+    let expanded = ports
+        .iter()
+        .filter(|p| !synthetic.contains(&p.sig().id))
+        .map(|p| {
+            let mut port: Port = p.origin().clone();
+            if contained.contains(&port.sig.id) {
+                port.visibility = true;
+            }
+            Representation::Origin(port)
+        })
+        .collect::<Vec<Representation<Port>>>();
+    (
+        Ports {
+            ports: expanded,
+            hide_invisible: ports.hide_invisible,
+            sig: ports.sig.clone(),
+        },
+        synthetic,
+        contained,
+    )
+}
+
+/// Converts rendered component into component without ports created by render
+pub fn expand_component(component: &Component) -> Component {
+    Component {
+        sig: component.sig.clone(),
+        ports: Representation::Origin(expand_ports(component.ports.origin()).0),
+        composition: component.composition,
+    }
+}
+
+/// Converts rendered composition into composition, which can be passed to `bind`.
+/// Ports and connections created by render (grouped ports) are removed and original
+/// ports and connections become visible. With `include` only components and nested
+/// compositions accepted by predicate (and connections between them) are exported.
+pub fn expand(composition: &Composition, include: &dyn Fn(&usize) -> bool) -> Composition {
+    let mut synthetic: Vec<usize> = Vec::new();
+    let mut contained: Vec<usize> = Vec::new();
+    let mut take_ports = |ports: &Ports| {
+        let (ports, removed, originals) = expand_ports(ports);
+        synthetic.extend(removed);
+        contained.extend(originals);
+        Representation::Origin(ports)
+    };
+    let ports = take_ports(composition.ports.origin());
+    let components = composition
+        .components
+        .iter()
+        // Compositions are added as components during calculation of layout
+        .filter(|c| c.render().map_or(true, |r| !r.is_composition()))
+        .filter(|c| include(&c.sig().id))
+        .map(|c| {
+            let component = c.origin();
+            Representation::Origin(Component {
+                sig: component.sig.clone(),
+                ports: take_ports(component.ports.origin()),
+                composition: component.composition,
+            })
+        })
+        .collect::<Vec<Representation<Component>>>();
+    let compositions = composition
+        .compositions
+        .iter()
+        .filter(|c| include(&c.sig().id))
+        .map(|c| Representation::Origin(expand(c.origin(), &|_| true)))
+        .collect::<Vec<Representation<Composition>>>();
+    let exported = [
+        vec![composition.sig.id],
+        components.iter().map(|c| c.sig().id).collect(),
+        compositions.iter().map(|c| c.sig().id).collect(),
+    ]
+    .concat();
+    let connections = composition
+        .connections
+        .iter()
+        .map(|c| c.origin())
+        .filter(|c| {
+            !synthetic.contains(&c.joint_in.port)
+                && !synthetic.contains(&c.joint_out.port)
+                && exported.contains(&c.joint_in.component)
+                && exported.contains(&c.joint_out.component)
+        })
+        .map(|c| {
+            let mut connection: Connection = c.clone();
+            connection.joint_in.grouped = None;
+            connection.joint_out.grouped = None;
+            if contained.contains(&connection.joint_in.port)
+                || contained.contains(&connection.joint_out.port)
+            {
+                connection.visibility = true;
+            }
+            Representation::Origin(connection)
+        })
+        .collect::<Vec<Representation<Connection>>>();
+    Composition {
+        sig: composition.sig.clone(),
+        components,
+        connections,
+        compositions,
+        ports,
+        parent: composition.parent,
+        interfaces: composition.interfaces.clone(),
+    }
+}
@@ -0,0 +1,100 @@
+use crate::{
+    entity::{Component, Composition, Connection, Ports, Signature},
+    export::{walk, Graph},
+};
+use std::collections::HashMap;
+
+/// Mermaid doesn't support escaping by backslash; quotes are replaced by entity
+fn escape(value: &str) -> String {
+    value.replace('"', "#quot;")
+}
+
+fn collect_names<'a>(composition: &'a Composition, names: &mut HashMap<usize, &'a str>) {
+    let mut add = |ports: &'a Ports| {
+        ports.iter().map(|p| p.origin()).for_each(|port| {
+            names.insert(port.sig.id, &port.sig.short_name);
+        });
+    };
+    add(composition.ports.origin());
+    composition
+        .components
+        .iter()
+        .for_each(|c| add(c.origin().ports.origin()));
+    composition
+        .compositions
+        .iter()
+        .for_each(|nested| collect_names(nested.origin(), names));
+}
+
+fn indent(depth: usize) -> String {
+    "    ".repeat(depth)
+}
+
+struct Mermaid<'a> {
+    out: String,
+    // Names of ports to label edges
+    names: HashMap<usize, &'a str>,
+}
+
+impl Mermaid<'_> {
+    fn node(&mut self, depth: usize, sig: &Signature, shape: (&str, &str)) {
+        self.out.push_str(&format!(
+            "{}n{}{}\"{}\"{}\n",
+            indent(depth),
+            sig.id,
+            shape.0,
+            escape(&sig.short_name),
+            shape.1
+        ));
+    }
+}
+
+impl Graph for Mermaid<'_> {
+    // Ports of composition are represented by own node inside of subgraph
+    fn composition(&mut self, composition: &Composition, depth: usize) {
+        self.node(depth, &composition.sig, ("([", "])"));
+    }
+
+    fn component(&mut self, component: &Component, depth: usize) {
+        self.node(depth, &component.sig, ("[", "]"));
+    }
+
+    fn nested(&mut self, nested: &Composition, depth: usize) {
+        self.out.push_str(&format!(
+            "{}subgraph c{}[\"{}\"]\n",
+            indent(depth),
+            nested.sig.id,
+            escape(&nested.sig.short_name)
+        ));
+    }
+
+    fn nested_end(&mut self, _nested: &Composition, depth: usize) {
+        self.out.push_str(&format!("{}end\n", indent(depth)));
+    }
+
+    fn connection(&mut self, c: &Connection, depth: usize) {
+        let name = |port: &usize| self.names.get(port).copied().unwrap_or_default();
+        let edge = format!(
+            "{}n{} -->|\"{} → {}\"| n{}\n",
+            indent(depth),
+            c.joint_out.component,
+            escape(name(&c.joint_out.port)),
+            escape(name(&c.joint_in.port)),
+            c.joint_in.component
+        );
+        self.out.push_str(&edge);
+    }
+}
+
+/// Writes composition as Mermaid flowchart. Components are nodes, nested compositions
+/// are subgraphs; ports are shown as labels of edges ("out port → in port").
+pub fn write(composition: &Composition) -> String {
+    let mut names: HashMap<usize, &str> = HashMap::new();
+    collect_names(composition, &mut names);
+    let mut mermaid = Mermaid {
+        out: String::from("flowchart LR\n"),
+        names,
+    };
+    walk(&mut mermaid, composition, 1);
+    mermaid.out
+}
@@ -0,0 +1,64 @@
+pub mod arxml;
+pub mod csv;
+pub mod dot;
+pub mod extract;
+pub mod graphml;
+pub mod icd;
+pub mod json;
+pub mod mermaid;
+
+use crate::entity::{Component, Composition, Connection};
+
+/// Writer of graph format (DOT, GraphML, Mermaid). Graph is written from composition
+/// without entities created by render; see `walk`.
+pub trait Graph {
+    /// Writes node of composition itself (it holds ports of composition)
+    fn composition(&mut self, composition: &Composition, depth: usize);
+
+    /// Called after all entities of composition are written
+    fn composition_end(&mut self, _composition: &Composition, _depth: usize) {}
+
+    fn component(&mut self, component: &Component, depth: usize);
+
+    /// Called before entities of nested composition are written with `depth + 1`
+    fn nested(&mut self, _nested: &Composition, _depth: usize) {}
+
+    fn nested_end(&mut self, _nested: &Composition, _depth: usize) {}
+
+    /// Edge goes from `joint_out` to `joint_in` of connection
+    fn connection(&mut self, connection: &Connection, depth: usize);
+}
+
+/// Walks composition: node of composition, components, nested compositions and then
+/// connections. Compositions placed as components are skipped, because they are
+/// written as nested compositions.
+pub fn walk<G: Graph>(graph: &mut G, composition: &Composition, depth: usize) {
+    graph.composition(composition, depth);
+    composition
+        .components
+        .iter()
+        .map(|c| c.origin())
+        .filter(|c| !c.composition)
+        .for_each(|c| graph.component(c, depth));
+    composition.compositions.iter().for_each(|nested| {
+        let nested = nested.origin();
+        graph.nested(nested, depth);
+        walk(graph, nested, depth + 1);
+        graph.nested_end(nested, depth);
+    });
+    composition
+        .connections
+        .iter()
+        .for_each(|c| graph.connection(c.origin(), depth));
+    graph.composition_end(composition, depth);
+}
+
+/// Escapes text of XML element or attribute
+pub fn escape_xml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
@@ -0,0 +1,151 @@
+use crate::{
+    entity::Removed,
+    state::{FilterState, SelectionState},
+};
+use serde::{Deserialize, Serialize};
+
+// Max number of commands, which can be undone
+const LIMIT: usize = 100;
+// Changes of view done within this time (ms) after previous one are one gesture
+const GESTURE_MS: f64 = 1000.0;
+
+/// Reversible operation. Each command keeps state, which was before (or after) an
+/// operation. Applying of command swaps current state with stored one, so same
+/// command is used to undo and redo an operation.
+#[derive(Debug, Deserialize, Serialize)]
+pub enum Command {
+    Model(Edit),
+    Selection(SelectionState),
+    Filter(FilterState),
+    // (x, y, zoom). Places of components are calculated by layout, so panning and zooming
+    // are the only manual moves
+    View(i32, i32, f64),
+    Transaction(Vec<Command>),
+}
+
+/// Change of model. Only changed entities are stored; applying of change returns change,
+/// which reverts it.
+#[derive(Debug, Deserialize, Serialize)]
+pub enum Edit {
+    // Entities to insert back into model
+    Insert(Vec<Removed>),
+    // Ids of entities to remove (components, compositions, ports or connections)
+    Remove(Vec<usize>),
+    // (id, short name)
+    Rename(usize, String),
+}
+
+impl Command {
+    /// Returns true if layout should be recalculated after applying of command
+    pub fn affects_layout(&self) -> bool {
+        match self {
+            Self::Model(..) | Self::Filter(..) => true,
+            Self::Selection(..) | Self::View(..) => false,
+            Self::Transaction(commands) => commands.iter().any(|c| c.affects_layout()),
+        }
+    }
+
+    /// Returns true if layout should be recalculated from scratch. Changes of model keep
+    /// places of components, which weren't changed
+    pub fn resets_layout(&self) -> bool {
+        match self {
+            Self::Filter(..) => true,
+            Self::Model(..) | Self::Selection(..) | Self::View(..) => false,
+            Self::Transaction(commands) => commands.iter().any(|c| c.resets_layout()),
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct History {
+    undo: Vec<Command>,
+    redo: Vec<Command>,
+    transaction: Option<Vec<Command>>,
+    // Time of last change of view
+    #[serde(skip)]
+    last_view: Option<f64>,
+}
+
+impl History {
+    pub fn push(&mut self, command: Command) {
+        if let Some(transaction) = self.transaction.as_mut() {
+            transaction.push(command);
+            return;
+        }
+        self.undo.push(command);
+        self.redo.clear();
+        if self.undo.len() > LIMIT {
+            self.undo.remove(0);
+        }
+    }
+
+    /// Stores change of view (`now` - time in ms). Sequential changes of one gesture (done
+    /// in transaction or within `GESTURE_MS` after previous change) are merged to avoid
+    /// recording of each step of panning/zooming; the first stored view is kept.
+    pub fn push_view(&mut self, command: Command, now: f64) {
+        let gesture = self.last_view.is_some_and(|last| now - last < GESTURE_MS);
+        self.last_view = Some(now);
+        let merged = match self.transaction.as_ref() {
+            Some(transaction) => matches!(transaction.last(), Some(Command::View(..))),
+            None => {
+                gesture
+                    && self.redo.is_empty()
+                    && matches!(self.undo.last(), Some(Command::View(..)))
+            }
+        };
+        if !merged {
+            self.push(command);
+        }
+    }
+
+    /// Starts transaction: all commands until commit will be undone/redone as one
+    pub fn begin(&mut self) {
+        if self.transaction.is_none() {
+            self.transaction = Some(Vec::new());
+        }
+    }
+
+    pub fn commit(&mut self) {
+        if let Some(commands) = self.transaction.take() {
+            if !commands.is_empty() {
+                self.push(Command::Transaction(commands));
+            }
+        }
+    }
+
+    pub fn take_undo(&mut self) -> Option<Command> {
+        self.commit();
+        self.last_view = None;
+        self.undo.pop()
+    }
+
+    pub fn take_redo(&mut self) -> Option<Command> {
+        self.commit();
+        self.last_view = None;
+        self.redo.pop()
+    }
+
+    /// Stores applied command
+    pub fn undone(&mut self, command: Command) {
+        self.redo.push(command);
+    }
+
+    pub fn redone(&mut self, command: Command) {
+        self.undo.push(command);
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty() || self.transaction.as_ref().is_some_and(|t| !t.is_empty())
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.transaction = None;
+        self.last_view = None;
+    }
+}
@@ -1,24 +1,47 @@
 extern crate console_error_panic_hook;
 extern crate wasm_bindgen;
 
+mod analysis;
 mod entity;
 mod error;
+mod export;
+mod history;
 mod render;
+mod search;
+mod snapshot;
 mod state;
+mod view_state;
 
 use entity::{
     dummy::{Dummy, SignatureProducer},
-    Composition, IsInputPort, Signature,
+    Composition, Connection, IsInputPort, Removed, Signature, SignatureGetter,
 };
 use error::E;
-use render::{options::Options, Grid, Ratio, Render, Style};
+use history::{Command, Edit, History};
+use render::{
+    elements::{self, is_point_in_polygon},
+    form::{Path, Point},
+    grid::{as_u32, ElementCoors, ElementType},
+    options::Options,
+    Container, Form, Grid, Ratio, Relative, Render, Style,
+};
+use search::{index::Index, query::Query};
 use serde::{Deserialize, Serialize};
-use state::State;
-use std::ops::RangeInclusive;
+use state::{
+    DragStatus, Dragging, EditEvent, FilterState, Focus, FocusDirection, FocusedMatch,
+    SelectionMode, SelectionState, State, ViewPosition,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    ops::RangeInclusive,
+};
 use wasm_bindgen::prelude::*;
 use wasm_bindgen_test::console_log;
 use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement};
 
+// Area in canvas coordinates: (x, y, x1, y1)
+type Area = (i32, i32, i32, i32);
+
 #[derive(Debug, Deserialize, Serialize)]
 struct Active {
     pub grid: Grid,
@@ -48,42 +71,903 @@ impl Active {
         context: &mut web_sys::CanvasRenderingContext2d,
         state: &State,
         options: &Options,
+        pinned: Option<&Grid>,
     ) -> Result<(), E> {
         self.composition
-            .calc(context, &mut self.grid, state, options)?;
+            .calc(context, &mut self.grid, state, options, pinned)?;
         self.grid.apply_margin();
         Ok(())
     }
-}
-
-#[derive(Debug, Serialize)]
-struct SnapshotSe<'a> {
-    active: &'a Active,
-    options: &'a Options,
-    state: &'a State,
-    ratio: &'a Ratio,
-    sig_producer: &'a SignatureProducer,
-}
+}
+
+#[derive(Debug)]
+#[wasm_bindgen]
+pub struct Board {
+    active: Active,
+    context: Option<CanvasRenderingContext2d>,
+    canvas: Option<HtmlCanvasElement>,
+    options: Options,
+    state: State,
+    ratio: Ratio,
+    sig_producer: SignatureProducer,
+    history: History,
+    // Index of names and relations of model to run queries
+    index: Index,
+}
+
+impl Board {
+    fn bind_composition(&mut self, composition: Composition) -> Result<(), E> {
+        self.active = Active::new(
+            &self.options,
+            Render::<Composition>::new(composition, true, &self.options, &mut self.sig_producer),
+        );
+        self.active.calc(
+            self.context.as_mut().ok_or(E::NoCanvasContext)?,
+            &self.state,
+            &self.options,
+            None,
+        )?;
+        self.index = Index::new(self.active.composition.origin());
+        self.history.clear();
+        Ok(())
+    }
+
+    /// Returns current model without entities created by render
+    fn export_composition(&self, filtered: bool) -> Composition {
+        export::json::expand(self.active.composition.origin(), &|id: &usize| {
+            !filtered || self.state.is_comp_included(id)
+        })
+    }
+
+    /// Returns composition built from selected components
+    fn extract_composition(&mut self, boundary: bool) -> Composition {
+        let composition = self.export_composition(false);
+        let selected = self.state.components.clone();
+        let active = &mut self.active.composition;
+        let sig_producer = &mut self.sig_producer;
+        export::extract::extract(composition, &selected, boundary, &mut |class_name| {
+            active.next_free_sig(sig_producer, class_name)
+        })
+    }
+
+    /// Applies change of model. Returns change, which reverts applied change, and ids of
+    /// removed entities
+    fn edit_model(&mut self, edit: Edit) -> Result<(Edit, Vec<usize>), E> {
+        let composition = &mut self.active.composition;
+        let (options, sig_producer) = (&self.options, &mut self.sig_producer);
+        let (inverse, changed, removed) = match edit {
+            Edit::Insert(entities) => {
+                // Connections are inserted after entities, which own ports of connections
+                let (connections, owners): (Vec<Removed>, Vec<Removed>) = entities
+                    .into_iter()
+                    .partition(|entity| matches!(entity, Removed::Connection(..)));
+                let mut inserted: Vec<usize> = Vec::new();
+                let mut changed: Vec<usize> = Vec::new();
+                for entity in owners.into_iter().chain(connections) {
+                    inserted.push(entity.id());
+                    changed.push(entity.id());
+                    match entity {
+                        Removed::Component(holder, component) => {
+                            composition.add_component(&holder, component, options, sig_producer)?
+                        }
+                        Removed::Composition(holder, nested) => {
+                            composition.add_composition(&holder, nested, options, sig_producer)?
+                        }
+                        Removed::Port(owner, port) => {
+                            changed.push(owner);
+                            composition.add_port(&owner, port, options, sig_producer)?
+                        }
+                        Removed::Connection(holder, connection) => composition.add_connection(
+                            &holder,
+                            connection,
+                            options,
+                            sig_producer,
+                        )?,
+                    }
+                }
+                // Connections are removed before owners of ports
+                inserted.reverse();
+                (Edit::Remove(inserted), changed, Vec::new())
+            }
+            Edit::Remove(ids) => {
+                let mut entities: Vec<Removed> = Vec::new();
+                for id in ids.iter() {
+                    let origin = composition.origin();
+                    entities.extend(if origin.find_connection_holder(id).is_some() {
+                        composition.remove_connection(id, options, sig_producer)?
+                    } else if origin.find_port_owner(id).is_some() {
+                        composition.remove_port(id, options, sig_producer)?
+                    } else {
+                        composition.remove_component(id, options, sig_producer)?
+                    });
+                }
+                let removed = entities
+                    .iter()
+                    .flat_map(|entity| entity.get_all_ids())
+                    .collect::<Vec<usize>>();
+                let mut changed = removed.clone();
+                changed.extend(entities.iter().filter_map(|entity| match entity {
+                    Removed::Port(owner, _) => Some(*owner),
+                    _ => None,
+                }));
+                (Edit::Insert(entities), changed, removed)
+            }
+            Edit::Rename(id, short_name) => {
+                let before = composition
+                    .origin_mut()
+                    .find_sig_mut(&id)
+                    .map(|sig| sig.short_name.clone())
+                    .ok_or(E::NotFound(id))?;
+                composition.rename(&id, short_name, options, sig_producer)?;
+                (Edit::Rename(id, before), vec![id], Vec::new())
+            }
+        };
+        self.index
+            .update(self.active.composition.origin(), &changed);
+        Ok((inverse, removed))
+    }
+
+    /// Applies change of model made by user: change is stored in history and layout is
+    /// recalculated. Returns ids of removed entities
+    fn change_model(&mut self, edit: Edit) -> Result<Vec<usize>, String> {
+        let (inverse, removed) = self.edit_model(edit)?;
+        self.history.push(Command::Model(inverse));
+        self.relayout(&removed)?;
+        Ok(removed)
+    }
+
+    /// Stores selection before change, if selection has been changed
+    fn record_selection(&mut self, before: SelectionState) {
+        if before != self.state.get_selection_state() {
+            self.history.push(Command::Selection(before));
+        }
+    }
+
+    /// Stores filters before change, if filters have been changed
+    fn record_filter(&mut self, before: FilterState) {
+        if before != self.state.get_filter_state() {
+            self.history.push(Command::Filter(before));
+        }
+    }
+
+    /// Applies command: current state is swapped with state stored in command
+    fn apply(&mut self, command: &mut Command) -> Result<(), E> {
+        match command {
+            Command::Model(edit) => {
+                let (inverse, removed) =
+                    self.edit_model(std::mem::replace(edit, Edit::Remove(Vec::new())))?;
+                *edit = inverse;
+                self.state.forget(&removed);
+            }
+            Command::Selection(selection) => {
+                let current = self.state.get_selection_state();
+                self.state
+                    .set_selection_state(std::mem::replace(selection, current));
+            }
+            Command::Filter(filter) => {
+                let current = self.state.get_filter_state();
+                self.state
+                    .set_filter_state(std::mem::replace(filter, current));
+                self.active.composition.drop_compositions_as_components();
+            }
+            Command::View(x, y, zoom) => {
+                let current = (self.state.x, self.state.y, self.state.zoom);
+                self.state.set_view_state(*x, *y, *zoom);
+                (*x, *y, *zoom) = current;
+            }
+            Command::Transaction(commands) => {
+                for command in commands.iter_mut().rev() {
+                    self.apply(command)?;
+                }
+                // Next applying (redo of undo or undo of redo) goes in opposite order
+                commands.reverse();
+            }
+        }
+        Ok(())
+    }
+
+    /// Toggles selection of component without rendering
+    fn toggle_component_selection(&mut self, id: usize, selfishly: bool) {
+        let all = |id: &usize| {
+            if let Some(comp) = self.active.composition.origin().get_component(id) {
+                [
+                    self.active
+                        .composition
+                        .origin()
+                        .find_connections_by_component(id)
+                        .iter()
+                        .flat_map(|conn| conn.get_ports())
+                        .collect::<Vec<&usize>>(),
+                    comp.ports
+                        .origin()
+                        .iter()
+                        .map(|port| &port.sig().id)
+                        .collect::<Vec<&usize>>(),
+                ]
+                .concat()
+            } else {
+                Vec::new()
+            }
+        };
+        let own = |id: &usize| {
+            if let Some(comp) = self.active.composition.origin().get_component(id) {
+                comp.ports
+                    .origin()
+                    .iter()
+                    .map(|port| &port.sig().id)
+                    .collect::<Vec<&usize>>()
+            } else {
+                Vec::new()
+            }
+        };
+        let linked = |id: &usize| {
+            let own = own(id);
+            self.active
+                .composition
+                .origin()
+                .find_connections_by_component(id)
+                .iter()
+                .flat_map(|conn: &&entity::Connection| conn.get_ports())
+                .filter(|p| !own.contains(p))
+                .collect::<Vec<&usize>>()
+        };
+        let insert = |state: &mut State, id: &usize, own: Vec<&usize>, linked: Vec<&usize>| {
+            own.iter().for_each(|id| {
+                state.insert_port(id);
+            });
+            linked.iter().for_each(|id| {
+                state.highlight_port(id);
+            });
+            state.insert_component(id);
+        };
+        let remove = |state: &mut State, id: &usize, ports: Vec<&usize>| {
+            ports.iter().for_each(|id| {
+                state.remove_port(id);
+                state.unhighlight_port(id);
+            });
+            state.remove_component(id);
+        };
+        let ports = all(&id);
+        if self.state.is_component_selected(&id) {
+            remove(&mut self.state, &id, ports);
+            self.state.components.to_vec().iter().for_each(|id| {
+                insert(&mut self.state, id, own(id), linked(id));
+            });
+            self.state.selection.remove_component(&id).notify();
+        } else {
+            if selfishly {
+                self.state.unselect_all(true);
+            }
+            insert(&mut self.state, &id, own(&id), linked(&id));
+            self.state.selection.insert_component(&id).notify();
+        }
+    }
+
+    /// Looks for components and ports of active composition inside of given bounds (canvas
+    /// coordinates: x, y, x1, y1). Candidates are taken from grid; `contains` checks area
+    /// of element (canvas coordinates). Returns (components, ports).
+    fn find_in_area(
+        &self,
+        bounds: Area,
+        contains: &dyn Fn(&Area) -> bool,
+    ) -> Result<(Vec<usize>, Vec<usize>), E> {
+        let relative = self.state.get_grid_relative();
+        let area_px = (
+            as_u32(self.ratio.get(bounds.0) - relative.x(0)),
+            as_u32(self.ratio.get(bounds.1) - relative.y(0)),
+            as_u32(self.ratio.get(bounds.2) - relative.x(0)),
+            as_u32(self.ratio.get(bounds.3) - relative.y(0)),
+        );
+        let composition = self.active.composition.origin();
+        let components = self
+            .active
+            .grid
+            .in_area(area_px, self.state.zoom, 1)
+            .into_iter()
+            .filter_map(|(id, _, _)| id.parse::<usize>().ok())
+            .filter(|id| composition.get_component(id).is_some())
+            .collect::<Vec<usize>>();
+        let ports = components
+            .iter()
+            .filter_map(|id| composition.get_component(id))
+            .flat_map(|component| component.ports.origin().iter())
+            .chain(composition.ports.origin().iter())
+            .map(|port| port.sig().id)
+            .collect::<Vec<usize>>();
+        let found = [
+            self.active
+                .grid
+                .get_coors_by_ids(&components, &relative, &self.ratio),
+            self.active
+                .composition
+                .get_coors_by_ids(&ports, &relative, &self.ratio)?,
+        ]
+        .concat()
+        .into_iter()
+        .filter(|(_, _, area)| contains(area))
+        .filter_map(|(id, ty, _)| id.parse::<usize>().ok().map(|id| (id, ty)))
+        .collect::<Vec<(usize, ElementType)>>();
+        let ids = |port: bool| {
+            found
+                .iter()
+                .filter(|(_, ty)| matches!(ty, ElementType::Port) == port)
+                .map(|(id, _)| *id)
+                .collect::<Vec<usize>>()
+        };
+        Ok((ids(false), ids(true)))
+    }
+
+    /// Applies area selection to current selection. Selection callback is called once.
+    fn select_found(&mut self, components: &[usize], ports: &[usize], mode: SelectionMode) {
+        let selcb = self.state.selection.selcb.take();
+        if matches!(mode, SelectionMode::Replace) {
+            self.state.unselect_all(true);
+        }
+        let select = !matches!(mode, SelectionMode::Subtract);
+        components.iter().for_each(|id| {
+            if self.state.is_component_selected(id) != select {
+                self.toggle_component_selection(*id, false);
+            }
+        });
+        ports.iter().for_each(|id| {
+            if self.state.is_port_selected(id) != select {
+                self.toggle_port_selection(*id, false);
+            }
+        });
+        self.state.selection.selcb = selcb;
+        self.state.selection.notify();
+    }
+
+    /// Selects components and ports inside of area; returns found (components, ports)
+    fn select_in_area(
+        &mut self,
+        bounds: Area,
+        contains: &dyn Fn(&Area) -> bool,
+        mode: JsValue,
+    ) -> Result<JsValue, String> {
+        let mode = if mode.is_undefined() || mode.is_null() {
+            SelectionMode::default()
+        } else {
+            serde_wasm_bindgen::from_value::<SelectionMode>(mode)
+                .map_err(|e| E::Serde(e.to_string()))?
+        };
+        let (components, ports) = self.find_in_area(bounds, contains)?;
+        let before = self.state.get_selection_state();
+        self.select_found(&components, &ports, mode);
+        self.record_selection(before);
+        self.render()?;
+        serde_wasm_bindgen::to_value(&(components, ports)).map_err(|e| e.to_string())
+    }
+
+    /// Toggles selection of port without rendering
+    fn toggle_port_selection(&mut self, id: usize, selfishly: bool) {
+        let connections = self
+            .active
+            .composition
+            .origin()
+            .find_connections_by_port(&id);
+        if selfishly && !self.state.is_port_selected(&id) {
+            self.state.unselect_all(true);
+        }
+        let inserted = self.state.toggle_port(&id);
+        for connection in connections.iter() {
+            let rel_port = if (&id).is_input_port(*connection) {
+                connection.out_port()
+            } else {
+                connection.in_port()
+            };
+            if inserted {
+                // Added
+                self.state.highlight_port(rel_port);
+            } else {
+                // Removed
+                self.state.unhighlight_port(rel_port);
+            }
+        }
+    }
+
+    /// Applies restored snapshot. Returns list of sections, which have been replaced by defaults
+    fn restore(
+        &mut self,
+        restored: snapshot::Restored,
+        selcb: js_sys::Function,
+    ) -> Result<snapshot::Report, E> {
+        let mut report = snapshot::Report {
+            version: restored.version,
+            dropped: Vec::new(),
+        };
+        if let Some(options) = restored.options {
+            self.options = options;
+        } else {
+            report.dropped.push(snapshot::Section::Options);
+        }
+        let editcb = self.state.edit.editcb.take();
+        if let Some(state) = restored.state {
+            self.state = state;
+            self.state.selection.set_selcb(selcb);
+        } else {
+            report.dropped.push(snapshot::Section::State);
+            self.state = State::new(
+                self.active.grid.as_px(self.options.grid.hmargin),
+                self.active.grid.as_px(self.options.grid.vmargin),
+                selcb,
+            );
+        }
+        self.state.edit.editcb = editcb;
+        match restored.render {
+            Ok((active, ratio, sig_producer)) => {
+                self.active = active;
+                self.ratio = ratio;
+                self.sig_producer = sig_producer;
+                self.index = Index::new(self.active.composition.origin());
+            }
+            Err(model) => {
+                report.dropped.push(snapshot::Section::Render);
+                self.ratio = self.options.ratio();
+                self.bind_composition(model)?;
+            }
+        }
+        self.history = match restored.history {
+            Some(Some(history)) => history,
+            Some(None) => {
+                report.dropped.push(snapshot::Section::History);
+                History::default()
+            }
+            None => History::default(),
+        };
+        Ok(report)
+    }
+
+    fn parse_query(query: &Option<String>) -> Option<Query> {
+        query.as_deref().map(Query::parse_or_text)
+    }
+
+    /// Refreshes board after applying of command from history
+    fn refresh(&mut self, command: &Command) -> Result<(), String> {
+        if command.resets_layout() {
+            self.recalc()
+        } else if command.affects_layout() {
+            self.relayout(&[])
+        } else {
+            self.render()
+        }
+    }
+
+    /// Looks for ports in given point (grid coordinates)
+    fn find_ports(&self, target: (i32, i32)) -> Result<Vec<ElementCoors>, E> {
+        self.active.composition.find_ports(
+            &self.active.grid.point(
+                target,
+                self.active
+                    .grid
+                    .as_px(self.options.grid.cells_space_horizontal),
+                &self.state.get_grid_relative(),
+            ),
+            &target,
+            &self.state,
+        )
+    }
+
+    /// Returns id of port in given point (canvas coordinates)
+    fn port_at(&self, x: i32, y: i32) -> Result<Option<usize>, E> {
+        let target = (
+            self.state.with_hmargin(self.ratio.get(x)),
+            self.state.with_vmargin(self.ratio.get(y)),
+        );
+        Ok(self
+            .find_ports(target)?
+            .into_iter()
+            .filter(|(_, ty, _)| matches!(ty, ElementType::Port))
+            .find_map(|(id, _, _)| id.parse::<usize>().ok()))
+    }
+
+    /// Updates end of dragged connection and snaps it to closest compatible port
+    fn drag_to(&mut self, x: i32, y: i32) -> Result<Option<DragStatus>, E> {
+        let hovered = self.port_at(x, y)?;
+        let snap_distance = self.options.connections.snap_distance as i32;
+        let Some(dragging) = self.state.edit.dragging.as_mut() else {
+            return Ok(None);
+        };
+        let snapped = dragging
+            .candidates
+            .iter()
+            .map(|(id, (cx, cy))| (*id, (*cx, *cy), (cx - x).pow(2) + (cy - y).pow(2)))
+            .filter(|(_, _, distance)| *distance <= snap_distance.pow(2))
+            .min_by_key(|(_, _, distance)| *distance);
+        let source = dragging.port;
+        if let Some((id, center, _)) = snapped {
+            dragging.to = center;
+            dragging.target = Some(id);
+            dragging.rejection = None;
+        } else {
+            dragging.to = (x, y);
+            dragging.target = None;
+            dragging.rejection = hovered.and_then(|id| {
+                self.active
+                    .composition
+                    .validate_connection(source, id)
+                    .err()
+            });
+        }
+        Ok(Some(DragStatus {
+            target: dragging.target,
+            connection: None,
+            rejection: dragging.rejection.clone(),
+        }))
+    }
+
+    /// Draws connection, which is dragged in edit mode
+    fn draw_dragging(&mut self) -> Result<(), E> {
+        let Some(dragging) = self.state.edit.dragging.as_ref() else {
+            return Ok(());
+        };
+        let cx = self.context.as_mut().ok_or(E::NoCanvasContext)?;
+        let scheme = &self.options.scheme;
+        let mut container = Container {
+            form: Form::Path(
+                ElementType::Connection,
+                Path::new(
+                    String::new(),
+                    [dragging.from, dragging.to]
+                        .iter()
+                        .map(|(x, y)| Point {
+                            x: self.ratio.get(*x),
+                            y: self.ratio.get(*y),
+                        })
+                        .collect(),
+                    &self.ratio,
+                ),
+            ),
+            style: if dragging.target.is_some() {
+                (&scheme.connection_snapped_line).into()
+            } else if dragging.rejection.is_some() {
+                (&scheme.connection_rejected_line).into()
+            } else {
+                (&scheme.connection_drag_line).into()
+            },
+        };
+        if let Form::Path(_, path) = &mut container.form {
+            path.sdot = true;
+            path.earrow = dragging.target.is_some();
+        }
+        container.render(cx, &Relative::new(0, 0, None), &self.options);
+        Ok(())
+    }
+
+    fn get_interface_rows(&self, options: JsValue) -> Result<Vec<export::icd::Row>, E> {
+        let options = if options.is_undefined() || options.is_null() {
+            export::icd::TableOptions::default()
+        } else {
+            serde_wasm_bindgen::from_value::<export::icd::TableOptions>(options)
+                .map_err(|e| E::Serde(e.to_string()))?
+        };
+        Ok(export::icd::get_rows(
+            &self.export_composition(options.filtered),
+            &options,
+        ))
+    }
+
+    /// Returns holders (grouped ports) of ports hidden in grouped ports of active composition
+    fn get_port_holders(&self) -> HashMap<usize, usize> {
+        let mut holders: HashMap<usize, usize> = HashMap::new();
+        let composition = self.active.composition.origin();
+        composition
+            .components
+            .iter()
+            .map(|c| c.origin().ports.origin())
+            .chain(
+                composition
+                    .compositions
+                    .iter()
+                    .map(|c| c.origin().ports.origin()),
+            )
+            .chain([composition.ports.origin()])
+            .flat_map(|ports| ports.iter())
+            .for_each(|port| {
+                port.origin().contains.iter().for_each(|id| {
+                    holders.insert(*id, port.sig().id);
+                });
+            });
+        holders
+    }
+
+    /// Returns areas of rendered components and ports (grid coordinates, without zoom)
+    fn get_areas(&self, ids: &[usize]) -> Result<HashMap<usize, Area>, E> {
+        let relative = Relative::new(0, 0, None);
+        Ok([
+            self.active
+                .grid
+                .get_coors_by_ids(ids, &relative, &self.ratio),
+            self.active
+                .composition
+                .get_coors_by_ids(ids, &relative, &self.ratio)?,
+        ]
+        .concat()
+        .into_iter()
+        .filter_map(|(id, _, area)| id.parse::<usize>().ok().map(|id| (id, area)))
+        .collect())
+    }
+
+    /// Sets keyboard focus and centers view on focused element, if it's out of canvas
+    fn set_focus(&mut self, focus: Focus) -> Result<(), E> {
+        let id = focus.port.unwrap_or(focus.component);
+        self.state.focus = Some(focus);
+        let Some(canvas) = self.canvas.as_ref() else {
+            return Ok(());
+        };
+        let size = (canvas.width(), canvas.height());
+        let relative = self.state.get_grid_relative();
+        let visible = [
+            self.active
+                .grid
+                .get_coors_by_ids(&[id], &relative, &self.ratio),
+            self.active
+                .composition
+                .get_coors_by_ids(&[id], &relative, &self.ratio)?,
+        ]
+        .concat()
+        .iter()
+        .any(|(_, _, (x, y, x1, y1))| {
+            *x >= 0
+                && *y >= 0
+                && *x1 <= self.ratio.invert(size.0 as i32)
+                && *y1 <= self.ratio.invert(size.1 as i32)
+        });
+        if visible {
+            return Ok(());
+        }
+        let Some((x, y, x1, y1)) = self.get_areas(&[id])?.get(&id).copied() else {
+            return Ok(());
+        };
+        let before = (self.state.x, self.state.y, self.state.zoom);
+        self.state.center_view(
+            (self.ratio.get((x + x1) / 2), self.ratio.get((y + y1) / 2)),
+            size,
+        );
+        if before != (self.state.x, self.state.y, self.state.zoom) {
+            self.history
+                .push(Command::View(before.0, before.1, before.2));
+        }
+        Ok(())
+    }
+
+    /// Moves keyboard focus to nearest component in given direction. Distance across
+    /// direction costs twice more than distance along it. If nothing is focused, first
+    /// component in reading order is focused.
+    fn move_focus(&mut self, direction: FocusDirection) -> Result<(), E> {
+        let composition = self.active.composition.origin();
+        let root = composition.sig.id;
+        let ids = composition
+            .components
+            .iter()
+            .map(|c| c.sig().id)
+            .chain([root])
+            .collect::<Vec<usize>>();
+        let areas = self.get_areas(&ids)?;
+        let center = |(x, y, x1, y1): &Area| ((x + x1) as i64 / 2, (y + y1) as i64 / 2);
+        let current = self.state.focus.as_ref().and_then(|focus| {
+            areas
+                .get(&focus.component)
+                .map(|area| (focus.component, area))
+        });
+        let next = if let Some((current, area)) = current {
+            let (cx, cy) = center(area);
+            areas
+                .iter()
+                .filter(|(id, _)| **id != current && **id != root)
+                .filter_map(|(id, area)| {
+                    let (x, y) = center(area);
+                    let (dx, dy) = (x - cx, y - cy);
+                    let (along, across) = match direction {
+                        FocusDirection::Right => (dx, dy.abs()),
+                        FocusDirection::Left => (-dx, dy.abs()),
+                        FocusDirection::Down => (dy, dx.abs()),
+                        FocusDirection::Up => (-dy, dx.abs()),
+                    };
+                    (along > 0).then_some((along + across * 2, *id))
+                })
+                .min()
+                .map(|(_, id)| id)
+        } else {
+            areas
+                .iter()
+                .filter(|(id, _)| **id != root)
+                .map(|(id, (x, y, _, _))| (*y, *x, *id))
+                .min()
+                .map(|(_, _, id)| id)
+        };
+        if let Some(component) = next {
+            self.set_focus(Focus {
+                component,
+                port: None,
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Moves keyboard focus to next (`forward`) or previous visible port of focused
+    /// component in reading order
+    fn step_focus_port(&mut self, forward: bool) -> Result<(), E> {
+        let Some(focus) = self.state.focus.clone() else {
+            return Ok(());
+        };
+        let composition = self.active.composition.origin();
+        let ports = if focus.component == composition.sig.id {
+            Some(composition.ports.origin())
+        } else {
+            composition
+                .get_component(&focus.component)
+                .map(|component| component.ports.origin())
+        };
+        let Some(ports) = ports else {
+            return Ok(());
+        };
+        let ids = ports
+            .iter()
+            .filter(|port| port.origin().visibility)
+            .map(|port| port.sig().id)
+            .collect::<Vec<usize>>();
+        let mut ports = self
+            .get_areas(&ids)?
+            .into_iter()
+            .filter(|(id, _)| ids.contains(id))
+            .map(|(id, (x, y, _, _))| (y, x, id))
+            .collect::<Vec<(i32, i32, usize)>>();
+        if ports.is_empty() {
+            return Ok(());
+        }
+        ports.sort();
+        let total = ports.len();
+        let current = focus
+            .port
+            .and_then(|port| ports.iter().position(|(_, _, id)| id == &port));
+        let index = match (current, forward) {
+            (Some(current), true) => (current + 1) % total,
+            (Some(current), false) => (current + total - 1) % total,
+            (None, true) => 0,
+            (None, false) => total - 1,
+        };
+        self.set_focus(Focus {
+            component: focus.component,
+            port: Some(ports[index].2),
+        })
+    }
+
+    /// Moves keyboard focus from focused port to opposite port of its connection. If port
+    /// has several connections, opposite port with smallest id is taken.
+    fn follow_focus(&mut self) -> Result<(), E> {
+        let Some(port) = self.state.focus.as_ref().and_then(|focus| focus.port) else {
+            return Ok(());
+        };
+        let composition = self.active.composition.origin();
+        let Some((target, component)) = composition
+            .find_connections_by_port(&port)
+            .iter()
+            .map(|connection| {
+                if connection.in_port() == &port {
+                    (*connection.out_port(), *connection.out_comp())
+                } else {
+                    (*connection.in_port(), *connection.in_comp())
+                }
+            })
+            .min()
+        else {
+            return Ok(());
+        };
+        self.set_focus(Focus {
+            component,
+            port: Some(target),
+        })
+    }
+
+    /// Moves focus to next (`forward`) or previous match in reading order (top to bottom,
+    /// left to right) and centers view on it. Ports hidden in grouped ports are focused
+    /// with holder port. Matches, which aren't rendered, are skipped.
+    fn focus_match(&mut self, forward: bool, zoom: Option<f64>) -> Result<Option<FocusedMatch>, E> {
+        let filtered = self.state.get_filtered();
+        let matches = self
+            .state
+            .get_extended_matches()
+            .map(|matches| {
+                matches
+                    .iter()
+                    .filter(|(id, holder, _)| {
+                        filtered.is_none_or(|filtered| filtered.contains(&holder.unwrap_or(*id)))
+                    })
+                    .map(|(id, holder, _)| (*id, *holder))
+                    .collect::<Vec<(usize, Option<usize>)>>()
+            })
+            .unwrap_or_default();
+        let mut targets = matches
+            .iter()
+            .map(|(id, holder)| holder.unwrap_or(*id))
+            .collect::<Vec<usize>>();
+        targets.sort();
+        targets.dedup();
+        let relative = Relative::new(0, 0, None);
+        let areas = [
+            self.active
+                .grid
+                .get_coors_by_ids(&targets, &relative, &self.ratio),
+            self.active
+                .composition
+                .get_coors_by_ids(&targets, &relative, &self.ratio)?,
+        ]
+        .concat()
+        .into_iter()
+        .filter_map(|(id, _, area)| id.parse::<usize>().ok().map(|id| (id, area)))
+        .collect::<HashMap<usize, (i32, i32, i32, i32)>>();
+        let mut matches = matches
+            .into_iter()
+            .filter_map(|(id, holder)| {
+                areas
+                    .get(&holder.unwrap_or(id))
+                    .map(|area| (id, holder, *area))
+            })
+            .collect::<Vec<(usize, Option<usize>, (i32, i32, i32, i32))>>();
+        if matches.is_empty() {
+            self.state.focused_match = None;
+            return Ok(None);
+        }
+        matches.sort_by_key(|(id, _, (x, y, _, _))| (*y, *x, *id));
+        let total = matches.len();
+        let current = self
+            .state
+            .focused_match
+            .and_then(|focused| matches.iter().position(|(id, _, _)| id == &focused));
+        let index = match (current, forward) {
+            (Some(current), true) => (current + 1) % total,
+            (Some(current), false) => (current + total - 1) % total,
+            (None, true) => 0,
+            (None, false) => total - 1,
+        };
+        let (id, holder, (x, y, x1, y1)) = matches[index];
+        let canvas = self.canvas.as_ref().ok_or(E::NoCanvasContext)?;
+        let before = (self.state.x, self.state.y, self.state.zoom);
+        if let Some(zoom) = zoom {
+            self.state.zoom = zoom;
+        }
+        self.state.center_view(
+            (self.ratio.get((x + x1) / 2), self.ratio.get((y + y1) / 2)),
+            (canvas.width(), canvas.height()),
+        );
+        if before != (self.state.x, self.state.y, self.state.zoom) {
+            self.history
+                .push(Command::View(before.0, before.1, before.2));
+        }
+        self.state.focused_match = Some(id);
+        self.state.set_highlighted(Some(vec![holder.unwrap_or(id)]));
+        Ok(Some(FocusedMatch {
+            index,
+            total,
+            id,
+            holder,
+        }))
+    }
 
-#[derive(Debug, Deserialize)]
-struct SnapshotDe {
-    active: Active,
-    options: Options,
-    state: State,
-    ratio: Ratio,
-    sig_producer: SignatureProducer,
-}
+    fn calc_layout(&mut self, pinned: Option<&Grid>) -> Result<(), String> {
+        self.active.drop_grid(&self.options);
+        let zoom = self.state.zoom;
+        // Calculation goes without considering zoom factor. During calculation zoom factor should be 1.0
+        self.state.zoom = 1.0;
+        self.active.calc(
+            self.context.as_mut().ok_or(E::NoCanvasContext)?,
+            &self.state,
+            &self.options,
+            pinned,
+        )?;
+        self.state.zoom = zoom;
+        self.render()
+    }
 
-#[derive(Debug)]
-#[wasm_bindgen]
-pub struct Board {
-    active: Active,
-    context: Option<CanvasRenderingContext2d>,
-    canvas: Option<HtmlCanvasElement>,
-    options: Options,
-    state: State,
-    ratio: Ratio,
-    sig_producer: SignatureProducer,
+    /// Recalculates layout after changes of model; state and view are kept. Components keep
+    /// own places: only new and resized components are placed into free cells, cells of
+    /// removed components stay empty.
+    fn relayout(&mut self, removed: &[usize]) -> Result<(), String> {
+        self.state.forget(removed);
+        let pinned = self.active.grid.clone();
+        self.calc_layout(Some(&pinned))
+    }
 }
 
 #[wasm_bindgen]
@@ -108,6 +992,7 @@ impl Board {
             selcb,
         );
         let ratio = options.ratio();
+        let index = Index::new(active.composition.origin());
         Self {
             options,
             active,
@@ -116,6 +1001,8 @@ impl Board {
             state,
             ratio,
             sig_producer,
+            history: History::default(),
+            index,
         }
     }
 
@@ -142,6 +1029,7 @@ impl Board {
             selcb,
         );
         let ratio = options.ratio();
+        let index = Index::new(active.composition.origin());
         Self {
             options,
             active,
@@ -150,6 +1038,8 @@ impl Board {
             state,
             ratio,
             sig_producer,
+            history: History::default(),
+            index,
         }
     }
 
@@ -208,60 +1098,544 @@ impl Board {
         let composition = serde_wasm_bindgen::from_value::<Composition>(composition)
             .map_err(|e| E::Serde(e.to_string()))?;
         self.state.drop();
-        self.active = Active::new(
-            &self.options,
-            Render::<Composition>::new(composition, true, &self.options, &mut self.sig_producer),
-        );
-        self.active.calc(
-            self.context.as_mut().ok_or(E::NoCanvasContext)?,
-            &self.state,
-            &self.options,
-        )?;
+        self.bind_composition(composition)?;
         Ok(())
     }
 
+    /// Compares two models and returns summary of changes
     #[wasm_bindgen]
-    pub fn save_snapshot(&self) -> Result<Vec<u8>, String> {
-        let snapshot = SnapshotSe {
-            active: &self.active,
-            options: &self.options,
-            state: &self.state,
-            ratio: &self.ratio,
-            sig_producer: &self.sig_producer,
+    pub fn diff(
+        &self,
+        origin: JsValue,
+        changed: JsValue,
+        by_path: bool,
+    ) -> Result<JsValue, String> {
+        let origin = serde_wasm_bindgen::from_value::<Composition>(origin)
+            .map_err(|e| E::Serde(e.to_string()))?;
+        let changed = serde_wasm_bindgen::from_value::<Composition>(changed)
+            .map_err(|e| E::Serde(e.to_string()))?;
+        let diff = analysis::diff::Diff::new(&origin, &changed, by_path);
+        serde_wasm_bindgen::to_value(&diff).map_err(|e| e.to_string())
+    }
+
+    /// Compares two models and binds union of both models. Added, removed, changed and moved
+    /// entities are highlighted. Returns summary of changes.
+    #[wasm_bindgen]
+    pub fn bind_diff(
+        &mut self,
+        origin: JsValue,
+        changed: JsValue,
+        by_path: bool,
+    ) -> Result<JsValue, String> {
+        let origin = serde_wasm_bindgen::from_value::<Composition>(origin)
+            .map_err(|e| E::Serde(e.to_string()))?;
+        let mut changed = serde_wasm_bindgen::from_value::<Composition>(changed)
+            .map_err(|e| E::Serde(e.to_string()))?;
+        let diff = analysis::diff::Diff::new(&origin, &changed, by_path);
+        let marks = diff.union(origin, &mut changed);
+        self.state.drop();
+        self.state.set_changes(Some(marks));
+        self.bind_composition(changed)?;
+        serde_wasm_bindgen::to_value(&diff).map_err(|e| e.to_string())
+    }
+
+    #[wasm_bindgen]
+    pub fn drop_diff(&mut self) {
+        self.state.set_changes(None);
+    }
+
+    /// Adds component into composition (root or nested) without rebinding
+    #[wasm_bindgen]
+    pub fn add_component(&mut self, parent: usize, component: JsValue) -> Result<(), String> {
+        let component = serde_wasm_bindgen::from_value::<entity::Component>(component)
+            .map_err(|e| E::Serde(e.to_string()))?;
+        self.change_model(Edit::Insert(vec![Removed::Component(parent, component)]))?;
+        Ok(())
+    }
+
+    /// Removes component or nested composition with related connections. Returns ids of
+    /// removed entities
+    #[wasm_bindgen]
+    pub fn remove_component_entity(&mut self, id: usize) -> Result<Vec<usize>, String> {
+        if self
+            .active
+            .composition
+            .origin()
+            .find_component_holder(&id)
+            .is_none()
+        {
+            return Err(E::NotFound(id).into());
+        }
+        self.change_model(Edit::Remove(vec![id]))
+    }
+
+    /// Adds port to component or composition without rebinding
+    #[wasm_bindgen]
+    pub fn add_port(&mut self, owner: usize, port: JsValue) -> Result<(), String> {
+        let port = serde_wasm_bindgen::from_value::<entity::Port>(port)
+            .map_err(|e| E::Serde(e.to_string()))?;
+        self.change_model(Edit::Insert(vec![Removed::Port(owner, port)]))?;
+        Ok(())
+    }
+
+    /// Removes port with related connections. Returns ids of removed entities
+    #[wasm_bindgen]
+    pub fn remove_port_entity(&mut self, id: usize) -> Result<Vec<usize>, String> {
+        if self
+            .active
+            .composition
+            .origin()
+            .find_port_owner(&id)
+            .is_none()
+        {
+            return Err(E::NotFound(id).into());
+        }
+        self.change_model(Edit::Remove(vec![id]))
+    }
+
+    /// Adds connection into composition (root or nested) without rebinding
+    #[wasm_bindgen]
+    pub fn add_connection(&mut self, parent: usize, connection: JsValue) -> Result<(), String> {
+        let connection = serde_wasm_bindgen::from_value::<entity::Connection>(connection)
+            .map_err(|e| E::Serde(e.to_string()))?;
+        self.change_model(Edit::Insert(vec![Removed::Connection(parent, connection)]))?;
+        Ok(())
+    }
+
+    /// Removes connection. Returns ids of removed entities
+    #[wasm_bindgen]
+    pub fn remove_connection(&mut self, id: usize) -> Result<Vec<usize>, String> {
+        if self
+            .active
+            .composition
+            .origin()
+            .find_connection_holder(&id)
+            .is_none()
+        {
+            return Err(E::NotFound(id).into());
+        }
+        self.change_model(Edit::Remove(vec![id]))
+    }
+
+    /// Changes short name of any entity
+    #[wasm_bindgen]
+    pub fn rename(&mut self, id: usize, short_name: String) -> Result<(), String> {
+        self.change_model(Edit::Rename(id, short_name))?;
+        Ok(())
+    }
+
+    /// Exports current model in format of `bind`. Grouped ports and connections created by
+    /// render are replaced by original ports and connections; kinds of interfaces resolved
+    /// on binding aren't exported. If `filtered` is true, only components matching current
+    /// filter are exported.
+    #[wasm_bindgen]
+    pub fn export_json(&self, filtered: Option<bool>) -> Result<JsValue, String> {
+        let mut composition = self.export_composition(filtered.unwrap_or(false));
+        composition.drop_resolved_kinds();
+        serde_wasm_bindgen::to_value(&composition).map_err(|e| e.to_string())
+    }
+
+    /// Exports current model as ARXML document
+    #[wasm_bindgen]
+    pub fn export_arxml(&self, filtered: Option<bool>) -> String {
+        export::arxml::write(&self.export_composition(filtered.unwrap_or(false)))
+    }
+
+    /// Exports current model as GraphViz DOT document
+    #[wasm_bindgen]
+    pub fn export_dot(&self, filtered: Option<bool>) -> String {
+        export::dot::write(&self.export_composition(filtered.unwrap_or(false)))
+    }
+
+    /// Exports current model as GraphML document
+    #[wasm_bindgen]
+    pub fn export_graphml(&self, filtered: Option<bool>) -> String {
+        export::graphml::write(&self.export_composition(filtered.unwrap_or(false)))
+    }
+
+    /// Exports current model as Mermaid flowchart
+    #[wasm_bindgen]
+    pub fn export_mermaid(&self, filtered: Option<bool>) -> String {
+        export::mermaid::write(&self.export_composition(filtered.unwrap_or(false)))
+    }
+
+    /// Builds composition from selected components with connections between them. If
+    /// `boundary` is true, connections to not selected components are represented by
+    /// ports of extracted composition. Result can be passed to `bind`.
+    #[wasm_bindgen]
+    pub fn extract_selection(&mut self, boundary: Option<bool>) -> Result<JsValue, String> {
+        serde_wasm_bindgen::to_value(&self.extract_composition(boundary.unwrap_or(false)))
+            .map_err(|e| e.to_string())
+    }
+
+    /// Builds composition from selected components and exports it as ARXML document
+    #[wasm_bindgen]
+    pub fn extract_selection_as_arxml(&mut self, boundary: Option<bool>) -> String {
+        export::arxml::write(&self.extract_composition(boundary.unwrap_or(false)))
+    }
+
+    /// Undoes last change (or transaction). Returns false if there is nothing to undo
+    #[wasm_bindgen]
+    pub fn undo(&mut self) -> Result<bool, String> {
+        let Some(mut command) = self.history.take_undo() else {
+            return Ok(false);
+        };
+        self.apply(&mut command)?;
+        self.refresh(&command)?;
+        self.history.undone(command);
+        Ok(true)
+    }
+
+    /// Redoes last undone change (or transaction). Returns false if there is nothing to redo
+    #[wasm_bindgen]
+    pub fn redo(&mut self) -> Result<bool, String> {
+        let Some(mut command) = self.history.take_redo() else {
+            return Ok(false);
+        };
+        self.apply(&mut command)?;
+        self.refresh(&command)?;
+        self.history.redone(command);
+        Ok(true)
+    }
+
+    #[wasm_bindgen]
+    pub fn can_undo(&self) -> bool {
+        self.history.can_undo()
+    }
+
+    #[wasm_bindgen]
+    pub fn can_redo(&self) -> bool {
+        self.history.can_redo()
+    }
+
+    /// Starts transaction. All changes until `commit_transaction` will be undone and
+    /// redone as one change
+    #[wasm_bindgen]
+    pub fn begin_transaction(&mut self) {
+        self.history.begin();
+    }
+
+    #[wasm_bindgen]
+    pub fn commit_transaction(&mut self) {
+        self.history.commit();
+    }
+
+    #[wasm_bindgen]
+    pub fn clear_history(&mut self) {
+        self.history.clear();
+    }
+
+    /// Turns on/off edit mode. In edit mode connections can be created by dragging
+    /// between ports
+    #[wasm_bindgen]
+    pub fn set_edit_mode(&mut self, enabled: bool) {
+        self.state.edit.enabled = enabled;
+        if !enabled {
+            self.state.edit.dragging = None;
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn is_edit_mode(&self) -> bool {
+        self.state.edit.enabled
+    }
+
+    /// Sets callback to notify about changes done in edit mode
+    #[wasm_bindgen]
+    pub fn set_editcb(&mut self, editcb: js_sys::Function) {
+        self.state.edit.editcb = Some(editcb);
+    }
+
+    /// Starts dragging of new connection from port in given point. Returns true if
+    /// dragging has been started.
+    #[wasm_bindgen]
+    pub fn drag_start(&mut self, x: i32, y: i32) -> Result<bool, String> {
+        if !self.state.edit.enabled {
+            return Ok(false);
+        }
+        let Some(port) = self.port_at(x, y)? else {
+            return Ok(false);
         };
-        bincode::serialize(&snapshot).map_err(|e| format!("Fail to convert state into bytes: {e}"))
+        let center = |(_, _, (x1, y1, x2, y2)): &ElementCoors| ((x1 + x2) / 2, (y1 + y2) / 2);
+        let relative = self.state.get_grid_relative();
+        let Some(from) = self
+            .active
+            .composition
+            .get_coors_by_ids(&[port], &relative, &self.ratio)?
+            .first()
+            .map(center)
+        else {
+            return Ok(false);
+        };
+        let connectable = self.active.composition.get_connectable_ports(port);
+        let candidates = self
+            .active
+            .composition
+            .get_coors_by_ids(&connectable, &relative, &self.ratio)?
+            .iter()
+            .filter_map(|coors| coors.0.parse::<usize>().ok().map(|id| (id, center(coors))))
+            .collect();
+        self.state.edit.dragging = Some(Dragging {
+            port,
+            from,
+            to: (x, y),
+            candidates,
+            target: None,
+            rejection: None,
+        });
+        self.render()?;
+        Ok(true)
+    }
+
+    /// Moves end of dragged connection. Returns current target and rejection reason (if
+    /// port under pointer cannot be connected).
+    #[wasm_bindgen]
+    pub fn drag_move(&mut self, x: i32, y: i32) -> Result<JsValue, String> {
+        let status = self.drag_to(x, y)?;
+        self.render()?;
+        serde_wasm_bindgen::to_value(&status).map_err(|e| e.to_string())
+    }
+
+    /// Finishes dragging. If connection is dropped on compatible port, new connection
+    /// will be created and edit callback will be called.
+    #[wasm_bindgen]
+    pub fn drag_end(&mut self, x: i32, y: i32) -> Result<JsValue, String> {
+        let Some(mut status) = self.drag_to(x, y)? else {
+            return serde_wasm_bindgen::to_value(&None::<DragStatus>).map_err(|e| e.to_string());
+        };
+        let dragging = self.state.edit.dragging.take();
+        if let (Some(target), Some(dragging)) = (status.target, dragging) {
+            match self
+                .active
+                .composition
+                .validate_connection(dragging.port, target)
+            {
+                Ok((joint_in, joint_out)) => {
+                    let parent = self.active.composition.sig().id;
+                    let connection = Connection {
+                        sig: self
+                            .active
+                            .composition
+                            .next_free_sig(&mut self.sig_producer, "connection"),
+                        joint_in,
+                        joint_out,
+                        visibility: true,
+                    };
+                    status.connection = Some(connection.sig.id);
+                    let (inverse, _) = self.edit_model(Edit::Insert(vec![Removed::Connection(
+                        parent,
+                        connection.clone(),
+                    )]))?;
+                    self.history.push(Command::Model(inverse));
+                    self.state.edit.notify(&EditEvent::ConnectionAdded {
+                        parent,
+                        connection: &connection,
+                    });
+                    self.relayout(&[])?;
+                }
+                Err(rejection) => {
+                    status.target = None;
+                    status.rejection = Some(rejection);
+                    self.render()?;
+                }
+            }
+        } else {
+            self.render()?;
+        }
+        serde_wasm_bindgen::to_value(&status).map_err(|e| e.to_string())
+    }
+
+    /// Cancels dragging of connection
+    #[wasm_bindgen]
+    pub fn drag_cancel(&mut self) -> Result<(), String> {
+        if self.state.edit.dragging.take().is_some() {
+            self.render()
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Saves state of board. History of changes is included if `with_history` is true
+    #[wasm_bindgen]
+    pub fn save_snapshot(&self, with_history: Option<bool>) -> Result<Vec<u8>, String> {
+        let sections = snapshot::Sections {
+            model: snapshot::encode_model(&self.export_composition(false))?,
+            options: snapshot::encode(&self.options)?,
+            state: snapshot::encode(&self.state)?,
+            render: snapshot::encode(&(&self.active, &self.ratio, &self.sig_producer))?,
+            history: if with_history.unwrap_or(false) {
+                Some(snapshot::encode(&self.history)?)
+            } else {
+                None
+            },
+        };
+        snapshot::write(&sections).map_err(|e| format!("Fail to convert state into bytes: {e}"))
     }
 
+    /// Loads state of board. Snapshots of previous versions are migrated; sections, which
+    /// cannot be restored (options, state, history), are replaced by defaults and render
+    /// is restored from model. Returns version of snapshot and list of dropped sections.
     #[wasm_bindgen]
     pub fn load_snapshot(
         &mut self,
         snapshot: Vec<u8>,
         selcb: js_sys::Function,
-    ) -> Result<(), String> {
-        let snapshot: SnapshotDe = bincode::deserialize(&snapshot)
-            .map_err(|e| format!("Fail to convert state into bytes: {e}"))?;
-        self.active = snapshot.active;
-        self.state = snapshot.state;
-        self.options = snapshot.options;
-        self.ratio = snapshot.ratio;
-        self.sig_producer = snapshot.sig_producer;
-        self.state.selection.set_selcb(selcb);
-        Ok(())
+    ) -> Result<JsValue, String> {
+        let report = self.restore(snapshot::read(&snapshot)?, selcb)?;
+        serde_wasm_bindgen::to_value(&report).map_err(|e| e.to_string())
+    }
+
+    /// Saves state of board as human-readable JSON. Besides of state, snapshot includes
+    /// calculated layout (cells of elements, ports and connections coordinates).
+    #[wasm_bindgen]
+    pub fn save_snapshot_json(&self, with_history: Option<bool>) -> Result<String, String> {
+        let snapshot = snapshot::JsonSnapshot {
+            layout: snapshot::Layout::new(&self.active, &self.state, &self.ratio)?,
+            model: self.export_composition(false),
+            options: &self.options,
+            state: &self.state,
+            render: (&self.active, &self.ratio, &self.sig_producer),
+            history: if with_history.unwrap_or(false) {
+                Some(&self.history)
+            } else {
+                None
+            },
+        };
+        Ok(snapshot::write_json(snapshot)?)
+    }
+
+    /// Loads state of board from JSON snapshot (see `load_snapshot`)
+    #[wasm_bindgen]
+    pub fn load_snapshot_json(
+        &mut self,
+        snapshot: &str,
+        selcb: js_sys::Function,
+    ) -> Result<JsValue, String> {
+        let report = self.restore(snapshot::read_json(snapshot)?, selcb)?;
+        serde_wasm_bindgen::to_value(&report).map_err(|e| e.to_string())
+    }
+
+    /// Returns view state (navigation path, selection, filters, targeted and highlighted
+    /// entities, position and zoom) encoded into compact url-safe string. Entities are
+    /// referred by ids and paths, so view state can be applied to another version of model.
+    /// `locations` - navigation path of host from root to shown composition (`{ id, sig }[]`).
+    #[wasm_bindgen]
+    pub fn export_view_state(&self, locations: JsValue) -> Result<String, String> {
+        let locations =
+            serde_wasm_bindgen::from_value::<Option<Vec<view_state::Location>>>(locations)
+                .map_err(|e| E::Serde(e.to_string()))?
+                .unwrap_or_default();
+        let resolver =
+            view_state::Resolver::new(analysis::diff::get_paths(self.active.composition.origin()));
+        let view = view_state::ViewState {
+            compositions: view_state::locations_refs(&locations),
+            components: resolver.refs(self.state.selection.get_components()),
+            ports: resolver.refs(self.state.selection.get_ports()),
+            queries: self.state.queries.clone(),
+            targeted: if self.state.queries.targeted.is_none() {
+                self.state
+                    .get_targeted()
+                    .map(|(targeted, _)| resolver.refs(targeted))
+            } else {
+                None
+            },
+            highlighted: self.state.get_highlighted().map(|ids| resolver.refs(ids)),
+            x: self.state.x,
+            y: self.state.y,
+            zoom: self.state.zoom,
+        };
+        Ok(view_state::encode(&view)?)
+    }
+
+    /// Returns navigation path stored in view state (references to compositions from root
+    /// to shown one). Host should navigate to the last composition of path before
+    /// applying of view state with `import_view_state`.
+    #[wasm_bindgen]
+    pub fn get_view_state_compositions(&self, encoded: &str) -> Result<JsValue, String> {
+        let view = view_state::decode(encoded)?;
+        serde_wasm_bindgen::to_value(&view.compositions).map_err(|e| e.to_string())
+    }
+
+    /// Applies view state to bound model. Returns list of referred entities, which don't
+    /// exist in current model. Applying is recorded in history as one change.
+    #[wasm_bindgen]
+    pub fn import_view_state(&mut self, encoded: &str) -> Result<JsValue, String> {
+        let view = view_state::decode(encoded)?;
+        let resolver =
+            view_state::Resolver::new(analysis::diff::get_paths(self.active.composition.origin()));
+        let mut report = view_state::Report::default();
+        let selection = self.state.get_selection_state();
+        let filter = self.state.get_filter_state();
+        let position = (self.state.x, self.state.y, self.state.zoom);
+        let selcb = self.state.selection.selcb.take();
+        self.state.unselect_all(true);
+        for id in resolver.ids(&view.components, &mut report) {
+            if !self.state.is_component_selected(&id) {
+                self.toggle_component_selection(id, false);
+            }
+        }
+        for id in resolver.ids(&view.ports, &mut report) {
+            if !self.state.is_port_selected(&id) {
+                self.toggle_port_selection(id, false);
+            }
+        }
+        self.state.selection.selcb = selcb;
+        self.state.selection.notify();
+        let queries = view.queries;
+        let query = Self::parse_query(&queries.filter);
+        self.state.set_filtered(
+            self.active
+                .composition
+                .get_filtered_ports(query.as_ref(), &self.index),
+        );
+        let targeted = if let Some(targeted) = view.targeted.as_ref() {
+            let ids = resolver.ids(targeted, &mut report);
+            self.active.composition.get_targeted_components_by_ids(ids)
+        } else {
+            let query = Self::parse_query(&queries.targeted);
+            self.active
+                .composition
+                .get_targeted_components(query.as_ref(), &self.index)
+        };
+        self.state.set_targeted(targeted);
+        let query = Self::parse_query(&queries.matches);
+        self.state.set_matches(
+            self.active
+                .composition
+                .get_matches(query.as_ref(), &self.index),
+            self.active
+                .composition
+                .get_matches_extended(query.as_ref(), &self.index),
+        );
+        self.state.queries = queries;
+        let highlighted = view
+            .highlighted
+            .as_ref()
+            .map(|highlighted| resolver.ids(highlighted, &mut report));
+        self.state.set_highlighted(highlighted);
+        self.state.set_view_state(view.x, view.y, view.zoom);
+        let mut commands = Vec::new();
+        if selection != self.state.get_selection_state() {
+            commands.push(Command::Selection(selection));
+        }
+        if filter != self.state.get_filter_state() {
+            commands.push(Command::Filter(filter));
+        }
+        if position != (self.state.x, self.state.y, self.state.zoom) {
+            commands.push(Command::View(position.0, position.1, position.2));
+        }
+        let changed = !commands.is_empty();
+        let command = Command::Transaction(commands);
+        self.refresh(&command)?;
+        if changed {
+            self.history.push(command);
+        }
+        serde_wasm_bindgen::to_value(&report).map_err(|e| e.to_string())
     }
 
     #[wasm_bindgen]
     pub fn recalc(&mut self) -> Result<(), String> {
-        self.active.drop_grid(&self.options);
-        let zoom = self.state.zoom;
-        // Calculation goes without considering zoom factor. During calculation zoom factor should be 1.0
-        self.state.zoom = 1.0;
-        self.active.calc(
-            self.context.as_mut().ok_or(E::NoCanvasContext)?,
-            &self.state,
-            &self.options,
-        )?;
-        self.state.zoom = zoom;
-        self.render()
+        self.calc_layout(None)
     }
 
     #[wasm_bindgen]
@@ -297,7 +1671,7 @@ impl Board {
             Err(e)?
         } else {
             let _ = self.active.grid.draw(cx, &relative);
-            Ok(())
+            Ok(self.draw_dragging()?)
         }
     }
 
@@ -306,10 +1680,30 @@ impl Board {
         self.active.grid.is_in_viewport(&id)
     }
 
+    /// Checks query of filter. Returns null if query is valid or error with position of
+    /// invalid part of query.
+    #[wasm_bindgen]
+    pub fn check_query(&self, query: &str) -> Result<JsValue, String> {
+        match Query::parse(query) {
+            Ok(_) => Ok(JsValue::NULL),
+            Err(err) => serde_wasm_bindgen::to_value(&err).map_err(|e| e.to_string()),
+        }
+    }
+
+    /// Sets filter of ports. Filter is a query (see `search::query`); plain text and
+    /// invalid query (see `check_query`) are matched with names of ports.
     #[wasm_bindgen]
-    pub fn set_filter(&mut self, filter: Option<String>) {
-        self.state
-            .set_filtered(self.active.composition.get_filtered_ports(filter));
+    pub fn set_filter(&mut self, filter: Option<String>) -> Result<(), String> {
+        let query = Self::parse_query(&filter);
+        let before = self.state.get_filter_state();
+        self.state.queries.filter = filter;
+        self.state.set_filtered(
+            self.active
+                .composition
+                .get_filtered_ports(query.as_ref(), &self.index),
+        );
+        self.record_filter(before);
+        Ok(())
     }
 
     #[wasm_bindgen]
@@ -320,15 +1714,26 @@ impl Board {
     }
 
     #[wasm_bindgen]
-    pub fn set_targeted(&mut self, filter: Option<String>) {
-        self.state
-            .set_targeted(self.active.composition.get_targeted_components(filter));
+    pub fn set_targeted(&mut self, filter: Option<String>) -> Result<(), String> {
+        let query = Self::parse_query(&filter);
+        let before = self.state.get_filter_state();
+        self.state.queries.targeted = filter;
+        self.state.set_targeted(
+            self.active
+                .composition
+                .get_targeted_components(query.as_ref(), &self.index),
+        );
+        self.record_filter(before);
+        Ok(())
     }
 
     #[wasm_bindgen]
     pub fn set_targeted_by_ids(&mut self, ids: Vec<usize>) {
+        let before = self.state.get_filter_state();
+        self.state.queries.targeted = None;
         self.state
             .set_targeted(self.active.composition.get_targeted_components_by_ids(ids));
+        self.record_filter(before);
     }
 
     #[wasm_bindgen]
@@ -351,11 +1756,20 @@ impl Board {
     }
 
     #[wasm_bindgen]
-    pub fn set_matches(&mut self, filter: Option<String>) {
+    pub fn set_matches(&mut self, filter: Option<String>) -> Result<(), String> {
+        let query = Self::parse_query(&filter);
+        let before = self.state.get_filter_state();
+        self.state.queries.matches = filter;
         self.state.set_matches(
-            self.active.composition.get_matches(filter.clone()),
-            self.active.composition.get_matches_extended(filter),
+            self.active
+                .composition
+                .get_matches(query.as_ref(), &self.index),
+            self.active
+                .composition
+                .get_matches_extended(query.as_ref(), &self.index),
         );
+        self.record_filter(before);
+        Ok(())
     }
 
     #[wasm_bindgen]
@@ -396,6 +1810,276 @@ impl Board {
         }
     }
 
+    /// Finds shortest signal paths between two entities (components, compositions or ports)
+    /// and highlights components and ports of found paths. Options (all optional):
+    /// `{ pass_through: "None" | "Flow" | "Any", directed: bool, limit: number }`. Returns
+    /// list of paths; each path is ordered list of components, ports and connections.
+    #[wasm_bindgen]
+    pub fn find_signal_paths(
+        &mut self,
+        from: usize,
+        to: usize,
+        options: JsValue,
+    ) -> Result<JsValue, String> {
+        let options = if options.is_undefined() || options.is_null() {
+            analysis::paths::PathOptions::default()
+        } else {
+            serde_wasm_bindgen::from_value::<analysis::paths::PathOptions>(options)
+                .map_err(|e| E::Serde(e.to_string()))?
+        };
+        let paths = analysis::paths::find(&self.export_composition(false), from, to, &options)?;
+        // Ports hidden in grouped ports are highlighted with holders
+        let holders = self.get_port_holders();
+        let mut components: Vec<usize> = Vec::new();
+        let mut ports: Vec<usize> = Vec::new();
+        paths.iter().flatten().for_each(|step| match step.entity {
+            analysis::diff::EntityKind::Component | analysis::diff::EntityKind::Composition => {
+                components.push(step.id)
+            }
+            analysis::diff::EntityKind::Port => {
+                ports.push(step.id);
+                ports.extend(holders.get(&step.id));
+            }
+            analysis::diff::EntityKind::Connection => {}
+        });
+        components.sort();
+        components.dedup();
+        ports.sort();
+        ports.dedup();
+        let before = self.state.get_selection_state();
+        self.state
+            .set_highlighted(if components.is_empty() && ports.is_empty() {
+                None
+            } else {
+                Some([components, ports.clone()].concat())
+            });
+        self.state.set_highlighted_ports(ports);
+        self.record_selection(before);
+        self.render()?;
+        serde_wasm_bindgen::to_value(&paths).map_err(|e| e.to_string())
+    }
+
+    /// Collects components transitively affected by given component or port: downstream
+    /// (from `joint_out` to `joint_in` of connections) or upstream. `depth` limits count
+    /// of hops from origin. If `dim` isn't false, board switches into impact mode: affected
+    /// components, their connections and ports are highlighted, everything else is dimmed;
+    /// otherwise current impact mode is left.
+    #[wasm_bindgen]
+    pub fn analyze_impact(
+        &mut self,
+        id: usize,
+        downstream: bool,
+        depth: Option<usize>,
+        dim: Option<bool>,
+    ) -> Result<JsValue, String> {
+        let impact =
+            analysis::impact::analyze(&self.export_composition(false), id, downstream, depth)?;
+        if dim.unwrap_or(true) {
+            let holders = self.get_port_holders();
+            let mut ports = impact.ports.clone();
+            ports.extend(impact.ports.iter().filter_map(|id| holders.get(id)));
+            ports.sort();
+            ports.dedup();
+            let mut included = vec![self.active.composition.sig().id];
+            included.extend(impact.components.iter().map(|(id, _)| *id));
+            included.extend(impact.connections.iter());
+            included.extend(ports.iter());
+            self.state.set_impact(included, ports);
+            self.render()?;
+        } else if self.state.drop_impact() {
+            self.render()?;
+        }
+        serde_wasm_bindgen::to_value(&impact).map_err(|e| e.to_string())
+    }
+
+    /// Leaves impact mode (see `analyze_impact`)
+    #[wasm_bindgen]
+    pub fn drop_impact(&mut self) -> Result<(), String> {
+        if self.state.drop_impact() {
+            self.render()?;
+        }
+        Ok(())
+    }
+
+    /// Finds feedback loops (strongly connected components) in graph of components built
+    /// from connections. Returns cycles with their connections and flag whether graph is
+    /// acyclic. If `highlight` is defined, components and ports of cycle with given
+    /// position are highlighted.
+    #[wasm_bindgen]
+    pub fn find_cycles(&mut self, highlight: Option<usize>) -> Result<JsValue, String> {
+        let cycles = analysis::cycles::find(&self.export_composition(false));
+        if let Some(position) = highlight {
+            let cycle = cycles
+                .cycles
+                .get(position)
+                .ok_or(E::Other(format!("Fail to find cycle {position}")))?;
+            let holders = self.get_port_holders();
+            let mut ports = cycle.ports.clone();
+            ports.extend(cycle.ports.iter().filter_map(|id| holders.get(id)));
+            ports.sort();
+            ports.dedup();
+            let before = self.state.get_selection_state();
+            self.state
+                .set_highlighted(Some([cycle.components.clone(), ports.clone()].concat()));
+            self.state.set_highlighted_ports(ports);
+            self.record_selection(before);
+            self.render()?;
+        }
+        serde_wasm_bindgen::to_value(&cycles).map_err(|e| e.to_string())
+    }
+
+    /// Returns metrics of composition: per component fan-in/fan-out and counts of ports,
+    /// usage of interfaces and composition-wide totals (all levels)
+    #[wasm_bindgen]
+    pub fn get_statistics(&self) -> Result<JsValue, String> {
+        let grouped = self
+            .get_port_holders()
+            .into_keys()
+            .collect::<HashSet<usize>>();
+        let statistics = analysis::statistics::collect(&self.export_composition(false), &grouped);
+        serde_wasm_bindgen::to_value(&statistics).map_err(|e| e.to_string())
+    }
+
+    /// Returns report of ports without connections in own scope (including ports hidden
+    /// in "unbound" grouped port). Ports connected in other compositions only are marked
+    /// as `ConnectedOuter`. If `highlight` is true, reported ports and their owners are
+    /// highlighted.
+    #[wasm_bindgen]
+    pub fn lint_ports(&mut self, highlight: Option<bool>) -> Result<JsValue, String> {
+        let report = analysis::lint::get_unconnected_ports(&self.export_composition(false));
+        if highlight.unwrap_or(false) {
+            let holders = self.get_port_holders();
+            let mut ports: Vec<usize> = Vec::new();
+            let mut owners: Vec<usize> = Vec::new();
+            report.ports.iter().for_each(|port| {
+                ports.push(port.port);
+                ports.extend(holders.get(&port.port));
+                owners.push(port.owner);
+            });
+            ports.sort();
+            ports.dedup();
+            owners.sort();
+            owners.dedup();
+            let before = self.state.get_selection_state();
+            self.state.set_highlighted(if ports.is_empty() {
+                None
+            } else {
+                Some([owners, ports.clone()].concat())
+            });
+            self.state.set_highlighted_ports(ports);
+            self.record_selection(before);
+            self.render()?;
+        }
+        serde_wasm_bindgen::to_value(&report).map_err(|e| e.to_string())
+    }
+
+    /// Exports report of unconnected ports (see `lint_ports`) as "csv" or "json" document
+    #[wasm_bindgen]
+    pub fn export_lint_ports(&self, format: &str) -> Result<String, String> {
+        let report = analysis::lint::get_unconnected_ports(&self.export_composition(false));
+        match format {
+            "csv" => Ok(report.to_csv()),
+            "json" => serde_json::to_string_pretty(&report).map_err(|e| e.to_string()),
+            _ => Err(E::Other(format!("Unknown format of report: {format}")).into()),
+        }
+    }
+
+    /// Returns true if graph of components has no feedback loops (see `find_cycles`)
+    #[wasm_bindgen]
+    pub fn is_dag(&self) -> bool {
+        analysis::cycles::is_dag(&self.export_composition(false))
+    }
+
+    /// Focuses next match (see `set_matches`) and centers view on it. Zoom is changed if
+    /// it's defined. Returns position of match and count of matches or null if there are
+    /// no matches.
+    #[wasm_bindgen]
+    pub fn focus_next_match(&mut self, zoom: Option<f64>) -> Result<JsValue, String> {
+        let focused = self.focus_match(true, zoom)?;
+        self.render()?;
+        serde_wasm_bindgen::to_value(&focused).map_err(|e| e.to_string())
+    }
+
+    /// Focuses previous match and centers view on it; see `focus_next_match`
+    #[wasm_bindgen]
+    pub fn focus_prev_match(&mut self, zoom: Option<f64>) -> Result<JsValue, String> {
+        let focused = self.focus_match(false, zoom)?;
+        self.render()?;
+        serde_wasm_bindgen::to_value(&focused).map_err(|e| e.to_string())
+    }
+
+    /// Moves keyboard focus to nearest component in direction ("Up", "Down", "Left" or
+    /// "Right"). Returns focused element or null.
+    #[wasm_bindgen]
+    pub fn focus_move(&mut self, direction: JsValue) -> Result<JsValue, String> {
+        let direction = serde_wasm_bindgen::from_value::<FocusDirection>(direction)
+            .map_err(|e| E::Serde(e.to_string()))?;
+        self.move_focus(direction)?;
+        self.render()?;
+        serde_wasm_bindgen::to_value(&self.state.focus).map_err(|e| e.to_string())
+    }
+
+    /// Moves keyboard focus to next (or previous, if `forward` is false) port of focused
+    /// component. Returns focused element or null.
+    #[wasm_bindgen]
+    pub fn focus_port(&mut self, forward: Option<bool>) -> Result<JsValue, String> {
+        self.step_focus_port(forward.unwrap_or(true))?;
+        self.render()?;
+        serde_wasm_bindgen::to_value(&self.state.focus).map_err(|e| e.to_string())
+    }
+
+    /// Moves keyboard focus from focused port to opposite port of connection. Returns
+    /// focused element or null.
+    #[wasm_bindgen]
+    pub fn focus_follow(&mut self) -> Result<JsValue, String> {
+        self.follow_focus()?;
+        self.render()?;
+        serde_wasm_bindgen::to_value(&self.state.focus).map_err(|e| e.to_string())
+    }
+
+    /// Toggles selection of focused port or component (like click on it)
+    #[wasm_bindgen]
+    pub fn focus_select(&mut self, selfishly: Option<bool>) -> Result<(), String> {
+        let Some(focus) = self.state.focus.clone() else {
+            return Ok(());
+        };
+        let before = self.state.get_selection_state();
+        if let Some(port) = focus.port {
+            self.toggle_port_selection(port, selfishly.unwrap_or(false));
+        } else if focus.component != self.active.composition.sig().id {
+            self.toggle_component_selection(focus.component, selfishly.unwrap_or(false));
+        }
+        self.record_selection(before);
+        self.render()
+    }
+
+    /// Returns focused element or null
+    #[wasm_bindgen]
+    pub fn get_focus(&self) -> Result<JsValue, String> {
+        serde_wasm_bindgen::to_value(&self.state.focus).map_err(|e| e.to_string())
+    }
+
+    /// Removes keyboard focus
+    #[wasm_bindgen]
+    pub fn drop_focus(&mut self) -> Result<(), String> {
+        if self.state.focus.take().is_some() {
+            self.render()?;
+        }
+        Ok(())
+    }
+
+    /// Fuzzy search of components, compositions and ports by name. Returns results sorted by
+    /// relevance with ranges of matched chars, owners of ports and grouped ports.
+    #[wasm_bindgen]
+    pub fn search(&self, pattern: &str, limit: Option<usize>) -> Result<JsValue, String> {
+        serde_wasm_bindgen::to_value(&search::fuzzy::search(
+            self.active.composition.origin(),
+            pattern,
+            limit,
+        ))
+        .map_err(|e| e.to_string())
+    }
+
     #[wasm_bindgen]
     pub fn get_ports_props(&self) -> Result<JsValue, String> {
         serde_wasm_bindgen::to_value(&self.active.composition.origin().get_ports_props())
@@ -432,17 +2116,7 @@ impl Board {
             .active
             .composition
             .find(&(target_x, target_y), self.state.zoom)?;
-        let ports = self.active.composition.find_ports(
-            &self.active.grid.point(
-                (target_x, target_y),
-                self.active
-                    .grid
-                    .as_px(self.options.grid.cells_space_horizontal),
-                &relative,
-            ),
-            &(target_x, target_y),
-            &self.state,
-        )?;
+        let ports = self.find_ports((target_x, target_y))?;
         let elements = [ids, inner, ports].concat();
         serde_wasm_bindgen::to_value(&elements).map_err(|e| e.to_string())
     }
@@ -489,6 +2163,27 @@ impl Board {
         serde_wasm_bindgen::to_value(&result).map_err(|e| e.to_string())
     }
 
+    /// Returns interface control table: one row per original (ungrouped) connection of
+    /// all levels with source and target components and ports, interface and path of
+    /// composition. Options define sorting column and order and filtering.
+    #[wasm_bindgen]
+    pub fn get_interface_table(&self, options: JsValue) -> Result<JsValue, String> {
+        let rows = self.get_interface_rows(options)?;
+        serde_wasm_bindgen::to_value(&rows).map_err(|e| e.to_string())
+    }
+
+    /// Exports interface control table (see `get_interface_table`) as "csv" or "json"
+    /// document
+    #[wasm_bindgen]
+    pub fn export_interface_table(&self, format: &str, options: JsValue) -> Result<String, String> {
+        let rows = self.get_interface_rows(options)?;
+        match format {
+            "csv" => Ok(export::icd::to_csv(&rows)),
+            "json" => serde_json::to_string_pretty(&rows).map_err(|e| e.to_string()),
+            _ => Err(E::Other(format!("Unknown format of table: {format}")).into()),
+        }
+    }
+
     #[wasm_bindgen]
     pub fn draw_by_id(
         &mut self,
@@ -536,137 +2231,233 @@ impl Board {
         serde_wasm_bindgen::to_value(&port).map_err(|e| e.to_string())
     }
 
+    #[wasm_bindgen]
+    pub fn get_interface(&self, id: usize) -> Result<JsValue, String> {
+        let interface: Option<&entity::Interface> =
+            self.active.composition.origin().find_interface(&id);
+        serde_wasm_bindgen::to_value(&interface).map_err(|e| e.to_string())
+    }
+
+    #[wasm_bindgen]
+    pub fn get_port_interface(&self, port: usize) -> Result<JsValue, String> {
+        let interface: Option<&entity::Interface> =
+            self.active.composition.get_port_interface(port);
+        serde_wasm_bindgen::to_value(&interface).map_err(|e| e.to_string())
+    }
+
+    #[wasm_bindgen]
+    pub fn are_ports_compatible(&self, a: usize, b: usize) -> Result<bool, String> {
+        Ok(self.active.composition.are_ports_compatible(a, b)?)
+    }
+
+    /// Checks interfaces of all connected ports and returns list of mismatches. If
+    /// `highlight` is true, mismatched connections will be drawn on board.
+    #[wasm_bindgen]
+    pub fn check_interfaces(&mut self, highlight: bool) -> Result<JsValue, String> {
+        let mismatches = analysis::interfaces::get_mismatches(&self.active.composition);
+        self.state.set_mismatches(if highlight {
+            Some(mismatches.iter().map(|m| m.visible).collect())
+        } else {
+            None
+        });
+        serde_wasm_bindgen::to_value(&mismatches).map_err(|e| e.to_string())
+    }
+
+    #[wasm_bindgen]
+    pub fn get_ports_by_interface(&self) -> Result<JsValue, String> {
+        serde_wasm_bindgen::to_value(&self.active.composition.get_ports_by_interface())
+            .map_err(|e| e.to_string())
+    }
+
     #[wasm_bindgen]
     pub fn get_size(&mut self) -> Result<JsValue, String> {
         serde_wasm_bindgen::to_value(&self.active.grid.get_size_invert_px())
             .map_err(|e| e.to_string())
     }
 
+    /// Sets position and zoom of view. Changes done in one gesture (see `History::push_view`)
+    /// are undone as one change.
     #[wasm_bindgen]
     pub fn set_view_state(&mut self, x: i32, y: i32, zoom: f64) {
+        let before = (self.state.x, self.state.y, self.state.zoom);
         self.state
             .set_view_state(self.ratio.get(x), self.ratio.get(y), zoom);
+        if before != (self.state.x, self.state.y, self.state.zoom) {
+            self.history.push_view(
+                Command::View(before.0, before.1, before.2),
+                js_sys::Date::now(),
+            );
+        }
     }
 
+    /// Returns position and zoom of view
     #[wasm_bindgen]
-    pub fn unselect_all(&mut self) -> Result<(), String> {
-        self.state.unselect_all(false);
-        self.render()?;
-        Ok(())
+    pub fn get_view_state(&self) -> Result<JsValue, String> {
+        let position = ViewPosition {
+            x: self.ratio.invert(self.state.x),
+            y: self.ratio.invert(self.state.y),
+            zoom: self.state.zoom,
+        };
+        serde_wasm_bindgen::to_value(&position).map_err(|e| e.to_string())
     }
+
+    /// Sets zoom and position of view to fit given components and ports into canvas. If
+    /// `ids` isn't defined, whole diagram is fitted; if it's empty, current selection is
+    /// fitted. `padding` - space around fitted area (in px). Returns new view state (see
+    /// `get_view_state`).
     #[wasm_bindgen]
-    pub fn toggle_component(&mut self, id: usize, selfishly: bool) -> Result<(), String> {
-        let all = |id: &usize| {
-            if let Some(comp) = self.active.composition.origin().get_component(id) {
-                [
-                    self.active
-                        .composition
-                        .origin()
-                        .find_connections_by_component(id)
-                        .iter()
-                        .flat_map(|conn| conn.get_ports())
-                        .collect::<Vec<&usize>>(),
-                    comp.ports
-                        .origin()
-                        .iter()
-                        .map(|port| &port.sig().id)
-                        .collect::<Vec<&usize>>(),
-                ]
-                .concat()
-            } else {
-                Vec::new()
+    pub fn fit(
+        &mut self,
+        ids: Option<Vec<usize>>,
+        padding: Option<i32>,
+    ) -> Result<JsValue, String> {
+        let canvas = self.canvas.as_ref().ok_or(E::NoCanvasContext)?;
+        let size = (canvas.width(), canvas.height());
+        let area = match ids {
+            None => {
+                // Size of grid includes margins, which are outside of grid coordinates
+                let (w, h) = self.active.grid.get_size_px();
+                let grid = &self.active.grid;
+                let hmargin = grid.as_px(grid.options.hmargin);
+                let vmargin = grid.as_px(grid.options.vmargin);
+                Some((-hmargin, -vmargin, w as i32 - hmargin, h as i32 - vmargin))
             }
-        };
-        let own = |id: &usize| {
-            if let Some(comp) = self.active.composition.origin().get_component(id) {
-                comp.ports
-                    .origin()
+            Some(ids) => {
+                let mut ids = if ids.is_empty() {
+                    [
+                        self.state.selection.get_components(),
+                        self.state.selection.get_ports(),
+                    ]
+                    .concat()
+                } else {
+                    ids
+                };
+                // Ports hidden in grouped ports are fitted with holders
+                let holders = self.get_port_holders();
+                let hidden = ids
                     .iter()
-                    .map(|port| &port.sig().id)
-                    .collect::<Vec<&usize>>()
-            } else {
-                Vec::new()
+                    .filter_map(|id| holders.get(id).copied())
+                    .collect::<Vec<usize>>();
+                ids.extend(hidden);
+                self.get_areas(&ids)?
+                    .into_values()
+                    .map(|(x, y, x1, y1)| {
+                        (
+                            self.ratio.get(x),
+                            self.ratio.get(y),
+                            self.ratio.get(x1),
+                            self.ratio.get(y1),
+                        )
+                    })
+                    .reduce(|(x, y, x1, y1), (ax, ay, ax1, ay1)| {
+                        (x.min(ax), y.min(ay), x1.max(ax1), y1.max(ay1))
+                    })
             }
         };
-        let linked = |id: &usize| {
-            let own = own(id);
-            self.active
-                .composition
-                .origin()
-                .find_connections_by_component(id)
-                .iter()
-                .flat_map(|conn: &&entity::Connection| conn.get_ports())
-                .filter(|p| !own.contains(p))
-                .collect::<Vec<&usize>>()
-        };
-        let insert = |state: &mut State, id: &usize, own: Vec<&usize>, linked: Vec<&usize>| {
-            own.iter().for_each(|id| {
-                state.insert_port(id);
-            });
-            linked.iter().for_each(|id| {
-                state.highlight_port(id);
-            });
-            state.insert_component(id);
-        };
-        let remove = |state: &mut State, id: &usize, ports: Vec<&usize>| {
-            ports.iter().for_each(|id| {
-                state.remove_port(id);
-                state.unhighlight_port(id);
-            });
-            state.remove_component(id);
-        };
-        let ports = all(&id);
-        if self.state.is_component_selected(&id) {
-            remove(&mut self.state, &id, ports);
-            self.state.components.to_vec().iter().for_each(|id| {
-                insert(&mut self.state, id, own(id), linked(id));
-            });
-            self.state.selection.remove_component(&id).notify();
-        } else {
-            if selfishly {
-                self.state.unselect_all(true);
+        if let Some(area) = area {
+            let before = (self.state.x, self.state.y, self.state.zoom);
+            self.state
+                .fit_view(area, size, self.ratio.get(padding.unwrap_or(0)));
+            if before != (self.state.x, self.state.y, self.state.zoom) {
+                self.history
+                    .push(Command::View(before.0, before.1, before.2));
             }
-            insert(&mut self.state, &id, own(&id), linked(&id));
-            self.state.selection.insert_component(&id).notify();
+            self.render()?;
         }
+        self.get_view_state()
+    }
+
+    #[wasm_bindgen]
+    pub fn unselect_all(&mut self) -> Result<(), String> {
+        let before = self.state.get_selection_state();
+        self.state.unselect_all(false);
+        self.record_selection(before);
+        self.render()?;
+        Ok(())
+    }
+    #[wasm_bindgen]
+    pub fn toggle_component(&mut self, id: usize, selfishly: bool) -> Result<(), String> {
+        let before = self.state.get_selection_state();
+        self.toggle_component_selection(id, selfishly);
+        self.record_selection(before);
         self.render()?;
         Ok(())
     }
 
     #[wasm_bindgen]
     pub fn toggle_port(&mut self, id: usize, selfishly: bool) -> Result<(), String> {
-        let connections = self
-            .active
-            .composition
-            .origin()
-            .find_connections_by_port(&id);
-        if selfishly && !self.state.is_port_selected(&id) {
-            self.state.unselect_all(true);
-        }
-        let inserted = self.state.toggle_port(&id);
-        for connection in connections.iter() {
-            let rel_port = if (&id).is_input_port(*connection) {
-                connection.out_port()
-            } else {
-                connection.in_port()
-            };
-            if inserted {
-                // Added
-                self.state.highlight_port(rel_port);
-            } else {
-                // Removed
-                self.state.unhighlight_port(rel_port);
-            }
-        }
+        let before = self.state.get_selection_state();
+        self.toggle_port_selection(id, selfishly);
+        self.record_selection(before);
         self.render()
     }
 
+    /// Selects components and ports, which are completely inside of rectangle (canvas
+    /// coordinates). `mode` is "Replace" (default), "Add" or "Subtract". Returns found
+    /// (components, ports).
+    #[wasm_bindgen]
+    pub fn select_in_rect(
+        &mut self,
+        x: i32,
+        y: i32,
+        w: i32,
+        h: i32,
+        mode: JsValue,
+    ) -> Result<JsValue, String> {
+        let bounds = (x.min(x + w), y.min(y + h), x.max(x + w), y.max(y + h));
+        self.select_in_area(
+            bounds,
+            &|area| {
+                elements::is_point_in(&(area.0, area.1), &bounds)
+                    && elements::is_point_in(&(area.2, area.3), &bounds)
+            },
+            mode,
+        )
+    }
+
+    /// Selects components and ports, which are completely inside of polygon (lasso).
+    /// `points` - flat list of coordinates of vertices (x0, y0, x1, y1, ...) in canvas
+    /// coordinates; see `select_in_rect` for `mode`.
+    #[wasm_bindgen]
+    pub fn select_in_polygon(
+        &mut self,
+        points: Vec<i32>,
+        mode: JsValue,
+    ) -> Result<JsValue, String> {
+        let polygon = points
+            .chunks_exact(2)
+            .map(|point| (point[0], point[1]))
+            .collect::<Vec<(i32, i32)>>();
+        if polygon.len() < 3 {
+            return Err(E::Other(String::from("Polygon should have 3 points at least")).into());
+        }
+        let bounds = polygon.iter().fold(
+            (i32::MAX, i32::MAX, i32::MIN, i32::MIN),
+            |(x, y, x1, y1), (px, py)| (x.min(*px), y.min(*py), x1.max(*px), y1.max(*py)),
+        );
+        self.select_in_area(
+            bounds,
+            &|area| {
+                [
+                    (area.0, area.1),
+                    (area.2, area.1),
+                    (area.0, area.3),
+                    (area.2, area.3),
+                ]
+                .iter()
+                .all(|corner| is_point_in_polygon(corner, &polygon))
+            },
+            mode,
+        )
+    }
+
     #[wasm_bindgen]
     pub fn show_connections_by_ports(
         &mut self,
         left: &[usize],
         right: &[usize],
     ) -> Result<(), String> {
+        let before = self.state.get_selection_state();
         self.state.unselect_all(true);
         let grouped = self.active.composition.get_grouped_ports()?;
         for (n, id) in left.iter().enumerate() {
@@ -694,12 +2485,15 @@ impl Board {
             self.state.insert_port(&pair.0);
             self.state.insert_port(&pair.1);
         }
+        self.record_selection(before);
         self.render()
     }
 
     #[wasm_bindgen]
     pub fn insert_component(&mut self, id: usize) -> Result<(), String> {
+        let before = self.state.get_selection_state();
         if self.state.insert_component(&id) {
+            self.record_selection(before);
             self.render()
         } else {
             Ok(())
@@ -708,7 +2502,9 @@ impl Board {
 
     #[wasm_bindgen]
     pub fn remove_component(&mut self, id: usize) -> Result<(), String> {
+        let before = self.state.get_selection_state();
         if self.state.remove_component(&id) {
+            self.record_selection(before);
             self.render()
         } else {
             Ok(())
@@ -717,7 +2513,9 @@ impl Board {
 
     #[wasm_bindgen]
     pub fn insert_port(&mut self, id: usize) -> Result<(), String> {
+        let before = self.state.get_selection_state();
         if self.state.insert_port(&id) {
+            self.record_selection(before);
             self.render()
         } else {
             Ok(())
@@ -726,7 +2524,9 @@ impl Board {
 
     #[wasm_bindgen]
     pub fn remove_port(&mut self, id: usize) -> Result<(), String> {
+        let before = self.state.get_selection_state();
         if self.state.remove_port(&id) {
+            self.record_selection(before);
             self.render()
         } else {
             Ok(())
@@ -6,6 +6,25 @@ pub fn is_point_in(point: &(i32, i32), area: &(i32, i32, i32, i32)) -> bool {
     !(x < a_x || x > a_x1 || y < a_y || y > a_y1)
 }
 
+/// Checks point against polygon (even-odd rule); polygon is closed automatically
+pub fn is_point_in_polygon(point: &(i32, i32), polygon: &[(i32, i32)]) -> bool {
+    let (x, y) = (point.0 as f64, point.1 as f64);
+    let mut inside = false;
+    let mut prev = match polygon.last() {
+        Some(last) => last,
+        None => return false,
+    };
+    for current in polygon.iter() {
+        let (x0, y0) = (prev.0 as f64, prev.1 as f64);
+        let (x1, y1) = (current.0 as f64, current.1 as f64);
+        if (y1 > y) != (y0 > y) && x < (x0 - x1) * (y - y1) / (y0 - y1) + x1 {
+            inside = !inside;
+        }
+        prev = current;
+    }
+    inside
+}
+
 // Target: (x,y,x1,y1), areas &[(x,y,x1,y1)]
 pub fn is_area_cross(target: &(u32, u32, u32, u32), area: &(u32, u32, u32, u32)) -> bool {
     let (ax, ay, ax1, ay1) = area;
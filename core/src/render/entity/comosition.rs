@@ -1,9 +1,12 @@
 use crate::{
+    analysis::interfaces::{self, Rejection},
     entity::{
-        dummy::SignatureProducer, Component, Composition, Connection, IsComponentIncluded,
-        IsPortIncluded, Joint, Port, PortType, Ports, Signature, SignatureGetter,
+        dummy::SignatureProducer, Component, Composition, Connection, Interface,
+        IsComponentIncluded, IsPortIncluded, Joint, Port, PortDirection, PortType, Ports, Removed,
+        Signature, SignatureGetter,
     },
     error::E,
+    export::json,
     render::{
         elements,
         form::{Path, Point, Rectangle},
@@ -11,9 +14,10 @@ use crate::{
         options::Options,
         Container, Form, Grid, Ratio, Relative, Render, Representation, Style, View,
     },
+    search::{index::Index, query::Query},
     state::State,
 };
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use wasm_bindgen::JsValue;
 use wasm_bindgen_test::console_log;
 
@@ -77,55 +81,16 @@ impl Render<Composition> {
         options: &Options,
         sig_producer: &mut SignatureProducer,
     ) -> Self {
+        if root {
+            entity.resolve_interfaces(&[]);
+        }
         if options.ports.grouping && root {
-            group_ports(&mut entity, sig_producer);
+            group_ports(&mut entity, sig_producer, None);
         }
         if options.ports.group_unbound {
             group_unbound_ports(&mut entity, sig_producer);
         }
-        entity.components = entity
-            .components
-            .drain(..)
-            .map(|r| {
-                if let Representation::Origin(component) = r {
-                    Representation::Render(Render::<Component>::new(component, options, None))
-                } else {
-                    r
-                }
-            })
-            .collect::<Vec<Representation<Component>>>();
-        entity.compositions = entity
-            .compositions
-            .drain(..)
-            .map(|r| {
-                if let Representation::Origin(composition) = r {
-                    Representation::Render(Render::<Composition>::new(
-                        composition,
-                        false,
-                        options,
-                        sig_producer,
-                    ))
-                } else {
-                    r
-                }
-            })
-            .collect::<Vec<Representation<Composition>>>();
-        entity.connections = entity
-            .connections
-            .drain(..)
-            .map(|r| {
-                if let Representation::Origin(connection) = r {
-                    Representation::Render(Render::<Connection>::new(connection, options))
-                } else {
-                    r
-                }
-            })
-            .collect::<Vec<Representation<Connection>>>();
-        entity.ports = if let Representation::Origin(ports) = entity.ports {
-            Representation::Render(Render::<Ports>::new(ports, options, false))
-        } else {
-            entity.ports
-        };
+        render_origins(&mut entity, options, sig_producer);
         entity.order();
         let id = entity.sig.id;
         Self {
@@ -158,138 +123,93 @@ impl Render<Composition> {
     /// )
     pub fn get_filtered_ports(
         &mut self,
-        filter: Option<String>,
+        filter: Option<&Query>,
+        index: &Index,
     ) -> Option<(Vec<usize>, Vec<usize>, Vec<usize>)> {
-        self.entity
-            .components
-            .retain(|c| c.render().map_or(true, |r| !r.is_composition()));
-        self.entity
-            .compositions
-            .iter_mut()
-            .for_each(|c| c.render_mut().unwrap().show());
-        filter.as_ref().map(|filter| {
-            let filtered = [
-                self.entity
-                    .components
-                    .iter()
-                    .flat_map(|c| c.origin().ports.origin().get_filtered_ports(filter))
-                    .collect::<Vec<usize>>(),
-                self.entity
-                    .compositions
-                    .iter()
-                    .flat_map(|c| c.origin().ports.origin().get_filtered_ports(filter))
-                    .collect::<Vec<usize>>(),
-                self.entity.ports.origin().get_filtered_ports(filter),
-            ]
-            .concat();
+        self.drop_compositions_as_components();
+        filter.map(|query| {
+            let candidates = query.get_candidates(index);
+            let only = get_candidates_owners(&candidates, index);
+            let filter = |port: &Port| is_port_matched(query, &candidates, port, index);
+            let filter = &filter;
+            let mut owners = HashSet::new();
+            let filtered = self
+                .get_ports_owners(only.as_ref())
+                .into_iter()
+                .flat_map(|(owner, ports)| {
+                    let filtered = ports.get_filtered_ports(filter);
+                    if !filtered.is_empty() {
+                        owners.insert(owner);
+                    }
+                    filtered
+                })
+                .collect::<Vec<usize>>();
+            let found = filtered.iter().copied().collect::<HashSet<usize>>();
             let linked = self
                 .entity
                 .connections
                 .iter()
                 .filter_map(|c| {
                     let connection = c.origin();
-                    if filtered.contains(connection.in_port()) {
+                    if found.contains(connection.in_port()) {
+                        owners.insert(*connection.out_comp());
                         Some(*connection.out_port())
-                    } else if filtered.contains(connection.out_port()) {
+                    } else if found.contains(connection.out_port()) {
+                        owners.insert(*connection.in_comp());
                         Some(*connection.in_port())
                     } else {
                         None
                     }
                 })
                 .collect::<Vec<usize>>();
-            let owners = [
-                self.entity
-                    .compositions
-                    .iter()
-                    .filter_map(|c| {
-                        let ports = &c.origin().ports.origin().ports;
-                        if ports.is_empty() {
-                            None
-                        } else if ports.iter().any(|p| filtered.contains(&p.sig().id))
-                            || ports.iter().any(|p| linked.contains(&p.sig().id))
-                        {
-                            Some(c.sig().id)
-                        } else {
-                            None
-                        }
-                    })
-                    .collect::<Vec<usize>>(),
-                self.entity
-                    .components
-                    .iter()
-                    .filter_map(|c| {
-                        let ports = &c.origin().ports.origin().ports;
-                        if ports.is_empty() {
-                            None
-                        } else if ports.iter().any(|p| filtered.contains(&p.sig().id))
-                            || ports.iter().any(|p| linked.contains(&p.sig().id))
-                        {
-                            Some(c.sig().id)
-                        } else {
-                            None
-                        }
-                    })
-                    .collect::<Vec<usize>>(),
-            ]
-            .concat();
+            let owners = self
+                .entity
+                .compositions
+                .iter()
+                .map(|c| c.sig().id)
+                .chain(self.entity.components.iter().map(|c| c.sig().id))
+                .filter(|id| owners.contains(id))
+                .collect::<Vec<usize>>();
             (filtered, linked, owners)
         })
     }
 
     pub fn get_targeted_components(
         &mut self,
-        filter: Option<String>,
+        filter: Option<&Query>,
+        index: &Index,
     ) -> Option<(Vec<usize>, Vec<usize>)> {
-        self.entity
-            .components
-            .retain(|c| c.render().map_or(true, |r| !r.is_composition()));
-        self.entity
-            .compositions
-            .iter_mut()
-            .for_each(|c| c.render_mut().unwrap().show());
-        filter.as_ref().map(|filter| {
+        self.drop_compositions_as_components();
+        filter.map(|query| {
+            let candidates = query.get_candidates(index);
+            let is_matched = |sig: &Signature, ports: &Ports| {
+                is_component_matched(query, &candidates, sig, ports, index)
+            };
             let targeted = [
                 self.entity
                     .components
                     .iter()
-                    .filter_map(|c| {
-                        if c.sig()
-                            .short_name
-                            .to_lowercase()
-                            .contains(&filter.to_lowercase())
-                        {
-                            Some(c.sig().id)
-                        } else {
-                            None
-                        }
-                    })
+                    .filter(|c| is_matched(c.sig(), c.origin().ports.origin()))
+                    .map(|c| c.sig().id)
                     .collect::<Vec<usize>>(),
                 self.entity
                     .compositions
                     .iter()
-                    .filter_map(|c| {
-                        if c.sig()
-                            .short_name
-                            .to_lowercase()
-                            .contains(&filter.to_lowercase())
-                        {
-                            Some(c.sig().id)
-                        } else {
-                            None
-                        }
-                    })
+                    .filter(|c| is_matched(c.sig(), c.origin().ports.origin()))
+                    .map(|c| c.sig().id)
                     .collect::<Vec<usize>>(),
             ]
             .concat();
+            let found = targeted.iter().copied().collect::<HashSet<usize>>();
             let linked = self
                 .entity
                 .connections
                 .iter()
                 .filter_map(|c| {
                     let connection = c.origin();
-                    if targeted.contains(connection.in_comp()) {
+                    if found.contains(connection.in_comp()) {
                         Some(*connection.out_comp())
-                    } else if targeted.contains(connection.out_comp()) {
+                    } else if found.contains(connection.out_comp()) {
                         Some(*connection.in_comp())
                     } else {
                         None
@@ -304,13 +224,7 @@ impl Render<Composition> {
         &mut self,
         targeted: Vec<usize>,
     ) -> Option<(Vec<usize>, Vec<usize>)> {
-        self.entity
-            .components
-            .retain(|c| c.render().map_or(true, |r| !r.is_composition()));
-        self.entity
-            .compositions
-            .iter_mut()
-            .for_each(|c| c.render_mut().unwrap().show());
+        self.drop_compositions_as_components();
         let linked = self
             .entity
             .connections
@@ -352,31 +266,29 @@ impl Render<Composition> {
 
     // TODO: we have to remove dupblicates, but it's a wierd fact we have it at all. It should be checked: why
     // we have duplicates of ports
-    pub fn get_matches(&mut self, filter: Option<String>) -> Option<Vec<usize>> {
+    pub fn get_matches(&mut self, filter: Option<&Query>, index: &Index) -> Option<Vec<usize>> {
         filter
-            .as_ref()
-            .map(|filter| {
+            .map(|query| {
+                let candidates = query.get_candidates(index);
+                let only = get_candidates_owners(&candidates, index);
+                let filter = |port: &Port| is_port_matched(query, &candidates, port, index);
+                let filter = &filter;
                 [
-                    self.entity
-                        .components
-                        .iter()
-                        .flat_map(|c| c.origin().ports.origin().get_filtered_ports(filter))
-                        .collect::<Vec<usize>>(),
-                    self.entity
-                        .compositions
-                        .iter()
-                        .flat_map(|c| c.origin().ports.origin().get_filtered_ports(filter))
+                    self.get_ports_owners(only.as_ref())
+                        .into_iter()
+                        .flat_map(|(_, ports)| ports.get_filtered_ports(filter))
                         .collect::<Vec<usize>>(),
-                    self.entity.ports.origin().get_filtered_ports(filter),
                     self.entity
                         .components
                         .iter()
                         .filter(|c| {
-                            c.origin()
-                                .sig
-                                .short_name
-                                .to_lowercase()
-                                .contains(&filter.to_lowercase())
+                            is_component_matched(
+                                query,
+                                &candidates,
+                                c.sig(),
+                                c.origin().ports.origin(),
+                                index,
+                            )
                         })
                         .map(|c| c.origin().sig().id)
                         .collect::<Vec<usize>>(),
@@ -385,70 +297,48 @@ impl Render<Composition> {
             })
             .map(|dirty| {
                 // We should not sort originaly collected dirty ids, because it's sorted by component/composition
-                let mut ids: Vec<usize> = Vec::new();
-                dirty.into_iter().for_each(|id| {
-                    if !ids.contains(&id) {
-                        ids.push(id);
-                    }
-                });
-                ids
+                let mut collected: HashSet<usize> = HashSet::new();
+                dirty
+                    .into_iter()
+                    .filter(|id| collected.insert(*id))
+                    .collect()
             })
     }
 
     // Vec<(port_id, holder_port_id, component_id)>
     pub fn get_matches_extended(
         &mut self,
-        filter: Option<String>,
+        filter: Option<&Query>,
+        index: &Index,
     ) -> Option<Vec<(usize, Option<usize>, usize)>> {
         let parent_id = self.sig().id;
         filter
-            .as_ref()
-            .map(|filter| {
+            .map(|query| {
+                let candidates = query.get_candidates(index);
+                let only = get_candidates_owners(&candidates, index);
+                let filter = |port: &Port| is_port_matched(query, &candidates, port, index);
+                let filter = &filter;
                 [
-                    self.entity
-                        .components
-                        .iter()
-                        .flat_map(|c| {
-                            let parent_id = c.sig().id;
-                            c.origin()
-                                .ports
-                                .origin()
-                                .get_filtered_ports_expanded(filter)
-                                .into_iter()
-                                .map(|(port_id, holder_id)| (port_id, holder_id, parent_id))
-                                .collect::<Vec<(usize, Option<usize>, usize)>>()
-                        })
-                        .collect::<Vec<(usize, Option<usize>, usize)>>(),
-                    self.entity
-                        .compositions
-                        .iter()
-                        .flat_map(|c| {
-                            let parent_id = c.sig().id;
-                            c.origin()
-                                .ports
-                                .origin()
+                    self.get_ports_owners(only.as_ref())
+                        .into_iter()
+                        .flat_map(|(owner, ports)| {
+                            ports
                                 .get_filtered_ports_expanded(filter)
                                 .into_iter()
-                                .map(|(port_id, holder_id)| (port_id, holder_id, parent_id))
-                                .collect::<Vec<(usize, Option<usize>, usize)>>()
+                                .map(move |(port_id, holder_id)| (port_id, holder_id, owner))
                         })
                         .collect::<Vec<(usize, Option<usize>, usize)>>(),
-                    self.entity
-                        .ports
-                        .origin()
-                        .get_filtered_ports_expanded(filter)
-                        .into_iter()
-                        .map(|(port_id, holder_id)| (port_id, holder_id, parent_id))
-                        .collect::<Vec<(usize, Option<usize>, usize)>>(),
                     self.entity
                         .components
                         .iter()
                         .filter(|c| {
-                            c.origin()
-                                .sig
-                                .short_name
-                                .to_lowercase()
-                                .contains(&filter.to_lowercase())
+                            is_component_matched(
+                                query,
+                                &candidates,
+                                c.sig(),
+                                c.origin().ports.origin(),
+                                index,
+                            )
                         })
                         .map(|c| (c.origin().sig().id, None, parent_id))
                         .collect::<Vec<(usize, Option<usize>, usize)>>(),
@@ -457,15 +347,11 @@ impl Render<Composition> {
             })
             .map(|dirty| {
                 // We should not sort originaly collected dirty ids, because it's sorted by component/composition
-                let mut ids: Vec<(usize, Option<usize>, usize)> = Vec::new();
-                let mut collected: Vec<usize> = Vec::new();
-                dirty.into_iter().for_each(|(id, holder, owner)| {
-                    if !collected.contains(&id) {
-                        collected.push(id);
-                        ids.push((id, holder, owner));
-                    }
-                });
-                ids
+                let mut collected: HashSet<usize> = HashSet::new();
+                dirty
+                    .into_iter()
+                    .filter(|(id, _, _)| collected.insert(*id))
+                    .collect()
             })
     }
 
@@ -611,12 +497,16 @@ impl Render<Composition> {
         Ok(())
     }
 
+    /// Calculates layout of composition. If `pinned` (previous grid) is given, components
+    /// keep own places, if their size wasn't changed; new and resized components are placed
+    /// into free cells.
     pub fn calc(
         &mut self,
         context: &mut web_sys::CanvasRenderingContext2d,
         grid: &mut Grid,
         state: &State,
         options: &Options,
+        pinned: Option<&Grid>,
     ) -> Result<(), E> {
         let relative = &state.get_view_relative();
         // Create composition grid
@@ -642,9 +532,33 @@ impl Render<Composition> {
                 .render_mut()?
                 .calc(context, relative, options, state, self.entity.sig.id)?;
         }
+        let mut located: Vec<usize> = Vec::new();
+        if let Some(pinned) = pinned {
+            for component in self
+                .entity
+                .components
+                .iter()
+                .filter(|c| state.is_comp_included(&c.sig().id))
+            {
+                let id = component.sig().id;
+                let Some((_, (x, y, x1, y1))) = pinned.map.get(&id.to_string()) else {
+                    continue;
+                };
+                let component_grid = Grid::forms_as_pair(
+                    get_forms_by_ids(&self.entity.components, &[id])?,
+                    [].to_vec(),
+                    &options.grid,
+                    options.ratio(),
+                )?;
+                if component_grid.size == (x1 - x + 1, y1 - y + 1)
+                    && composition_grid.place(&component_grid, (*x, *y))
+                {
+                    located.push(id);
+                }
+            }
+        }
         // Get dependencies data (list of components with IN / OUT connections)
         let mut dependencies: Vec<(usize, usize)> = Vec::new();
-        let mut located: Vec<usize> = Vec::new();
         let ordered_linked = Connection::get_ordered_linked(&self.entity.connections, state);
         for (host_id, _, _) in ordered_linked.iter() {
             if located.contains(host_id) {
@@ -724,7 +638,14 @@ impl Render<Composition> {
         if !targets.contains(&self.entity.sig.id) || self.hidden {
             return Ok(());
         }
+        let dimmed = state.is_dimmed(&self.entity.sig.id);
+        if dimmed {
+            context.set_global_alpha(options.scheme.dimmed_alpha);
+        }
         self.view.render(context, relative, options);
+        if dimmed {
+            context.set_global_alpha(1.0);
+        }
         for component in self
             .entity
             .components
@@ -746,6 +667,9 @@ impl Render<Composition> {
                 .draw(context, relative, targets, options, state)?;
         }
         let ratio = options.ratio();
+        if dimmed {
+            context.set_global_alpha(options.scheme.dimmed_alpha);
+        }
         context.set_stroke_style(&JsValue::from_str(&options.scheme.composition_label.stroke));
         context.set_text_baseline("bottom");
         context.set_font(&format!(
@@ -766,10 +690,15 @@ impl Render<Composition> {
             state,
             self.entity.sig.id,
         )?;
+        if dimmed {
+            context.set_global_alpha(1.0);
+        }
         for connection in self.entity.connections.iter_mut().filter(|conn| {
             conn.origin().visibility
-                && (state.is_port_selected_or_highlighted(conn.origin().in_port())
+                && ((state.is_port_selected_or_highlighted(conn.origin().in_port())
                     && state.is_port_selected_or_highlighted(conn.origin().out_port()))
+                    || state.is_connection_mismatched(&conn.sig().id)
+                    || state.get_change(&conn.sig().id).is_some())
         }) {
             connection
                 .render_mut()?
@@ -950,6 +879,36 @@ impl Render<Composition> {
         Ok(found)
     }
 
+    /// Returns points of calculated connections (without view offset and zoom)
+    pub fn get_connections_points(
+        &self,
+        state: &State,
+        ratio: &Ratio,
+    ) -> Vec<(usize, Vec<(i32, i32)>)> {
+        self.entity
+            .connections
+            .iter()
+            .filter(|conn| {
+                let origin = conn.origin();
+                origin.visibility
+                    && state.is_comp_included(origin.in_comp())
+                    && state.is_comp_included(origin.out_comp())
+            })
+            .filter_map(|conn| {
+                let Form::Path(_, path) = &conn.render().ok()?.view.container.form else {
+                    return None;
+                };
+                Some((
+                    conn.sig().id,
+                    path.points
+                        .iter()
+                        .map(|p| (ratio.invert(p.x), ratio.invert(p.y)))
+                        .collect(),
+                ))
+            })
+            .collect()
+    }
+
     pub fn get_grouped_ports(&self) -> Result<Vec<(usize, Vec<usize>)>, E> {
         let mut ports: Vec<(usize, Vec<usize>)> = Vec::new();
         for component in self.entity.components.iter() {
@@ -961,6 +920,150 @@ impl Render<Composition> {
         Ok(ports)
     }
 
+    /// Returns ports grouped by interface id
+    pub fn get_ports_by_interface(&self) -> Vec<(usize, Vec<usize>)> {
+        let mut grouped: Vec<(usize, Vec<usize>)> = Vec::new();
+        [
+            vec![self.entity.ports.origin().get_grouped_by_interface()],
+            self.entity
+                .components
+                .iter()
+                .map(|c| c.origin().ports.origin().get_grouped_by_interface())
+                .collect::<Vec<Vec<(usize, Vec<usize>)>>>(),
+            self.entity
+                .compositions
+                .iter()
+                .map(|c| c.origin().ports.origin().get_grouped_by_interface())
+                .collect::<Vec<Vec<(usize, Vec<usize>)>>>(),
+        ]
+        .concat()
+        .into_iter()
+        .flatten()
+        .for_each(|(interface, ports)| {
+            if let Some((_, ids)) = grouped.iter_mut().find(|(id, _)| id == &interface) {
+                ports.into_iter().for_each(|port| {
+                    if !ids.contains(&port) {
+                        ids.push(port);
+                    }
+                });
+            } else {
+                grouped.push((interface, ports));
+            }
+        });
+        grouped
+    }
+
+    /// Returns interface used by port
+    pub fn get_port_interface(&self, id: usize) -> Option<&Interface> {
+        self.get_port(id)
+            .and_then(|port| port.interface())
+            .and_then(|sig| self.origin().find_interface(&sig.id))
+    }
+
+    /// Checks whether two ports can be connected: directions should complement each other
+    /// and interfaces should be compatible.
+    pub fn are_ports_compatible(&self, a: usize, b: usize) -> Result<bool, E> {
+        let (Some(port_a), Some(port_b)) = (self.get_port(a), self.get_port(b)) else {
+            return Err(E::Other(format!("Fail to find ports {a} and/or {b}")));
+        };
+        Ok(interfaces::check(port_a, port_b, self.origin()).is_none())
+    }
+
+    /// Checks whether new connection between two visible ports can be created. Returns
+    /// joints ordered by direction: (in, out)
+    pub fn validate_connection(&self, a: usize, b: usize) -> Result<(Joint, Joint), Rejection> {
+        if a == b {
+            return Err(Rejection::SamePort);
+        }
+        let (Some(port_a), Some(port_b)) = (self.get_port(a), self.get_port(b)) else {
+            return Err(Rejection::NotFound(if self.get_port(a).is_none() {
+                a
+            } else {
+                b
+            }));
+        };
+        for port in [port_a, port_b] {
+            if !port.contains.is_empty() {
+                return Err(Rejection::GroupedPort(port.sig.id));
+            }
+        }
+        let (Some(owner_a), Some(owner_b)) = (
+            self.entity.find_port_owner(&a),
+            self.entity.find_port_owner(&b),
+        ) else {
+            return Err(Rejection::NotFound(a));
+        };
+        if owner_a == owner_b {
+            return Err(Rejection::SameComponent);
+        }
+        if self
+            .entity
+            .connections
+            .iter()
+            .any(|c| (&(a, b)).included_as_port(c))
+        {
+            return Err(Rejection::AlreadyConnected);
+        }
+        if let Some(reason) = interfaces::check(port_a, port_b, self.origin()) {
+            return Err(Rejection::Mismatch(reason));
+        }
+        if matches!(port_a.direction(), Some(PortDirection::Required))
+            || matches!(port_b.direction(), Some(PortDirection::Provided))
+        {
+            Ok((Joint::new(a, owner_a), Joint::new(b, owner_b)))
+        } else {
+            Ok((Joint::new(b, owner_b), Joint::new(a, owner_a)))
+        }
+    }
+
+    /// Returns visible ports, which can be connected with given port
+    pub fn get_connectable_ports(&self, port: usize) -> Vec<usize> {
+        self.entity
+            .components
+            .iter()
+            .flat_map(|c| c.origin().ports.origin().iter())
+            .chain(self.entity.ports.origin().iter())
+            .filter(|p| p.origin().visibility && p.origin().contains.is_empty())
+            .map(|p| p.sig().id)
+            .filter(|id| self.validate_connection(port, *id).is_ok())
+            .collect()
+    }
+
+    /// Returns signature with id, which isn't used by any entity
+    pub fn next_free_sig(
+        &mut self,
+        sig_producer: &mut SignatureProducer,
+        class_name: &str,
+    ) -> Signature {
+        loop {
+            let sig = sig_producer.next_for(class_name);
+            if self.entity.find_sig_mut(&sig.id).is_none() {
+                return sig;
+            }
+        }
+    }
+
+    /// Returns id of connection, which represents given connection on board. Hidden
+    /// (grouped) connection is represented by connection between grouped ports.
+    pub fn get_visible_connection(&self, connection: &Connection) -> usize {
+        if connection.visibility {
+            return connection.sig.id;
+        }
+        let grouped = self.get_grouped_ports().unwrap_or_default();
+        let Some((holder, _)) = grouped
+            .iter()
+            .find(|(_, inners)| inners.contains(&connection.joint_in.port))
+        else {
+            return connection.sig.id;
+        };
+        self.entity
+            .connections
+            .iter()
+            .find(|c| &c.origin().joint_in.port == holder)
+            .map(|c| c.sig().id)
+            .unwrap_or(connection.sig.id)
+    }
+
     pub fn get_port(&self, id: usize) -> Option<&Port> {
         if let Some(port) = self.origin().get_port(&id) {
             return Some(port);
@@ -978,7 +1081,10 @@ impl Render<Composition> {
         None
     }
 
-    fn connection_to_connection_data(&self, conn: &Connection) -> (ConnectionData, ConnectionData) {
+    pub fn connection_to_connection_data(
+        &self,
+        conn: &Connection,
+    ) -> (ConnectionData, ConnectionData) {
         let port_out = self.find_port(conn.out_comp(), conn.out_port());
         let port_in = self.find_port(conn.in_comp(), conn.in_port());
         if let (Some(port_out), Some(port_in)) = (port_out, port_in) {
@@ -1043,6 +1149,374 @@ impl Render<Composition> {
         self.entity.components.iter().map(|c| c.sig().id).collect()
     }
 
+    /// Removes compositions, which were added as components during calculation
+    pub fn drop_compositions_as_components(&mut self) {
+        self.entity
+            .components
+            .retain(|c| c.render().map_or(true, |r| !r.is_composition()));
+        self.entity
+            .compositions
+            .iter_mut()
+            .for_each(|c| c.render_mut().unwrap().show());
+    }
+
+    /// Adds component into composition (self or nested)
+    pub fn add_component(
+        &mut self,
+        parent: &usize,
+        component: Component,
+        options: &Options,
+        sig_producer: &mut SignatureProducer,
+    ) -> Result<(), E> {
+        self.ensure_unique(
+            &[
+                vec![component.sig.id],
+                component
+                    .ports
+                    .origin()
+                    .iter()
+                    .map(|p| p.sig().id)
+                    .collect(),
+            ]
+            .concat(),
+        )?;
+        if self.entity.find_composition_mut(parent).is_none() {
+            return Err(E::NotFound(*parent));
+        }
+        self.drop_compositions_as_components();
+        let affected = vec![component.sig.id];
+        if let Some(holder) = self.entity.find_composition_mut(parent) {
+            holder.components.push(Representation::Origin(component));
+        }
+        self.regroup(&affected, options, sig_producer);
+        Ok(())
+    }
+
+    /// Adds nested composition into composition (self or nested)
+    pub fn add_composition(
+        &mut self,
+        parent: &usize,
+        mut composition: Composition,
+        options: &Options,
+        sig_producer: &mut SignatureProducer,
+    ) -> Result<(), E> {
+        self.ensure_unique(&composition.get_all_ids())?;
+        if self.entity.find_composition_mut(parent).is_none() {
+            return Err(E::NotFound(*parent));
+        }
+        self.drop_compositions_as_components();
+        let affected = vec![composition.sig.id];
+        composition.parent = Some(*parent);
+        if let Some(holder) = self.entity.find_composition_mut(parent) {
+            holder
+                .compositions
+                .push(Representation::Origin(composition));
+        }
+        self.regroup(&affected, options, sig_producer);
+        Ok(())
+    }
+
+    /// Removes component or nested composition with all related connections.
+    /// Returns removed entities; connections go before component.
+    pub fn remove_component(
+        &mut self,
+        id: &usize,
+        options: &Options,
+        sig_producer: &mut SignatureProducer,
+    ) -> Result<Vec<Removed>, E> {
+        if id == &self.entity.sig.id {
+            return Err(E::NotFound(*id));
+        }
+        let holder = self
+            .entity
+            .find_component_holder(id)
+            .ok_or(E::NotFound(*id))?;
+        self.drop_compositions_as_components();
+        let mut affected = self.get_affected(&[*id]);
+        self.ungroup(&affected, options);
+        // Connections are taken first to update counters of ports of component
+        let mut removed = self.take_connections(&|c: &Connection| {
+            &c.joint_in.component == id || &c.joint_out.component == id
+        });
+        if let Some(component) = self.entity.take_component(id) {
+            removed.push(Removed::Component(
+                holder,
+                json::expand_component(component.origin()),
+            ));
+        } else if let Some(composition) = self.entity.take_composition(id) {
+            removed.push(Removed::Composition(
+                holder,
+                json::expand(composition.origin(), &|_| true),
+            ));
+        }
+        affected.retain(|v| v != id);
+        self.regroup(&affected, options, sig_producer);
+        Ok(removed)
+    }
+
+    /// Adds port to component or composition (self or nested)
+    pub fn add_port(
+        &mut self,
+        owner: &usize,
+        port: Port,
+        options: &Options,
+        sig_producer: &mut SignatureProducer,
+    ) -> Result<(), E> {
+        self.ensure_unique(&[port.sig.id])?;
+        if self.entity.find_ports(owner).is_none() {
+            return Err(E::NotFound(*owner));
+        }
+        self.drop_compositions_as_components();
+        let affected = self.get_affected(&[*owner]);
+        self.ungroup(&affected, options);
+        if let Some(ports) = self.entity.find_ports_mut(owner) {
+            ports.add(Representation::Origin(port), None);
+        }
+        self.regroup(&affected, options, sig_producer);
+        Ok(())
+    }
+
+    /// Removes port with all related connections. Returns removed entities; connections go
+    /// before port.
+    pub fn remove_port(
+        &mut self,
+        id: &usize,
+        options: &Options,
+        sig_producer: &mut SignatureProducer,
+    ) -> Result<Vec<Removed>, E> {
+        let owner = self.entity.find_port_owner(id).ok_or(E::NotFound(*id))?;
+        if self.is_grouped_port(&owner, id) {
+            return Err(E::GroupedEntity(*id));
+        }
+        self.drop_compositions_as_components();
+        let affected = self.get_affected(&[owner]);
+        self.ungroup(&affected, options);
+        let mut removed = self
+            .take_connections(&|c: &Connection| &c.joint_in.port == id || &c.joint_out.port == id);
+        if let Some(port) = self.entity.take_port(&owner, id) {
+            removed.push(Removed::Port(owner, port.origin().clone()));
+        }
+        self.regroup(&affected, options, sig_producer);
+        Ok(removed)
+    }
+
+    /// Adds connection into composition (self or nested)
+    pub fn add_connection(
+        &mut self,
+        parent: &usize,
+        mut connection: Connection,
+        options: &Options,
+        sig_producer: &mut SignatureProducer,
+    ) -> Result<(), E> {
+        self.ensure_unique(&[connection.sig.id])?;
+        if self.entity.find_composition_mut(parent).is_none() {
+            return Err(E::NotFound(*parent));
+        }
+        for joint in [&connection.joint_in, &connection.joint_out] {
+            if self
+                .entity
+                .find_ports(&joint.component)
+                .and_then(|ports| ports.find(&joint.port))
+                .is_none()
+            {
+                return Err(E::NotFound(joint.port));
+            }
+            if self.is_grouped_port(&joint.component, &joint.port) {
+                return Err(E::GroupedEntity(joint.port));
+            }
+        }
+        self.drop_compositions_as_components();
+        connection.joint_in.grouped = None;
+        connection.joint_out.grouped = None;
+        let affected = self.get_affected(&[
+            connection.joint_in.component,
+            connection.joint_out.component,
+        ]);
+        self.ungroup(&affected, options);
+        for joint in [&connection.joint_in, &connection.joint_out] {
+            self.entity
+                .count_connection(&joint.component, &joint.port, *parent, true);
+        }
+        if let Some(holder) = self.entity.find_composition_mut(parent) {
+            holder.connections.push(Representation::Origin(connection));
+        }
+        self.regroup(&affected, options, sig_producer);
+        Ok(())
+    }
+
+    /// Removes connection. Connections between grouped ports cannot be removed, because
+    /// such connections are created by render.
+    pub fn remove_connection(
+        &mut self,
+        id: &usize,
+        options: &Options,
+        sig_producer: &mut SignatureProducer,
+    ) -> Result<Vec<Removed>, E> {
+        let holder = self
+            .entity
+            .find_connection_holder(id)
+            .ok_or(E::NotFound(*id))?;
+        let ((in_comp, in_port), (out_comp, out_port)) = self
+            .entity
+            .find_composition_mut(&holder)
+            .and_then(|c| c.connections.iter().find(|c| &c.sig().id == id))
+            .map(|c| {
+                let conn = c.origin();
+                (
+                    (conn.joint_in.component, conn.joint_in.port),
+                    (conn.joint_out.component, conn.joint_out.port),
+                )
+            })
+            .ok_or(E::NotFound(*id))?;
+        if self.is_grouped_port(&in_comp, &in_port) || self.is_grouped_port(&out_comp, &out_port) {
+            return Err(E::GroupedEntity(*id));
+        }
+        self.drop_compositions_as_components();
+        let affected = self.get_affected(&[in_comp, out_comp]);
+        self.ungroup(&affected, options);
+        let removed = self.take_connections(&|c: &Connection| &c.sig.id == id);
+        self.regroup(&affected, options, sig_producer);
+        Ok(removed)
+    }
+
+    /// Changes short name of any entity
+    pub fn rename(
+        &mut self,
+        id: &usize,
+        short_name: String,
+        options: &Options,
+        sig_producer: &mut SignatureProducer,
+    ) -> Result<(), E> {
+        if self.entity.find_sig_mut(id).is_none() {
+            return Err(E::NotFound(*id));
+        }
+        // Compositions added as components are dropped to rename composition itself
+        self.drop_compositions_as_components();
+        if let Some(sig) = self.entity.find_sig_mut(id) {
+            sig.short_name = short_name;
+        }
+        if let Some(owner) = self.entity.find_port_owner(id) {
+            if let Some(Representation::Render(ports)) = self.entity.find_ports_rep_mut(&owner) {
+                ports.refresh_label(id, options)?;
+            }
+            return Ok(());
+        }
+        // Labels of grouped ports are taken from names of components
+        let affected = self.get_affected(&[*id]);
+        self.ungroup(&affected, options);
+        self.regroup(&affected, options, sig_producer);
+        Ok(())
+    }
+
+    fn ensure_unique(&mut self, ids: &[usize]) -> Result<(), E> {
+        for id in ids.iter() {
+            if self.entity.find_sig_mut(id).is_some() {
+                return Err(E::AlreadyExists(*id));
+            }
+        }
+        Ok(())
+    }
+
+    fn is_grouped_port(&self, owner: &usize, id: &usize) -> bool {
+        self.entity
+            .find_ports(owner)
+            .and_then(|ports| ports.find(id))
+            .map(|p| !p.origin().contains.is_empty())
+            .unwrap_or(false)
+    }
+
+    /// Returns given components with all components linked to it
+    fn get_affected(&self, ids: &[usize]) -> Vec<usize> {
+        let mut affected = ids.to_vec();
+        self.get_components_linked_to(ids.to_vec())
+            .into_iter()
+            .for_each(|id| {
+                if !affected.contains(&id) {
+                    affected.push(id);
+                }
+            });
+        affected
+    }
+
+    /// Removes connections and updates counters of connections of related ports.
+    /// Returns removed connections.
+    fn take_connections<F: Fn(&Connection) -> bool>(&mut self, predicate: &F) -> Vec<Removed> {
+        let removed = self.entity.take_connections(predicate);
+        for (holder, connection) in removed.iter() {
+            for joint in [
+                &connection.origin().joint_in,
+                &connection.origin().joint_out,
+            ] {
+                self.entity
+                    .count_connection(&joint.component, &joint.port, *holder, false);
+            }
+        }
+        removed
+            .into_iter()
+            .map(|(holder, connection)| {
+                let mut connection = connection.origin().clone();
+                connection.joint_in.grouped = None;
+                connection.joint_out.grouped = None;
+                Removed::Connection(holder, connection)
+            })
+            .collect()
+    }
+
+    /// Drops grouping of ports related to affected components
+    fn ungroup(&mut self, affected: &[usize], options: &Options) {
+        if options.ports.grouping {
+            ungroup_ports(&mut self.entity, affected);
+        }
+        if options.ports.group_unbound {
+            for owner in affected.iter() {
+                if let Some(ports) = self.entity.find_ports_mut(owner) {
+                    ungroup_unbound(ports);
+                }
+            }
+        }
+    }
+
+    /// Groups ports of affected components and renders new entities
+    fn regroup(
+        &mut self,
+        affected: &[usize],
+        options: &Options,
+        sig_producer: &mut SignatureProducer,
+    ) {
+        if options.ports.grouping {
+            group_ports(&mut self.entity, sig_producer, Some(affected));
+        }
+        if options.ports.group_unbound {
+            for owner in affected.iter() {
+                if let Some(ports) = self.entity.find_ports_mut(owner) {
+                    group_unbound(ports, sig_producer);
+                }
+            }
+        }
+        self.entity.resolve_interfaces(&[]);
+        render_origins(&mut self.entity, options, sig_producer);
+        self.entity.order();
+    }
+
+    /// Returns owners of ports (components, compositions and composition itself) in
+    /// order of model. If `only` is defined, other owners are skipped without walking
+    /// their ports.
+    fn get_ports_owners<'a>(&'a self, only: Option<&HashSet<usize>>) -> Vec<(usize, &'a Ports)> {
+        self.entity
+            .components
+            .iter()
+            .map(|c| (c.sig().id, c.origin().ports.origin()))
+            .chain(
+                self.entity
+                    .compositions
+                    .iter()
+                    .map(|c| (c.sig().id, c.origin().ports.origin())),
+            )
+            .chain([(self.sig().id, self.entity.ports.origin())])
+            .filter(|(id, _)| only.is_none_or(|only| only.contains(id)))
+            .collect()
+    }
+
     fn find_entity<'a>(&'a self, id: &usize) -> Option<Entry<'a>> {
         self.entity
             .components
@@ -1068,6 +1542,48 @@ impl Render<Composition> {
     }
 }
 
+/// Returns ids of owners of candidates found by index (candidate components are owners
+/// of themselves). None means index can't narrow query and all owners should be checked.
+fn get_candidates_owners(
+    candidates: &Option<HashSet<usize>>,
+    index: &Index,
+) -> Option<HashSet<usize>> {
+    candidates.as_ref().map(|candidates| {
+        candidates
+            .iter()
+            .map(|id| index.owner(id).unwrap_or(*id))
+            .collect()
+    })
+}
+
+/// Checks port with query; ports, which aren't candidates found by index, are skipped
+fn is_port_matched(
+    query: &Query,
+    candidates: &Option<HashSet<usize>>,
+    port: &Port,
+    index: &Index,
+) -> bool {
+    candidates
+        .as_ref()
+        .is_none_or(|candidates| candidates.contains(&port.sig.id))
+        && query.is_port_matched(port, index)
+}
+
+/// Checks component with query; components, which aren't candidates found by index, are
+/// skipped
+fn is_component_matched(
+    query: &Query,
+    candidates: &Option<HashSet<usize>>,
+    sig: &Signature,
+    ports: &Ports,
+    index: &Index,
+) -> bool {
+    candidates
+        .as_ref()
+        .is_none_or(|candidates| candidates.contains(&sig.id))
+        && query.is_component_matched(sig, ports, index)
+}
+
 fn get_forms_by_ids<'a>(
     components: &'a [Representation<Component>],
     ids: &[usize],
@@ -1081,7 +1597,13 @@ fn get_forms_by_ids<'a>(
     Ok(found)
 }
 
-pub fn group_ports(entity: &mut Composition, sig_producer: &mut SignatureProducer) {
+/// Groups ports of components, which have more than one connection between each other.
+/// If `only` is defined, only connections of given components will be grouped.
+pub fn group_ports(
+    entity: &mut Composition,
+    sig_producer: &mut SignatureProducer,
+    only: Option<&[usize]>,
+) {
     let mut added_connections: Vec<Representation<Connection>> = Vec::new();
     let mut added_ports: Vec<(usize, Representation<Port>)> = Vec::new();
     let mut grouped: HashMap<(usize, usize), Vec<(usize, usize)>> = HashMap::new();
@@ -1120,6 +1642,9 @@ pub fn group_ports(entity: &mut Composition, sig_producer: &mut SignatureProduce
         .filter(|conn| {
             ports.contains_key(conn.origin().in_port())
                 && ports.contains_key(conn.origin().out_port())
+                && only.is_none_or(|ids| {
+                    ids.contains(conn.origin().in_comp()) || ids.contains(conn.origin().out_comp())
+                })
         })
         .for_each(|conn| {
             let uuid = (*conn.origin().in_comp(), *conn.origin().out_comp());
@@ -1173,6 +1698,8 @@ pub fn group_ports(entity: &mut Composition, sig_producer: &mut SignatureProduce
                     find(&entity.components, &entity.compositions, comp_joint_out)
                         .map(|en| en.sig().short_name.to_owned())
                 },
+                interface_kind: None,
+                interface_kind_resolved: false,
                 visibility: true,
             };
             let mut connected = HashMap::new();
@@ -1195,6 +1722,8 @@ pub fn group_ports(entity: &mut Composition, sig_producer: &mut SignatureProduce
                     find(&entity.components, &entity.compositions, comp_joint_in)
                         .map(|en| en.sig().short_name.to_owned())
                 },
+                interface_kind: None,
+                interface_kind_resolved: false,
                 visibility: true,
             };
             added_connections.push(Representation::Origin(Connection {
@@ -1274,62 +1803,179 @@ pub fn group_ports(entity: &mut Composition, sig_producer: &mut SignatureProduce
 }
 
 pub fn group_unbound_ports(entity: &mut Composition, sig_producer: &mut SignatureProducer) {
-    let unbound_ports = entity
-        .ports
-        .origin()
+    group_unbound(entity.ports.origin_mut(), sig_producer);
+    for component in entity.components.iter_mut() {
+        group_unbound(component.origin_mut().ports.origin_mut(), sig_producer);
+    }
+}
+
+/// Hides all unbound ports into one grouped port
+pub fn group_unbound(ports: &mut Ports, sig_producer: &mut SignatureProducer) {
+    let unbound_ports = ports
         .ports
         .iter()
         .filter(|p| p.origin().connected.is_empty() && p.origin().contains.is_empty())
         .map(|p| p.sig().id)
         .collect::<Vec<usize>>();
-    if !unbound_ports.is_empty() && unbound_ports.len() != 1 {
-        entity.ports.origin_mut().hide(&unbound_ports);
-        entity.ports.origin_mut().add(
-            Representation::Origin(Port {
-                provided_interface: None,
-                provided_required_interface: None,
-                required_interface: None,
-                sig: sig_producer.next_for("unbound grouped"),
-                port_type: PortType::Left,
-                contains: unbound_ports,
-                connected: HashMap::new(),
-                visibility: true,
-                label: None,
-            }),
-            Some(0),
-        );
+    if unbound_ports.is_empty() || unbound_ports.len() == 1 {
+        return;
     }
-    for component in entity.components.iter_mut() {
-        let unbound_ports = component
-            .origin()
-            .ports
-            .origin()
-            .ports
-            .iter()
-            .filter(|p| p.origin().connected.is_empty() && p.origin().contains.is_empty())
-            .map(|p| p.sig().id)
-            .collect::<Vec<usize>>();
-        if unbound_ports.is_empty() || unbound_ports.len() == 1 {
-            continue;
-        }
-        component
-            .origin_mut()
-            .ports
-            .origin_mut()
-            .hide(&unbound_ports);
-        component.origin_mut().ports.origin_mut().add(
-            Representation::Origin(Port {
-                sig: sig_producer.next_for("unbound grouped"),
-                provided_interface: None,
-                provided_required_interface: None,
-                required_interface: None,
-                port_type: PortType::Left,
-                contains: unbound_ports,
-                connected: HashMap::new(),
-                visibility: true,
-                label: None,
-            }),
-            Some(0),
-        );
+    ports.hide(&unbound_ports);
+    ports.add(
+        Representation::Origin(Port {
+            sig: sig_producer.next_for("unbound grouped"),
+            provided_interface: None,
+            provided_required_interface: None,
+            required_interface: None,
+            port_type: PortType::Left,
+            contains: unbound_ports,
+            connected: HashMap::new(),
+            visibility: true,
+            label: None,
+            interface_kind: None,
+            interface_kind_resolved: false,
+        }),
+        Some(0),
+    );
+}
+
+/// Removes grouped port with unbound ports and shows hidden unbound ports
+pub fn ungroup_unbound(ports: &mut Ports) {
+    let Some(id) = ports
+        .ports
+        .iter()
+        .find(|p| p.origin().connected.is_empty() && !p.origin().contains.is_empty())
+        .map(|p| p.sig().id)
+    else {
+        return;
+    };
+    if let Some(grouped) = ports.take(&id) {
+        ports.show(&grouped.origin().contains);
     }
 }
+
+/// Removes grouped ports (and connections between grouped ports), which are related
+/// to given components. Original ports and connections become visible.
+pub fn ungroup_ports(entity: &mut Composition, components: &[usize]) {
+    let is_grouped = |entity: &Composition, owner: &usize, port: &usize| {
+        entity
+            .find_ports(owner)
+            .and_then(|ports| ports.find(port))
+            .map(|p| !p.origin().contains.is_empty() && !p.origin().connected.is_empty())
+            .unwrap_or(false)
+    };
+    let joined = entity
+        .connections
+        .iter()
+        .filter(|c| {
+            let conn = c.origin();
+            (components.contains(&conn.joint_in.component)
+                || components.contains(&conn.joint_out.component))
+                && is_grouped(entity, &conn.joint_in.component, &conn.joint_in.port)
+                && is_grouped(entity, &conn.joint_out.component, &conn.joint_out.port)
+        })
+        .map(|c| {
+            let conn = c.origin();
+            (
+                conn.sig.id,
+                [
+                    (conn.joint_in.component, conn.joint_in.port),
+                    (conn.joint_out.component, conn.joint_out.port),
+                ],
+            )
+        })
+        .collect::<Vec<(usize, [(usize, usize); 2])>>();
+    let mut removed: Vec<usize> = Vec::new();
+    let mut contained: Vec<usize> = Vec::new();
+    for (connection, joints) in joined.iter() {
+        let _ = entity.take_connection(connection);
+        for (owner, port) in joints.iter() {
+            let Some(ports) = entity.find_ports_mut(owner) else {
+                continue;
+            };
+            if let Some(grouped) = ports.take(port) {
+                ports.show(&grouped.origin().contains);
+                contained.extend(grouped.origin().contains.iter());
+                removed.push(*port);
+            }
+        }
+    }
+    entity.connections.iter_mut().for_each(|c| {
+        let conn = c.origin_mut();
+        if conn
+            .joint_in
+            .grouped
+            .is_some_and(|id| removed.contains(&id))
+        {
+            conn.joint_in.grouped = None;
+        }
+        if conn
+            .joint_out
+            .grouped
+            .is_some_and(|id| removed.contains(&id))
+        {
+            conn.joint_out.grouped = None;
+        }
+        if contained.contains(&conn.joint_in.port) || contained.contains(&conn.joint_out.port) {
+            conn.visibility = true;
+        }
+    });
+}
+
+/// Creates render for entities, which aren't rendered yet (including entities added
+/// into already rendered components and compositions)
+fn render_origins(
+    entity: &mut Composition,
+    options: &Options,
+    sig_producer: &mut SignatureProducer,
+) {
+    entity.components = entity
+        .components
+        .drain(..)
+        .map(|r| match r {
+            Representation::Origin(component) => {
+                Representation::Render(Render::<Component>::new(component, options, None))
+            }
+            Representation::Render(mut render) => {
+                if let Ok(ports) = render.origin_mut().ports.render_mut() {
+                    ports.render_origins(options);
+                }
+                Representation::Render(render)
+            }
+        })
+        .collect::<Vec<Representation<Component>>>();
+    entity.compositions = entity
+        .compositions
+        .drain(..)
+        .map(|r| match r {
+            Representation::Origin(composition) => Representation::Render(
+                Render::<Composition>::new(composition, false, options, sig_producer),
+            ),
+            Representation::Render(mut render) => {
+                render_origins(render.origin_mut(), options, sig_producer);
+                Representation::Render(render)
+            }
+        })
+        .collect::<Vec<Representation<Composition>>>();
+    entity.connections = entity
+        .connections
+        .drain(..)
+        .map(|r| {
+            if let Representation::Origin(connection) = r {
+                Representation::Render(Render::<Connection>::new(connection, options))
+            } else {
+                r
+            }
+        })
+        .collect::<Vec<Representation<Connection>>>();
+    entity.ports = match std::mem::replace(&mut entity.ports, Representation::Origin(Ports::new()))
+    {
+        Representation::Origin(ports) => {
+            Representation::Render(Render::<Ports>::new(ports, options, false))
+        }
+        Representation::Render(mut render) => {
+            render.render_origins(options);
+            Representation::Render(render)
+        }
+    };
+}
@@ -124,12 +124,22 @@ impl Render<Component> {
         } else {
             self.view.container.style = (&options.scheme.component_rect).into();
         }
+        if let Some(kind) = state.get_change(&self.entity.sig.id) {
+            self.view.container.style = options.scheme.diff_rect(kind).into();
+        }
         if state.is_match(&self.entity.sig.id) {
             self.view.container.style = (&options.scheme.matched_rect).into();
         }
         if state.is_highlighted(&self.entity.sig.id) {
             self.view.container.style = (&options.scheme.highlighted_rect).into();
         }
+        if state.is_focused(&self.entity.sig.id) {
+            self.view.container.style = (&options.scheme.focused_rect).into();
+        }
+        let dimmed = state.is_dimmed(&self.entity.sig.id);
+        if dimmed {
+            context.set_global_alpha(options.scheme.dimmed_alpha);
+        }
         self.view.render(context, relative, options);
         let self_relative = self.relative(relative);
         let ratio = options.ratio();
@@ -150,6 +160,9 @@ impl Render<Component> {
             relative.x(self.view.container.get_coors().0) as f64,
             relative.y(self.view.container.get_coors().1 - ratio.get(3)) as f64,
         );
+        if dimmed {
+            context.set_global_alpha(1.0);
+        }
         Ok(())
     }
 }
@@ -2,7 +2,8 @@ use crate::{
     entity::{Connection, Signature, SignatureGetter},
     error::E,
     render::{
-        form::Path, grid::ElementType, options::Options, Container, Form, Relative, Render, View,
+        form::Path, grid::ElementType, options::Options, Container, Form, Relative, Render, Style,
+        View,
     },
     state::State,
 };
@@ -36,15 +37,35 @@ impl Render<Connection> {
         context: &mut web_sys::CanvasRenderingContext2d,
         relative: &Relative,
         options: &Options,
-        _state: &State,
+        state: &State,
     ) -> Result<(), E> {
+        self.view.container.style = if state.is_connection_mismatched(&self.entity.sig.id) {
+            (&options.scheme.connection_mismatch_line).into()
+        } else if let Some(kind) = state.get_change(&self.entity.sig.id) {
+            let color = options.scheme.diff_rect(kind);
+            Style {
+                stroke_style: color.stroke.clone(),
+                fill_style: color.stroke.clone(),
+            }
+        } else {
+            (&options.scheme.connection_line).into()
+        };
         if let Form::Path(_, path) = &mut self.view.container.form {
             path.sarrow = true;
             path.sdot = false;
             path.edot = true;
             path.earrow = false;
         }
+        // Connection could be replaced by connection of grouped ports, so it's checked by ports
+        let dimmed =
+            state.is_dimmed(self.entity.in_port()) || state.is_dimmed(self.entity.out_port());
+        if dimmed {
+            context.set_global_alpha(options.scheme.dimmed_alpha);
+        }
         self.view.render(context, relative, options);
+        if dimmed {
+            context.set_global_alpha(1.0);
+        }
         Ok(())
     }
 }
@@ -2,3 +2,5 @@ mod comosition;
 mod component;
 mod connection;
 mod port;
+
+pub use comosition::ConnectionData;
@@ -275,6 +275,29 @@ impl Render<Ports> {
         }
         Ok(found)
     }
+
+    /// Renders ports, which were added after rendering of holder
+    pub fn render_origins(&mut self, options: &Options) {
+        self.entity.ports = self
+            .entity
+            .ports
+            .drain(..)
+            .map(|r| {
+                if let Representation::Origin(port) = r {
+                    Representation::Render(Render::<Port>::new(port, options, false))
+                } else {
+                    r
+                }
+            })
+            .collect::<Vec<Representation<Port>>>();
+    }
+
+    pub fn refresh_label(&mut self, id: &usize, options: &Options) -> Result<(), E> {
+        if let Some(port) = self.entity.ports.iter_mut().find(|p| &p.sig().id == id) {
+            port.render_mut()?.refresh_label(options);
+        }
+        Ok(())
+    }
 }
 
 impl<'a, 'b: 'a> SignatureGetter<'a, 'b> for Render<Port> {
@@ -284,9 +307,8 @@ impl<'a, 'b: 'a> SignatureGetter<'a, 'b> for Render<Port> {
 }
 
 impl Render<Port> {
-    pub fn new(entity: Port, options: &Options, _belong_to_inner_composition: bool) -> Self {
-        let id = entity.sig.id;
-        let label = if entity.contains.is_empty() {
+    fn label(entity: &Port, options: &Options) -> String {
+        if entity.contains.is_empty() {
             entity.get_label(options)
         } else if let (1, Some(id)) = (entity.contains.len(), entity.contains.first()) {
             id.to_string()
@@ -296,18 +318,30 @@ impl Render<Port> {
             "unlinked".to_owned()
         } else {
             format!("{} ports", entity.contains.len())
-        };
+        }
+    }
+
+    pub fn new(entity: Port, options: &Options, _belong_to_inner_composition: bool) -> Self {
+        let id = entity.sig.id;
+        let label = Self::label(&entity, options);
         let align = match entity.port_type {
             PortType::Right => label::Align::Left,
             PortType::Left => label::Align::Right,
         };
         let ratio = options.ratio();
+        let badge_label = |sig: &Signature| {
+            if let Some(kind) = entity.interface_kind.as_ref() {
+                format!("{} {}", kind.abbreviation(), abbreviation(&sig.class_name))
+            } else {
+                abbreviation(&sig.class_name)
+            }
+        };
         let badge = entity
             .provided_required_interface
             .as_ref()
             .map(|v| {
                 (
-                    abbreviation(&v.class_name),
+                    badge_label(v),
                     options.scheme.port_pri_bagde.stroke.to_owned(),
                     options.scheme.port_pri_bagde.fill.to_owned(),
                 )
@@ -315,7 +349,7 @@ impl Render<Port> {
             .or_else(|| {
                 entity.provided_interface.as_ref().map(|v| {
                     (
-                        abbreviation(&v.class_name),
+                        badge_label(v),
                         options.scheme.port_pi_bagde.stroke.to_owned(),
                         options.scheme.port_pi_bagde.fill.to_owned(),
                     )
@@ -324,7 +358,7 @@ impl Render<Port> {
             .or_else(|| {
                 entity.required_interface.as_ref().map(|v| {
                     (
-                        abbreviation(&v.class_name),
+                        badge_label(v),
                         options.scheme.port_ri_bagde.stroke.to_owned(),
                         options.scheme.port_ri_bagde.fill.to_owned(),
                     )
@@ -376,6 +410,13 @@ impl Render<Port> {
         }
     }
 
+    pub fn refresh_label(&mut self, options: &Options) {
+        let label = Self::label(&self.entity, options);
+        if let Form::Label(_, form) = &mut self.view.container.form {
+            form.label.1 = label;
+        }
+    }
+
     pub fn calc(
         &mut self,
         context: &mut web_sys::CanvasRenderingContext2d,
@@ -461,6 +502,10 @@ impl Render<Port> {
             self.view.container.style = (&options.scheme.port_highlighted_rect).into();
             set_label_color(&mut self.view, &options.scheme.port_highlighted_rect.stroke);
         }
+        if let Some(kind) = state.get_change(&self.entity.sig.id) {
+            self.view.container.style = options.scheme.diff_rect(kind).into();
+            set_label_color(&mut self.view, &options.scheme.diff_rect(kind).stroke);
+        }
         if state.is_match(&self.entity.sig.id) {
             self.view.container.style = (&options.scheme.matched_rect).into();
             set_label_color(&mut self.view, &options.scheme.matched_rect.stroke);
@@ -469,6 +514,10 @@ impl Render<Port> {
             self.view.container.style = (&options.scheme.highlighted_rect).into();
             set_label_color(&mut self.view, &options.scheme.highlighted_rect.stroke);
         }
+        if state.is_focused(&self.entity.sig.id) {
+            self.view.container.style = (&options.scheme.focused_rect).into();
+            set_label_color(&mut self.view, &options.scheme.focused_rect.stroke);
+        }
         self.view.render(context, relative, options);
         Ok(())
     }
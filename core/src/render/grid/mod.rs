@@ -275,16 +275,8 @@ impl Grid {
             return false;
         }
         // Extend box to consider necessary spaces
-        x = if x > self.options.cells_space_horizontal {
-            x - self.options.cells_space_horizontal
-        } else {
-            0
-        };
-        y = if y > self.options.cells_space_vertical {
-            y - self.options.cells_space_vertical
-        } else {
-            0
-        };
+        x = x.saturating_sub(self.options.cells_space_horizontal);
+        y = y.saturating_sub(self.options.cells_space_vertical);
         x1 += self.options.cells_space_horizontal;
         y1 += self.options.cells_space_vertical;
         let extd_target = (x, y, x1, y1);
@@ -376,19 +368,40 @@ impl Grid {
                 }
             }
         }
-        // Merge grid
-        if let Some((p_x, p_y)) = point {
-            grid.map.iter().for_each(|(id, (ty, (x, y, x1, y1)))| {
-                self.map.insert(
-                    id.clone(),
-                    (ty.clone(), (x + p_x, y + p_y, x1 + p_x, y1 + p_y)),
-                );
-            });
+        if let Some(point) = point {
+            self.merge(grid, point);
         }
         // Remove unused space
         self.cut_unused_space();
     }
 
+    /// Inserts grid into given point, if space is free there. Returns false if space is
+    /// occupied; in this case grid isn't inserted.
+    pub fn place(&mut self, grid: &Grid, point: (u32, u32)) -> bool {
+        let (x, y) = point;
+        let target = (x, y, x + grid.size.0 - 1, y + grid.size.1 - 1);
+        self.size = (
+            self.size.0.max(target.2 + 1 + self.options.hpadding),
+            self.size.1.max(target.3 + 1 + self.options.vpadding),
+        );
+        let free = self.is_block_free(target);
+        if free {
+            self.merge(grid, point);
+        }
+        self.cut_unused_space();
+        free
+    }
+
+    fn merge(&mut self, grid: &Grid, point: (u32, u32)) {
+        let (p_x, p_y) = point;
+        grid.map.iter().for_each(|(id, (ty, (x, y, x1, y1)))| {
+            self.map.insert(
+                id.clone(),
+                (ty.clone(), (x + p_x, y + p_y, x1 + p_x, y1 + p_y)),
+            );
+        });
+    }
+
     pub fn as_px(&self, cells: u32) -> i32 {
         (self.cell * cells) as i32
     }
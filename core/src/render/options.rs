@@ -1,4 +1,4 @@
-use crate::render::Ratio;
+use crate::{analysis::diff::ChangeKind, render::Ratio};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -17,6 +17,15 @@ pub struct Ports {
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Connections {
     pub hide: bool,
+    // Distance (in px) to snap dragged connection to port in edit mode
+    #[serde(default = "Connections::default_snap_distance")]
+    pub snap_distance: u32,
+}
+
+impl Connections {
+    fn default_snap_distance() -> u32 {
+        12
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -57,7 +66,26 @@ pub struct ColorScheme {
     pub matched_rect: RectColor,
     pub hovered_rect: RectColor,
     pub connection_line: RectColor,
+    #[serde(default = "ColorScheme::default_connection_mismatch_line")]
+    pub connection_mismatch_line: RectColor,
+    #[serde(default = "ColorScheme::default_connection_drag_line")]
+    pub connection_drag_line: RectColor,
+    #[serde(default = "ColorScheme::default_connection_snapped_line")]
+    pub connection_snapped_line: RectColor,
+    #[serde(default = "ColorScheme::default_connection_rejected_line")]
+    pub connection_rejected_line: RectColor,
     pub port_highlighted_rect: RectColor,
+    // Element focused by keyboard navigation
+    #[serde(default = "ColorScheme::default_focused_rect")]
+    pub focused_rect: RectColor,
+    #[serde(default = "ColorScheme::default_diff_added_rect")]
+    pub diff_added_rect: RectColor,
+    #[serde(default = "ColorScheme::default_diff_removed_rect")]
+    pub diff_removed_rect: RectColor,
+    #[serde(default = "ColorScheme::default_diff_changed_rect")]
+    pub diff_changed_rect: RectColor,
+    #[serde(default = "ColorScheme::default_diff_moved_rect")]
+    pub diff_moved_rect: RectColor,
     pub port_rect: RectColor,
     pub port_unlinked_rect: RectColor,
     pub port_linked_rect: RectColor,
@@ -69,6 +97,77 @@ pub struct ColorScheme {
     pub port_subbagde: RectColor,
     pub label_subtitle: RectColor,
     pub label: RectColor,
+    // Opacity of entities outside of impact analysis result
+    #[serde(default = "ColorScheme::default_dimmed_alpha")]
+    pub dimmed_alpha: f64,
+}
+
+impl ColorScheme {
+    fn default_connection_mismatch_line() -> RectColor {
+        RectColor {
+            stroke: String::from("rgb(220,30,30)"),
+            fill: String::from("rgb(220,30,30)"),
+        }
+    }
+    fn default_connection_drag_line() -> RectColor {
+        RectColor {
+            stroke: String::from("rgb(120,120,120)"),
+            fill: String::from("rgb(120,120,120)"),
+        }
+    }
+    fn default_connection_snapped_line() -> RectColor {
+        RectColor {
+            stroke: String::from("rgb(20,150,20)"),
+            fill: String::from("rgb(20,150,20)"),
+        }
+    }
+    fn default_connection_rejected_line() -> RectColor {
+        RectColor {
+            stroke: String::from("rgb(220,30,30)"),
+            fill: String::from("rgb(220,30,30)"),
+        }
+    }
+    fn default_focused_rect() -> RectColor {
+        RectColor {
+            stroke: String::from("rgb(210,110,0)"),
+            fill: String::from("rgb(255,225,180)"),
+        }
+    }
+    fn default_diff_added_rect() -> RectColor {
+        RectColor {
+            stroke: String::from("rgb(20,120,20)"),
+            fill: String::from("rgb(190,240,190)"),
+        }
+    }
+    fn default_diff_removed_rect() -> RectColor {
+        RectColor {
+            stroke: String::from("rgb(160,20,20)"),
+            fill: String::from("rgb(250,190,190)"),
+        }
+    }
+    fn default_diff_changed_rect() -> RectColor {
+        RectColor {
+            stroke: String::from("rgb(170,110,0)"),
+            fill: String::from("rgb(255,225,160)"),
+        }
+    }
+    fn default_diff_moved_rect() -> RectColor {
+        RectColor {
+            stroke: String::from("rgb(20,70,170)"),
+            fill: String::from("rgb(190,210,250)"),
+        }
+    }
+    fn default_dimmed_alpha() -> f64 {
+        0.2
+    }
+    pub fn diff_rect(&self, kind: &ChangeKind) -> &RectColor {
+        match kind {
+            ChangeKind::Added => &self.diff_added_rect,
+            ChangeKind::Removed => &self.diff_removed_rect,
+            ChangeKind::Changed => &self.diff_changed_rect,
+            ChangeKind::Moved => &self.diff_moved_rect,
+        }
+    }
 }
 
 impl Default for ColorScheme {
@@ -114,6 +213,15 @@ impl Default for ColorScheme {
                 stroke: String::from("rgb(30,30,30)"),
                 fill: String::from("rgb(30,30,30)"),
             },
+            connection_mismatch_line: ColorScheme::default_connection_mismatch_line(),
+            connection_drag_line: ColorScheme::default_connection_drag_line(),
+            connection_snapped_line: ColorScheme::default_connection_snapped_line(),
+            connection_rejected_line: ColorScheme::default_connection_rejected_line(),
+            focused_rect: ColorScheme::default_focused_rect(),
+            diff_added_rect: ColorScheme::default_diff_added_rect(),
+            diff_removed_rect: ColorScheme::default_diff_removed_rect(),
+            diff_changed_rect: ColorScheme::default_diff_changed_rect(),
+            diff_moved_rect: ColorScheme::default_diff_moved_rect(),
             port_rect: RectColor {
                 stroke: String::from("rgb(50,50,50)"),
                 fill: String::from("rgb(240,240,240)"),
@@ -158,6 +266,7 @@ impl Default for ColorScheme {
                 stroke: String::from("rgb(0,0,0)"),
                 fill: String::from("rgb(0,0,0)"),
             },
+            dimmed_alpha: ColorScheme::default_dimmed_alpha(),
         }
     }
 }
@@ -187,7 +296,10 @@ impl Default for Options {
                 grouping: true,
                 group_unbound: true,
             },
-            connections: Connections { hide: false },
+            connections: Connections {
+                hide: false,
+                snap_distance: Connections::default_snap_distance(),
+            },
             grid: GridOptions {
                 vpadding: 3,
                 hpadding: 5,
@@ -0,0 +1,274 @@
+use super::utf16_offsets;
+use crate::{
+    analysis::diff::EntityKind,
+    entity::{Composition, Ports, Signature},
+};
+use serde::Serialize;
+use std::cmp::Ordering;
+
+const SCORE_MATCH: i32 = 16;
+// Match at start of word (after separator or on change of case)
+const BONUS_BOUNDARY: i32 = 8;
+// Match right after previous matched char
+const BONUS_CONSECUTIVE: i32 = 8;
+// Penalty for each skipped char between matched chars
+const PENALTY_GAP: i32 = 1;
+// Max penalty for skipped chars before first matched char
+const PENALTY_LEADING_MAX: i32 = 3;
+
+#[derive(Debug, PartialEq)]
+pub struct FuzzyMatch {
+    pub score: i32,
+    // Ranges [start, end) of matched chars; offsets in UTF-16 code units (as indexes of
+    // JS strings)
+    pub ranges: Vec<(usize, usize)>,
+}
+
+fn is_separator(c: char) -> bool {
+    c.is_whitespace() || matches!(c, '_' | '-' | '.' | '/' | ':')
+}
+
+fn boundary_bonus(prev: Option<char>, c: char) -> i32 {
+    match prev {
+        None => BONUS_BOUNDARY,
+        Some(prev) if is_separator(prev) => BONUS_BOUNDARY,
+        Some(prev) if prev.is_lowercase() && c.is_uppercase() => BONUS_BOUNDARY,
+        Some(prev) if !prev.is_numeric() && c.is_numeric() => BONUS_BOUNDARY / 2,
+        _ => 0,
+    }
+}
+
+/// Checks whether all chars of pattern are present in text in same order (case-insensitive)
+/// and finds alignment with best score. Whitespaces of pattern are ignored.
+pub fn fuzzy_match(pattern: &str, text: &str) -> Option<FuzzyMatch> {
+    let pattern: Vec<char> = pattern
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect();
+    let origin: Vec<char> = text.chars().collect();
+    // Lowercasing could change length of text; such chars are compared as is
+    let lowered: Vec<char> = origin
+        .iter()
+        .map(|c| {
+            let mut lower = c.to_lowercase();
+            match (lower.next(), lower.next()) {
+                (Some(l), None) => l,
+                _ => *c,
+            }
+        })
+        .collect();
+    let (n, m) = (pattern.len(), lowered.len());
+    if n == 0 || n > m {
+        return None;
+    }
+    // Quick check of subsequence
+    let mut chars = lowered.iter();
+    if !pattern.iter().all(|p| chars.any(|c| c == p)) {
+        return None;
+    }
+    let bonus: Vec<i32> = (0..m)
+        .map(|j| boundary_bonus(if j == 0 { None } else { Some(origin[j - 1]) }, origin[j]))
+        .collect();
+    // scores[i][j] - best score of matching pattern[..=i], where pattern[i] is matched
+    // with text[j]; from[i][j] - position of match of pattern[i - 1]
+    let mut scores: Vec<Vec<Option<i32>>> = vec![vec![None; m]; n];
+    let mut from: Vec<Vec<usize>> = vec![vec![0; m]; n];
+    for j in 0..m {
+        if lowered[j] == pattern[0] {
+            scores[0][j] = Some(
+                SCORE_MATCH + bonus[j] * 2 - (j as i32 * PENALTY_GAP).min(PENALTY_LEADING_MAX),
+            );
+        }
+    }
+    for i in 1..n {
+        // Best (score + PENALTY_GAP * k, k) of previous row for k < j - 1
+        let mut gapped: Option<(i32, usize)> = None;
+        for j in i..m {
+            if j >= 2 {
+                if let Some(score) = scores[i - 1][j - 2] {
+                    let value = score + PENALTY_GAP * (j - 2) as i32;
+                    if gapped.is_none_or(|(best, _)| value > best) {
+                        gapped = Some((value, j - 2));
+                    }
+                }
+            }
+            if lowered[j] != pattern[i] {
+                continue;
+            }
+            let consecutive = scores[i - 1][j - 1].map(|score| (score + BONUS_CONSECUTIVE, j - 1));
+            let gap = gapped.map(|(value, k)| (value - PENALTY_GAP * (j - 1) as i32, k));
+            let best = match (consecutive, gap) {
+                (Some(a), Some(b)) => Some(if a.0 >= b.0 { a } else { b }),
+                (a, b) => a.or(b),
+            };
+            if let Some((score, k)) = best {
+                scores[i][j] = Some(score + SCORE_MATCH + bonus[j]);
+                from[i][j] = k;
+            }
+        }
+    }
+    let (mut j, score) = scores[n - 1]
+        .iter()
+        .enumerate()
+        .filter_map(|(j, score)| score.map(|score| (j, score)))
+        .max_by(|(ja, a), (jb, b)| a.cmp(b).then(jb.cmp(ja)))?;
+    let mut positions = vec![j; n];
+    for i in (1..n).rev() {
+        j = from[i][j];
+        positions[i - 1] = j;
+    }
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    positions
+        .into_iter()
+        .for_each(|pos| match ranges.last_mut() {
+            Some((_, end)) if *end == pos => *end += 1,
+            _ => ranges.push((pos, pos + 1)),
+        });
+    let offsets = utf16_offsets(text);
+    Some(FuzzyMatch {
+        score,
+        ranges: ranges
+            .into_iter()
+            .map(|(start, end)| (offsets[start], offsets[end]))
+            .collect(),
+    })
+}
+
+/// Found entity
+#[derive(Debug, Serialize)]
+pub struct Found {
+    pub id: usize,
+    pub entity: EntityKind,
+    pub name: String,
+    pub score: i32,
+    // Ranges [start, end) of matched chars of name (UTF-16 offsets)
+    pub ranges: Vec<(usize, usize)>,
+    // Owner of port: (id, name)
+    pub owner: Option<(usize, String)>,
+    // Grouped port, which includes port: (id, name)
+    pub holder: Option<(usize, String)>,
+}
+
+fn search_in_ports(pattern: &str, ports: &Ports, owner: &Signature, found: &mut Vec<Found>) {
+    let mut push = |sig: &Signature, holder: Option<&Signature>| {
+        if let Some(matched) = fuzzy_match(pattern, &sig.short_name) {
+            found.push(Found {
+                id: sig.id,
+                entity: EntityKind::Port,
+                name: sig.short_name.clone(),
+                score: matched.score,
+                ranges: matched.ranges,
+                owner: Some((owner.id, owner.short_name.clone())),
+                holder: holder.map(|holder| (holder.id, holder.short_name.clone())),
+            });
+        }
+    };
+    ports.iter().for_each(|port| {
+        let origin = port.origin();
+        if !origin.visibility {
+            return;
+        }
+        if origin.contains.is_empty() {
+            push(&origin.sig, None);
+        } else {
+            origin.contains.iter().for_each(|id| {
+                if let Some(inner) = ports.find(id) {
+                    push(&inner.origin().sig, Some(&origin.sig));
+                }
+            });
+        }
+    });
+}
+
+/// Looks for components, compositions and ports with names matching pattern. Results are
+/// sorted by score (best first).
+pub fn search(composition: &Composition, pattern: &str, limit: Option<usize>) -> Vec<Found> {
+    let mut found: Vec<Found> = Vec::new();
+    let push = |sig: &Signature, entity: EntityKind, found: &mut Vec<Found>| {
+        if let Some(matched) = fuzzy_match(pattern, &sig.short_name) {
+            found.push(Found {
+                id: sig.id,
+                entity,
+                name: sig.short_name.clone(),
+                score: matched.score,
+                ranges: matched.ranges,
+                owner: None,
+                holder: None,
+            });
+        }
+    };
+    composition
+        .components
+        .iter()
+        .filter(|c| !c.origin().composition)
+        .for_each(|c| {
+            push(&c.origin().sig, EntityKind::Component, &mut found);
+            search_in_ports(
+                pattern,
+                c.origin().ports.origin(),
+                &c.origin().sig,
+                &mut found,
+            );
+        });
+    composition.compositions.iter().for_each(|c| {
+        push(&c.origin().sig, EntityKind::Composition, &mut found);
+        search_in_ports(
+            pattern,
+            c.origin().ports.origin(),
+            &c.origin().sig,
+            &mut found,
+        );
+    });
+    search_in_ports(
+        pattern,
+        composition.ports.origin(),
+        &composition.sig,
+        &mut found,
+    );
+    found.sort_by(|a, b| match b.score.cmp(&a.score) {
+        Ordering::Equal => a.name.len().cmp(&b.name.len()).then(a.id.cmp(&b.id)),
+        ordering => ordering,
+    });
+    if let Some(limit) = limit {
+        found.truncate(limit);
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ranges(pattern: &str, text: &str) -> Option<Vec<(usize, usize)>> {
+        fuzzy_match(pattern, text).map(|matched| matched.ranges)
+    }
+
+    #[test]
+    fn matches_subsequence() {
+        assert_eq!(ranges("dl", "DoorLock"), Some(vec![(0, 1), (4, 5)]));
+        assert_eq!(ranges("door lock", "DoorLock"), Some(vec![(0, 8)]));
+        assert_eq!(ranges("ld", "DoorLock"), None);
+        assert_eq!(ranges(" ", "DoorLock"), None);
+        assert_eq!(ranges("doorlocks", "DoorLock"), None);
+    }
+
+    #[test]
+    fn prefers_boundaries() {
+        let boundary = fuzzy_match("dl", "DoorLock").unwrap();
+        let inner = fuzzy_match("dl", "Idle").unwrap();
+        assert!(boundary.score > inner.score);
+        // "д" of first word is skipped in favour of start of second word
+        assert_eq!(ranges("дв", "Передняя дверь"), Some(vec![(9, 11)]));
+    }
+
+    #[test]
+    fn reports_ranges_in_utf16() {
+        // Emoji takes two UTF-16 code units
+        assert_eq!(ranges("lock", "🔒 Lock"), Some(vec![(3, 7)]));
+        assert_eq!(ranges("ll", "🔒Lock🔒Lamp"), Some(vec![(2, 3), (8, 9)]));
+        // Lowercase of "İ" is two chars, so it's compared as is and doesn't shift ranges
+        assert_eq!(ranges("stan", "İstanbul"), Some(vec![(1, 5)]));
+        assert_eq!(ranges("İs", "İstanbul"), None);
+    }
+}
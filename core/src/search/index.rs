@@ -0,0 +1,272 @@
+use crate::entity::{Composition, Connection, Ports, Signature};
+use std::collections::{HashMap, HashSet};
+
+type Trigram = [char; 3];
+// (in port, out port, in component, out component)
+type Link = (usize, usize, usize, usize);
+
+/// Text of entity with normalized (lowercased) copy
+#[derive(Debug)]
+pub struct Name {
+    pub origin: String,
+    pub lowered: String,
+}
+
+impl Name {
+    fn new(origin: &str) -> Self {
+        Self {
+            origin: origin.to_owned(),
+            lowered: origin.to_lowercase(),
+        }
+    }
+}
+
+/// Index of names and relations of entities of composition (top level only). Index is
+/// built on binding of model and is updated on changes of model, so queries don't walk
+/// whole model and don't normalize names on each check.
+#[derive(Debug, Default)]
+pub struct Index {
+    // Short names of components, compositions and ports
+    names: HashMap<usize, Name>,
+    // Class names of components, compositions and ports
+    classes: HashMap<usize, Name>,
+    // Owner (component or composition) of port
+    owners: HashMap<usize, usize>,
+    // Ports of owner
+    ports: HashMap<usize, Vec<usize>>,
+    connections: HashMap<usize, Link>,
+    // Connected ports
+    links: HashMap<usize, Vec<usize>>,
+    // Connected components
+    neighbours: HashMap<usize, Vec<usize>>,
+    // Trigrams of lowercased short names
+    trigrams: HashMap<Trigram, HashSet<usize>>,
+}
+
+fn get_trigrams(lowered: &str) -> Vec<Trigram> {
+    let chars: Vec<char> = lowered.chars().collect();
+    let mut trigrams: Vec<Trigram> = chars.windows(3).map(|w| [w[0], w[1], w[2]]).collect();
+    trigrams.sort_unstable();
+    trigrams.dedup();
+    trigrams
+}
+
+/// Returns owners of ports of top level: composition itself, components and compositions
+fn get_owners(composition: &Composition) -> Vec<(&Signature, &Ports)> {
+    let mut owners = vec![(&composition.sig, composition.ports.origin())];
+    composition
+        .components
+        .iter()
+        .filter(|c| !c.origin().composition)
+        .for_each(|c| owners.push((&c.origin().sig, c.origin().ports.origin())));
+    composition
+        .compositions
+        .iter()
+        .for_each(|c| owners.push((&c.origin().sig, c.origin().ports.origin())));
+    owners
+}
+
+fn detach(map: &mut HashMap<usize, Vec<usize>>, a: usize, b: usize) {
+    for (from, to) in [(a, b), (b, a)] {
+        if let Some(linked) = map.get_mut(&from) {
+            if let Some(pos) = linked.iter().position(|id| id == &to) {
+                linked.swap_remove(pos);
+            }
+            if linked.is_empty() {
+                map.remove(&from);
+            }
+        }
+    }
+}
+
+impl Index {
+    pub fn new(composition: &Composition) -> Self {
+        let mut index = Self::default();
+        get_owners(composition)
+            .into_iter()
+            .for_each(|(sig, ports)| index.add_owner(sig, ports));
+        composition
+            .connections
+            .iter()
+            .for_each(|c| index.link(c.origin()));
+        index
+    }
+
+    /// Updates index after change of model. `changed` - ids of added, removed or changed
+    /// entities (components, compositions, ports, connections). Components linked to
+    /// changed ones are reindexed as well, because render regroups their ports.
+    pub fn update(&mut self, composition: &Composition, changed: &[usize]) {
+        let mut affected: HashSet<usize> = HashSet::new();
+        changed.iter().for_each(|id| {
+            if self.ports.contains_key(id) {
+                affected.insert(*id);
+            } else if let Some(owner) = self.owners.get(id) {
+                affected.insert(*owner);
+            } else if let Some((_, _, in_comp, out_comp)) = self.connections.get(id) {
+                affected.extend([*in_comp, *out_comp]);
+            } else {
+                // Added entity
+                affected.insert(*id);
+            }
+        });
+        let extend = |index: &Self, affected: &mut HashSet<usize>| {
+            let linked = affected
+                .iter()
+                .flat_map(|id| index.neighbours(id).iter().copied())
+                .collect::<Vec<usize>>();
+            affected.extend(linked);
+        };
+        extend(self, &mut affected);
+        // Connections are synchronized by ids, because grouping of ports replaces
+        // connections without notice
+        let current = composition
+            .connections
+            .iter()
+            .map(|c| (c.origin().sig.id, c.origin()))
+            .collect::<HashMap<usize, &Connection>>();
+        let removed = self
+            .connections
+            .keys()
+            .filter(|id| !current.contains_key(id))
+            .copied()
+            .collect::<Vec<usize>>();
+        removed.iter().for_each(|id| {
+            if let Some((_, _, in_comp, out_comp)) = self.unlink(id) {
+                affected.extend([in_comp, out_comp]);
+            }
+        });
+        current.iter().for_each(|(id, connection)| {
+            if !self.connections.contains_key(id) {
+                self.link(connection);
+                affected.extend([*connection.in_comp(), *connection.out_comp()]);
+            }
+        });
+        extend(self, &mut affected);
+        let owners = get_owners(composition)
+            .into_iter()
+            .map(|(sig, ports)| (sig.id, (sig, ports)))
+            .collect::<HashMap<usize, (&Signature, &Ports)>>();
+        affected.iter().for_each(|id| {
+            self.remove_owner(id);
+            if let Some((sig, ports)) = owners.get(id) {
+                self.add_owner(sig, ports);
+            }
+        });
+    }
+
+    pub fn name(&self, id: &usize) -> Option<&Name> {
+        self.names.get(id)
+    }
+
+    pub fn class(&self, id: &usize) -> Option<&Name> {
+        self.classes.get(id)
+    }
+
+    pub fn owner(&self, port: &usize) -> Option<usize> {
+        self.owners.get(port).copied()
+    }
+
+    /// Returns ports connected to given port
+    pub fn links(&self, port: &usize) -> &[usize] {
+        self.links.get(port).map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns components connected to given component
+    pub fn neighbours(&self, component: &usize) -> &[usize] {
+        self.neighbours.get(component).map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns ids of entities, which short names could contain given lowercased text.
+    /// Returns None if text is too short to be looked up by index.
+    pub fn find(&self, text: &str) -> Option<HashSet<usize>> {
+        let trigrams = get_trigrams(text);
+        if trigrams.is_empty() {
+            return None;
+        }
+        let Some(mut sets) = trigrams
+            .iter()
+            .map(|trigram| self.trigrams.get(trigram))
+            .collect::<Option<Vec<&HashSet<usize>>>>()
+        else {
+            return Some(HashSet::new());
+        };
+        sets.sort_by_key(|set| set.len());
+        let (smallest, rest) = sets.split_first()?;
+        Some(
+            smallest
+                .iter()
+                .filter(|id| rest.iter().all(|set| set.contains(id)))
+                .copied()
+                .collect(),
+        )
+    }
+
+    fn add_entity(&mut self, sig: &Signature) {
+        let name = Name::new(&sig.short_name);
+        get_trigrams(&name.lowered).into_iter().for_each(|trigram| {
+            self.trigrams.entry(trigram).or_default().insert(sig.id);
+        });
+        self.names.insert(sig.id, name);
+        self.classes.insert(sig.id, Name::new(&sig.class_name));
+    }
+
+    fn remove_entity(&mut self, id: &usize) {
+        if let Some(name) = self.names.remove(id) {
+            get_trigrams(&name.lowered).into_iter().for_each(|trigram| {
+                if let Some(ids) = self.trigrams.get_mut(&trigram) {
+                    ids.remove(id);
+                    if ids.is_empty() {
+                        self.trigrams.remove(&trigram);
+                    }
+                }
+            });
+        }
+        self.classes.remove(id);
+    }
+
+    fn add_owner(&mut self, sig: &Signature, ports: &Ports) {
+        self.add_entity(sig);
+        let ids = ports
+            .iter()
+            .map(|port| {
+                self.add_entity(port.sig());
+                self.owners.insert(port.sig().id, sig.id);
+                port.sig().id
+            })
+            .collect::<Vec<usize>>();
+        self.ports.insert(sig.id, ids);
+    }
+
+    fn remove_owner(&mut self, id: &usize) {
+        if let Some(ports) = self.ports.remove(id) {
+            ports.iter().for_each(|port| {
+                self.remove_entity(port);
+                self.owners.remove(port);
+            });
+            self.remove_entity(id);
+        }
+    }
+
+    fn link(&mut self, connection: &Connection) {
+        let link = (
+            *connection.in_port(),
+            *connection.out_port(),
+            *connection.in_comp(),
+            *connection.out_comp(),
+        );
+        let attach = |map: &mut HashMap<usize, Vec<usize>>, a: usize, b: usize| {
+            map.entry(a).or_default().push(b);
+            map.entry(b).or_default().push(a);
+        };
+        attach(&mut self.links, link.0, link.1);
+        attach(&mut self.neighbours, link.2, link.3);
+        self.connections.insert(connection.sig.id, link);
+    }
+
+    fn unlink(&mut self, id: &usize) -> Option<Link> {
+        let link = self.connections.remove(id)?;
+        detach(&mut self.links, link.0, link.1);
+        detach(&mut self.neighbours, link.2, link.3);
+        Some(link)
+    }
+}
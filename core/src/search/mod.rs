@@ -0,0 +1,15 @@
+pub mod fuzzy;
+pub mod index;
+pub mod query;
+
+/// Returns offsets (in UTF-16 code units, as indexes of JS strings) of each char of text;
+/// the last item is length of whole text
+pub fn utf16_offsets(text: &str) -> Vec<usize> {
+    let mut offset = 0;
+    let mut offsets = vec![0];
+    offsets.extend(text.chars().map(|c| {
+        offset += c.len_utf16();
+        offset
+    }));
+    offsets
+}
@@ -0,0 +1,684 @@
+use super::{
+    index::{Index, Name},
+    utf16_offsets,
+};
+use crate::entity::{Port, PortDirection, PortType, Ports, Signature};
+use regex_lite::Regex;
+use serde::Serialize;
+use std::{collections::HashSet, fmt};
+
+// Query syntax:
+//   query   := or
+//   or      := and ("or" and)*
+//   and     := not ("and"? not)*
+//   not     := ("not" | "!") not | primary
+//   primary := "(" or ")" | [field ":"] value
+//   value   := word | "quoted text" | /regex/
+// Field is one of named selectors (see `Field::from_key`) or key of attribute of port
+// (see `ATTRIBUTES`). Words with "*" or "?" are globs and should match whole value;
+// other words and quoted text match any part of value. Matching is case-insensitive.
+// Plain text (only words without fields, operators, parentheses and globs) is matched
+// as one phrase with any part of name, like filters without query language; words are
+// matched separately if they're joined by operator ("door and lock").
+
+// Keys of attributes of port (as they're named in model), which can be used as field
+// selectors. Enums are matched by names they're serialized with.
+const ATTRIBUTES: &[&str] = &[
+    "short_name",
+    "class_name",
+    "port_type",
+    "visibility",
+    "interface_kind",
+    "provided_interface",
+    "required_interface",
+    "provided_required_interface",
+];
+
+/// Error of query parsing. Positions are offsets in UTF-16 code units (as indexes of JS
+/// strings)
+#[derive(Debug, Clone, Serialize)]
+pub struct ParseError {
+    pub message: String,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl ParseError {
+    fn new(message: impl Into<String>, start: usize, end: usize) -> Self {
+        Self {
+            message: message.into(),
+            start,
+            end,
+        }
+    }
+
+    /// Converts positions from indexes of chars into UTF-16 offsets
+    fn into_utf16(self, offsets: &[usize]) -> Self {
+        let offset = |pos: usize| offsets[pos.min(offsets.len() - 1)];
+        Self {
+            start: offset(self.start),
+            end: offset(self.end),
+            message: self.message,
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (at {}..{})", self.message, self.start, self.end)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Field {
+    // Short name of entity
+    Name,
+    // Class name of entity
+    Class,
+    // Class or short name of interface of port
+    Interface,
+    // Short name of owner of port
+    Component,
+    Id,
+    // provided, required, provided-required
+    Direction,
+    // Kind of interface of port
+    Kind,
+    // Side of port: left, right
+    Side,
+    // Entity has connection to entity with matching name
+    LinkedTo,
+    // Attribute of port by key (see `ATTRIBUTES`)
+    Attribute(&'static str),
+}
+
+impl Field {
+    fn from_key(key: &str) -> Option<Self> {
+        Some(match key.to_lowercase().as_str() {
+            "name" => Self::Name,
+            "class" => Self::Class,
+            "interface" => Self::Interface,
+            "component" => Self::Component,
+            "id" => Self::Id,
+            "direction" => Self::Direction,
+            "kind" => Self::Kind,
+            "side" => Self::Side,
+            "linked-to" => Self::LinkedTo,
+            key => {
+                return ATTRIBUTES
+                    .iter()
+                    .find(|attr| **attr == key)
+                    .map(|attr| Self::Attribute(attr))
+            }
+        })
+    }
+}
+
+#[derive(Debug)]
+pub enum Matcher {
+    // Lowercased text
+    Contains(String),
+    Pattern(Regex),
+}
+
+impl Matcher {
+    pub fn is_match(&self, value: &str) -> bool {
+        match self {
+            Self::Contains(text) => value.to_lowercase().contains(text),
+            Self::Pattern(re) => re.is_match(value),
+        }
+    }
+
+    /// Same as `is_match`, but uses normalized value from index if it's actual
+    pub fn is_match_indexed(&self, value: &str, indexed: Option<&Name>) -> bool {
+        match (self, indexed) {
+            (Self::Contains(text), Some(name)) if name.origin == value => {
+                name.lowered.contains(text)
+            }
+            _ => self.is_match(value),
+        }
+    }
+
+    /// Same as `is_match`, but text should be equal to whole value
+    pub fn is_equal(&self, value: &str) -> bool {
+        match self {
+            Self::Contains(text) => &value.to_lowercase() == text,
+            Self::Pattern(re) => re.is_match(value),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Expr {
+    // Empty query; matches everything
+    All,
+    Term(Field, Matcher),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Open,
+    Close,
+    And,
+    Or,
+    Not,
+    // (field, value)
+    Term(Option<String>, Value),
+}
+
+#[derive(Debug, PartialEq)]
+enum Value {
+    Word(String),
+    Quoted(String),
+    Regex(String),
+}
+
+// (token, start, end)
+type Spanned = (Token, usize, usize);
+
+fn is_word_char(c: char) -> bool {
+    !c.is_whitespace() && !matches!(c, '(' | ')' | '"')
+}
+
+fn is_key_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '-' || c == '_'
+}
+
+fn tokenize(input: &str) -> Result<Vec<Spanned>, ParseError> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens: Vec<Spanned> = Vec::new();
+    let mut pos = 0;
+    // Reads value till closing char; returns position after closing char
+    let closed = |from: usize, close: char| -> Option<(String, usize)> {
+        let mut value = String::new();
+        let mut i = from;
+        while i < chars.len() {
+            if chars[i] == '\\' && i + 1 < chars.len() && chars[i + 1] == close {
+                value.push(close);
+                i += 2;
+                continue;
+            }
+            if chars[i] == close {
+                return Some((value, i + 1));
+            }
+            value.push(chars[i]);
+            i += 1;
+        }
+        None
+    };
+    while pos < chars.len() {
+        let c = chars[pos];
+        if c.is_whitespace() {
+            pos += 1;
+            continue;
+        }
+        let start = pos;
+        if c == '(' || c == ')' {
+            tokens.push((
+                if c == '(' { Token::Open } else { Token::Close },
+                start,
+                start + 1,
+            ));
+            pos += 1;
+            continue;
+        }
+        if c == '!' {
+            tokens.push((Token::Not, start, start + 1));
+            pos += 1;
+            continue;
+        }
+        // Field selector
+        let mut key_end = pos;
+        while key_end < chars.len() && is_key_char(chars[key_end]) {
+            key_end += 1;
+        }
+        let field = if key_end > pos && key_end < chars.len() && chars[key_end] == ':' {
+            let key: String = chars[pos..key_end].iter().collect();
+            pos = key_end + 1;
+            Some(key)
+        } else {
+            None
+        };
+        let value = match chars.get(pos) {
+            Some('"') => {
+                let (text, end) = closed(pos + 1, '"').ok_or(ParseError::new(
+                    "Missing closing quote",
+                    pos,
+                    chars.len(),
+                ))?;
+                pos = end;
+                Value::Quoted(text)
+            }
+            Some('/') => {
+                let (text, end) = closed(pos + 1, '/').ok_or(ParseError::new(
+                    "Missing closing slash of regular expression",
+                    pos,
+                    chars.len(),
+                ))?;
+                pos = end;
+                Value::Regex(text)
+            }
+            _ => {
+                let from = pos;
+                while pos < chars.len() && is_word_char(chars[pos]) {
+                    pos += 1;
+                }
+                if from == pos {
+                    return Err(ParseError::new("Missing value", start, pos.max(start + 1)));
+                }
+                Value::Word(chars[from..pos].iter().collect())
+            }
+        };
+        let token = match (&field, &value) {
+            (None, Value::Word(word)) => match word.to_lowercase().as_str() {
+                "and" => Token::And,
+                "or" => Token::Or,
+                "not" => Token::Not,
+                _ => Token::Term(field, value),
+            },
+            _ => Token::Term(field, value),
+        };
+        tokens.push((token, start, pos));
+    }
+    Ok(tokens)
+}
+
+fn glob_to_regex(glob: &str) -> String {
+    let mut re = String::from("(?i)^");
+    glob.chars().for_each(|c| match c {
+        '*' => re.push_str(".*"),
+        '?' => re.push('.'),
+        c => re.push_str(&regex_lite::escape(&c.to_string())),
+    });
+    re.push('$');
+    re
+}
+
+struct Parser {
+    tokens: Vec<Spanned>,
+    pos: usize,
+    len: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(token, _, _)| token)
+    }
+
+    fn span(&self) -> (usize, usize) {
+        self.tokens
+            .get(self.pos)
+            .map(|(_, start, end)| (*start, *end))
+            .unwrap_or((self.len, self.len))
+    }
+
+    fn or(&mut self) -> Result<Expr, ParseError> {
+        let mut left = self.and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.pos += 1;
+            left = Expr::Or(Box::new(left), Box::new(self.and()?));
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<Expr, ParseError> {
+        let mut left = self.not()?;
+        loop {
+            match self.peek() {
+                Some(Token::And) => {
+                    self.pos += 1;
+                }
+                Some(Token::Not | Token::Open | Token::Term(..)) => {}
+                _ => break,
+            }
+            left = Expr::And(Box::new(left), Box::new(self.not()?));
+        }
+        Ok(left)
+    }
+
+    fn not(&mut self) -> Result<Expr, ParseError> {
+        if self.peek() == Some(&Token::Not) {
+            self.pos += 1;
+            return Ok(Expr::Not(Box::new(self.not()?)));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Expr, ParseError> {
+        let (start, end) = self.span();
+        let Some((token, _, _)) = self.tokens.get_mut(self.pos) else {
+            return Err(ParseError::new("Unexpected end of query", start, end));
+        };
+        match std::mem::replace(token, Token::Close) {
+            Token::Open => {
+                self.pos += 1;
+                let expr = self.or()?;
+                if self.peek() != Some(&Token::Close) {
+                    return Err(ParseError::new(
+                        "Missing closing parenthesis",
+                        start,
+                        self.span().1,
+                    ));
+                }
+                self.pos += 1;
+                Ok(expr)
+            }
+            Token::Term(field, value) => {
+                self.pos += 1;
+                term(field, value, start, end)
+            }
+            Token::Close => Err(ParseError::new("Unexpected \")\"", start, end)),
+            Token::And | Token::Or | Token::Not => {
+                Err(ParseError::new("Operator without operand", start, end))
+            }
+        }
+    }
+}
+
+fn term(field: Option<String>, value: Value, start: usize, end: usize) -> Result<Expr, ParseError> {
+    let field = match field {
+        Some(key) => Field::from_key(&key).ok_or(ParseError::new(
+            format!("Unknown field \"{key}\""),
+            start,
+            start + key.chars().count(),
+        ))?,
+        None => Field::Name,
+    };
+    let matcher = match value {
+        Value::Word(word) if word.contains(['*', '?']) => Matcher::Pattern(
+            Regex::new(&glob_to_regex(&word))
+                .map_err(|e| ParseError::new(e.to_string(), start, end))?,
+        ),
+        Value::Word(text) | Value::Quoted(text) => Matcher::Contains(text.to_lowercase()),
+        Value::Regex(re) => Matcher::Pattern(Regex::new(&format!("(?i){re}")).map_err(|e| {
+            ParseError::new(format!("Invalid regular expression: {e}"), start, end)
+        })?),
+    };
+    Ok(Expr::Term(field, matcher))
+}
+
+/// Parsed query
+#[derive(Debug)]
+pub struct Query {
+    expr: Expr,
+}
+
+impl Query {
+    pub fn parse(input: &str) -> Result<Self, ParseError> {
+        Self::parse_expr(input)
+            .map(|expr| Self { expr })
+            .map_err(|err| err.into_utf16(&utf16_offsets(input)))
+    }
+
+    /// Parses query; invalid query is matched as plain text with names of entities (the
+    /// same way as filters without query language), so any text can be used as filter
+    pub fn parse_or_text(input: &str) -> Self {
+        Self::parse(input).unwrap_or_else(|_| Self {
+            expr: Expr::Term(Field::Name, Matcher::Contains(input.to_lowercase())),
+        })
+    }
+
+    fn parse_expr(input: &str) -> Result<Expr, ParseError> {
+        let tokens = tokenize(input)?;
+        if tokens.is_empty() {
+            return Ok(Expr::All);
+        }
+        let plain = tokens.iter().all(|(token, _, _)| {
+            matches!(token, Token::Term(None, Value::Word(word)) if !word.contains(['*', '?']))
+        });
+        if plain {
+            return Ok(Expr::Term(
+                Field::Name,
+                Matcher::Contains(input.to_lowercase()),
+            ));
+        }
+        let mut parser = Parser {
+            tokens,
+            pos: 0,
+            len: input.chars().count(),
+        };
+        let expr = parser.or()?;
+        if parser.pos < parser.tokens.len() {
+            let (start, end) = parser.span();
+            return Err(ParseError::new("Unexpected token", start, end));
+        }
+        Ok(expr)
+    }
+
+    /// Returns ids of entities (ports, components, compositions), which could be matched
+    /// by query. Ids are found by index of names; returns None if query cannot be
+    /// narrowed by index and all entities should be checked.
+    pub fn get_candidates(&self, index: &Index) -> Option<HashSet<usize>> {
+        fn find(expr: &Expr, index: &Index) -> Option<HashSet<usize>> {
+            match expr {
+                Expr::Term(Field::Name, Matcher::Contains(text)) => index.find(text),
+                Expr::And(a, b) => match (find(a, index), find(b, index)) {
+                    (Some(a), Some(b)) => Some(a.intersection(&b).copied().collect()),
+                    (a, b) => a.or(b),
+                },
+                Expr::Or(a, b) => match (find(a, index), find(b, index)) {
+                    (Some(a), Some(b)) => Some(a.union(&b).copied().collect()),
+                    _ => None,
+                },
+                _ => None,
+            }
+        }
+        find(&self.expr, index)
+    }
+
+    pub fn is_port_matched(&self, port: &Port, index: &Index) -> bool {
+        fn check(expr: &Expr, port: &Port, index: &Index) -> bool {
+            match expr {
+                Expr::All => true,
+                Expr::Not(expr) => !check(expr, port, index),
+                Expr::And(a, b) => check(a, port, index) && check(b, port, index),
+                Expr::Or(a, b) => check(a, port, index) || check(b, port, index),
+                Expr::Term(Field::Component, matcher) => index
+                    .owner(&port.sig.id)
+                    .and_then(|owner| index.name(&owner))
+                    .is_some_and(|owner| matcher.is_match_indexed(&owner.origin, Some(owner))),
+                Expr::Term(Field::LinkedTo, matcher) => {
+                    index.links(&port.sig.id).iter().any(|id| {
+                        [Some(*id), index.owner(id)]
+                            .into_iter()
+                            .flatten()
+                            .filter_map(|id| index.name(&id))
+                            .any(|name| matcher.is_match_indexed(&name.origin, Some(name)))
+                    })
+                }
+                Expr::Term(field, matcher) => is_port_field_matched(port, *field, matcher, index),
+            }
+        }
+        check(&self.expr, port, index)
+    }
+
+    /// Checks component or composition. Port related fields (interface, direction, etc.)
+    /// are matched if any port of component is matched.
+    pub fn is_component_matched(&self, sig: &Signature, ports: &Ports, index: &Index) -> bool {
+        fn check(expr: &Expr, sig: &Signature, ports: &Ports, index: &Index) -> bool {
+            match expr {
+                Expr::All => true,
+                Expr::Not(expr) => !check(expr, sig, ports, index),
+                Expr::And(a, b) => check(a, sig, ports, index) && check(b, sig, ports, index),
+                Expr::Or(a, b) => check(a, sig, ports, index) || check(b, sig, ports, index),
+                Expr::Term(
+                    Field::Name | Field::Component | Field::Attribute("short_name"),
+                    matcher,
+                ) => matcher.is_match_indexed(&sig.short_name, index.name(&sig.id)),
+                Expr::Term(Field::Class | Field::Attribute("class_name"), matcher) => {
+                    matcher.is_match_indexed(&sig.class_name, index.class(&sig.id))
+                }
+                Expr::Term(Field::Id, matcher) => matcher.is_equal(&sig.id.to_string()),
+                Expr::Term(Field::LinkedTo, matcher) => {
+                    index.neighbours(&sig.id).iter().any(|id| {
+                        index
+                            .name(id)
+                            .is_some_and(|name| matcher.is_match_indexed(&name.origin, Some(name)))
+                    })
+                }
+                Expr::Term(field, matcher) => ports
+                    .iter()
+                    .any(|port| is_port_field_matched(port.origin(), *field, matcher, index)),
+            }
+        }
+        check(&self.expr, sig, ports, index)
+    }
+}
+
+fn is_port_field_matched(port: &Port, field: Field, matcher: &Matcher, index: &Index) -> bool {
+    match field {
+        Field::Name => matcher.is_match_indexed(&port.sig.short_name, index.name(&port.sig.id)),
+        Field::Class => matcher.is_match_indexed(&port.sig.class_name, index.class(&port.sig.id)),
+        Field::Id => matcher.is_equal(&port.sig.id.to_string()),
+        Field::Interface => port.interface().is_some_and(|sig| {
+            matcher.is_match(&sig.class_name) || matcher.is_match(&sig.short_name)
+        }),
+        Field::Direction => port.direction().is_some_and(|direction| {
+            matcher.is_match(match direction {
+                PortDirection::Provided => "provided",
+                PortDirection::Required => "required",
+                PortDirection::ProvidedRequired => "provided-required",
+            })
+        }),
+        Field::Kind => port
+            .interface_kind
+            .as_ref()
+            .is_some_and(|kind| matcher.is_match(&serde_name(kind))),
+        Field::Side => matcher.is_match(match port.port_type {
+            PortType::Left => "left",
+            PortType::Right => "right",
+        }),
+        Field::Attribute(key) => get_port_attribute(port, key)
+            .iter()
+            .any(|value| matcher.is_match(value)),
+        // Relations are checked with index
+        Field::Component | Field::LinkedTo => false,
+    }
+}
+
+/// Returns name of unit variant of enum, which it's serialized with; unlike `Debug`
+/// this name is a part of model format and doesn't change
+fn serde_name<T: Serialize>(value: &T) -> String {
+    serde_json::to_value(value)
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_owned))
+        .unwrap_or_default()
+}
+
+/// Returns values of attribute of port by key (see `ATTRIBUTES`). Interfaces are matched
+/// by short and class names.
+fn get_port_attribute(port: &Port, key: &str) -> Vec<String> {
+    let interface = |sig: &Option<Signature>| {
+        sig.as_ref()
+            .map(|sig| vec![sig.short_name.clone(), sig.class_name.clone()])
+            .unwrap_or_default()
+    };
+    match key {
+        "short_name" => vec![port.sig.short_name.clone()],
+        "class_name" => vec![port.sig.class_name.clone()],
+        "port_type" => vec![serde_name(&port.port_type)],
+        "visibility" => vec![port.visibility.to_string()],
+        "interface_kind" => port.interface_kind.iter().map(serde_name).collect(),
+        "provided_interface" => interface(&port.provided_interface),
+        "required_interface" => interface(&port.required_interface),
+        "provided_required_interface" => interface(&port.provided_required_interface),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(input: &str) -> (String, usize, usize) {
+        let err = Query::parse(input).expect_err(input);
+        (err.message, err.start, err.end)
+    }
+
+    fn text(input: &str) -> Option<String> {
+        match Query::parse(input).unwrap().expr {
+            Expr::Term(Field::Name, Matcher::Contains(text)) => Some(text),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn reports_spans_of_errors() {
+        assert_eq!(error("a \"door"), ("Missing closing quote".into(), 2, 7));
+        assert_eq!(
+            error("name:/door"),
+            ("Missing closing slash of regular expression".into(), 5, 10)
+        );
+        assert_eq!(error("name:"), ("Missing value".into(), 0, 5));
+        assert_eq!(
+            error("bogus:door"),
+            ("Unknown field \"bogus\"".into(), 0, 5)
+        );
+        assert_eq!(
+            error("(door or lock"),
+            ("Missing closing parenthesis".into(), 0, 13)
+        );
+        assert_eq!(error("and door"), ("Operator without operand".into(), 0, 3));
+        assert_eq!(error("door and"), ("Unexpected end of query".into(), 8, 8));
+        assert_eq!(error("door )"), ("Unexpected token".into(), 5, 6));
+        let (message, start, end) = error("a /[/");
+        assert!(message.starts_with("Invalid regular expression"));
+        assert_eq!((start, end), (2, 5));
+    }
+
+    #[test]
+    fn reports_spans_in_utf16() {
+        // Emoji takes two UTF-16 code units; cyrillic letters take one
+        assert_eq!(
+            error("😀 or \"дверь"),
+            ("Missing closing quote".into(), 6, 12)
+        );
+        assert_eq!(
+            error("😀 or ключ:x"),
+            ("Unknown field \"ключ\"".into(), 6, 10)
+        );
+        assert_eq!(error("😀😀 and"), ("Unexpected end of query".into(), 8, 8));
+    }
+
+    #[test]
+    fn matches_plain_text_as_phrase() {
+        assert_eq!(text("Door Lock").as_deref(), Some("door lock"));
+        assert_eq!(text("door and lock"), None);
+        assert_eq!(text("class:door"), None);
+        assert_eq!(text("do*r"), None);
+        assert!(matches!(Query::parse("  ").unwrap().expr, Expr::All));
+        let Expr::Term(_, matcher) = Query::parse_or_text("(door").expr else {
+            panic!("invalid query isn't matched as text");
+        };
+        assert!(matcher.is_match("Front (Door)"));
+    }
+
+    #[test]
+    fn builds_expressions() {
+        let Expr::Or(a, b) = Query::parse("a b or not c").unwrap().expr else {
+            panic!("\"or\" should have lowest priority");
+        };
+        assert!(matches!(*a, Expr::And(..)));
+        assert!(matches!(*b, Expr::Not(..)));
+        let Expr::Term(_, matcher) = Query::parse("Door*").unwrap().expr else {
+            panic!("glob isn't parsed as term");
+        };
+        assert!(matcher.is_match("doorLock"));
+        assert!(!matcher.is_match("FrontDoor"));
+        let Expr::Term(_, matcher) = Query::parse("/^d.+r$/").unwrap().expr else {
+            panic!("regex isn't parsed as term");
+        };
+        assert!(matcher.is_match("Door"));
+        let Expr::Term(field, matcher) = Query::parse("short_name:\"a \\\" b\"").unwrap().expr
+        else {
+            panic!("quoted text isn't parsed as term");
+        };
+        assert!(field != Field::Name);
+        assert!(matcher.is_match("x A \" B"));
+    }
+}
@@ -0,0 +1,466 @@
+use crate::{
+    entity::{dummy::SignatureProducer, Composition},
+    error::{SnapshotError, E},
+    history::History,
+    render::{grid::ElementCoor, options::Options, Ratio, Relative},
+    state::State,
+    Active,
+};
+use bincode::Options as _;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+
+mod v0;
+mod v1;
+
+// Layout of snapshot:
+// MAGIC | version: u16 | payload length: u32 | payload checksum: u32 | payload
+// All numbers are little endian.
+const MAGIC: &[u8; 4] = b"BRDS";
+const HEADER_LEN: usize = MAGIC.len() + 2 + 4 + 4;
+// Value of "format" field of JSON snapshot
+const JSON_FORMAT: &str = "board-snapshot";
+
+/// Current version of snapshot format. Should be increased on each change of `Sections`
+/// or of structures stored in sections; structures of previous version should be frozen
+/// in own module (`v0`, `v1`, ...) and migrated in `migrate`.
+pub const VERSION: u16 = 2;
+
+/// Part of snapshot, which can be dropped on loading if it cannot be restored
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum Section {
+    Options,
+    State,
+    Render,
+    History,
+}
+
+/// Payload of snapshot. Each section is serialized separately, so change of internal
+/// structures (options, state, render) doesn't make whole snapshot unreadable. Model is
+/// stored as JSON without render entities and is used to restore render if render section
+/// is outdated. Empty section is treated as dropped.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct Sections {
+    pub model: Vec<u8>,
+    pub options: Vec<u8>,
+    pub state: Vec<u8>,
+    // (Active, Ratio, SignatureProducer)
+    pub render: Vec<u8>,
+    pub history: Option<Vec<u8>>,
+}
+
+/// Summary of loading of snapshot
+#[derive(Debug, Serialize)]
+pub struct Report {
+    pub version: u16,
+    // Sections, which cannot be restored and have been replaced by defaults
+    pub dropped: Vec<Section>,
+}
+
+/// Sections of snapshot restored with current structures
+pub struct Restored {
+    pub version: u16,
+    pub options: Option<Options>,
+    pub state: Option<State>,
+    // Restored render or model to calculate render from
+    pub render: Result<(Active, Ratio, SignatureProducer), Composition>,
+    // None - history isn't stored; Some(None) - history cannot be restored
+    pub history: Option<Option<History>>,
+}
+
+/// Calculated layout of board. It's included into JSON snapshot to inspect and compare
+/// layouts; it isn't used on loading.
+#[derive(Debug, Serialize)]
+pub struct Layout {
+    // Size of grid (in cells)
+    pub size: (u32, u32),
+    // Size of cell (in pixels)
+    pub cell: u32,
+    // Occupied cells (x, y, x1, y1) by elements
+    pub cells: Vec<(String, ElementCoor)>,
+    // Ports areas (x, y, x1, y1); in pixels
+    pub ports: Vec<(usize, (i32, i32, i32, i32))>,
+    // Points of connections; in pixels
+    pub connections: Vec<(usize, Vec<(i32, i32)>)>,
+}
+
+impl Layout {
+    pub fn new(active: &Active, state: &State, ratio: &Ratio) -> Result<Self, E> {
+        let mut cells = active
+            .grid
+            .map
+            .iter()
+            .map(|(id, coor)| (id.clone(), coor.clone()))
+            .collect::<Vec<(String, ElementCoor)>>();
+        cells.sort_by_key(|(id, _)| (id.parse::<usize>().ok(), id.clone()));
+        let mut ports = active
+            .composition
+            .get_coors_by_ids(
+                &active.composition.origin().get_all_ids(),
+                &Relative::new(0, 0, None),
+                ratio,
+            )?
+            .into_iter()
+            .filter_map(|(id, _, area)| id.parse::<usize>().ok().map(|id| (id, area)))
+            .collect::<Vec<(usize, (i32, i32, i32, i32))>>();
+        ports.sort_by_key(|(id, _)| *id);
+        let mut connections = active.composition.get_connections_points(state, ratio);
+        connections.sort_by_key(|(id, _)| *id);
+        Ok(Self {
+            size: active.grid.size,
+            cell: ratio.invert(active.grid.cell),
+            cells,
+            ports,
+            connections,
+        })
+    }
+}
+
+/// Human-readable snapshot
+#[derive(Serialize)]
+pub struct JsonSnapshot<'a> {
+    pub layout: Layout,
+    pub model: Composition,
+    pub options: &'a Options,
+    pub state: &'a State,
+    pub render: (&'a Active, &'a Ratio, &'a SignatureProducer),
+    pub history: Option<&'a History>,
+}
+
+// FNV-1a
+fn checksum(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0x811c9dc5u32, |hash, byte| {
+        (hash ^ *byte as u32).wrapping_mul(0x01000193)
+    })
+}
+
+pub fn encode<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, SnapshotError> {
+    bincode::serialize(value).map_err(|e| SnapshotError::Corrupted(e.to_string()))
+}
+
+/// Decodes section; returns None if section cannot be read with current structures
+pub fn decode<T: DeserializeOwned>(bytes: &[u8]) -> Option<T> {
+    bincode::deserialize(bytes).ok()
+}
+
+/// Model is stored as JSON, so adding of entities fields (with defaults) doesn't make
+/// model of snapshot unreadable
+pub fn encode_model(model: &Composition) -> Result<Vec<u8>, SnapshotError> {
+    serde_json::to_vec(model).map_err(|e| SnapshotError::Corrupted(e.to_string()))
+}
+
+fn decode_model(bytes: &[u8]) -> Option<Composition> {
+    serde_json::from_slice(bytes).ok()
+}
+
+/// Decodes frozen structures of previous version. All bytes should be consumed, which
+/// rejects data, which only starts like a valid snapshot.
+fn decode_legacy<T: DeserializeOwned>(bytes: &[u8]) -> Option<T> {
+    bincode::DefaultOptions::new()
+        .with_fixint_encoding()
+        .reject_trailing_bytes()
+        .deserialize(bytes)
+        .ok()
+}
+
+/// Converts frozen structure of previous version into current one. Conversion goes
+/// through JSON, so fields added since previous version get default values.
+fn convert<F: Serialize, T: DeserializeOwned>(legacy: &F) -> Option<T> {
+    serde_json::to_value(legacy)
+        .ok()
+        .and_then(|value| serde_json::from_value(value).ok())
+}
+
+/// Converts frozen structure of previous version into section of current version; section
+/// is dropped (empty) if structure cannot be converted
+fn upgrade<F: Serialize, T: DeserializeOwned + Serialize>(
+    legacy: &F,
+) -> Result<Vec<u8>, SnapshotError> {
+    convert::<F, T>(legacy)
+        .map(|current| encode(&current))
+        .unwrap_or(Ok(Vec::new()))
+}
+
+pub fn write_json(snapshot: JsonSnapshot) -> Result<String, SnapshotError> {
+    #[derive(Serialize)]
+    struct Envelope<'a> {
+        format: &'static str,
+        version: u16,
+        #[serde(flatten)]
+        snapshot: JsonSnapshot<'a>,
+    }
+    serde_json::to_string_pretty(&Envelope {
+        format: JSON_FORMAT,
+        version: VERSION,
+        snapshot,
+    })
+    .map_err(|e| SnapshotError::Corrupted(e.to_string()))
+}
+
+pub fn write(sections: &Sections) -> Result<Vec<u8>, SnapshotError> {
+    let payload = encode(sections)?;
+    let len = u32::try_from(payload.len())
+        .map_err(|_| SnapshotError::Corrupted("snapshot is too big".to_string()))?;
+    let mut bytes = Vec::with_capacity(HEADER_LEN + payload.len());
+    bytes.extend_from_slice(MAGIC);
+    bytes.extend_from_slice(&VERSION.to_le_bytes());
+    bytes.extend_from_slice(&len.to_le_bytes());
+    bytes.extend_from_slice(&checksum(&payload).to_le_bytes());
+    bytes.extend_from_slice(&payload);
+    Ok(bytes)
+}
+
+/// Reads snapshot and migrates it to current version
+pub fn read(bytes: &[u8]) -> Result<Restored, SnapshotError> {
+    if !bytes.starts_with(MAGIC) {
+        // Snapshots without header have been created before versioning of format
+        return restore(0, v0::migrate(bytes)?);
+    }
+    if bytes.len() < HEADER_LEN {
+        return Err(SnapshotError::Corrupted("header is truncated".to_string()));
+    }
+    let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+    let len = u32::from_le_bytes([bytes[6], bytes[7], bytes[8], bytes[9]]) as usize;
+    let sum = u32::from_le_bytes([bytes[10], bytes[11], bytes[12], bytes[13]]);
+    let payload = &bytes[HEADER_LEN..];
+    if payload.len() != len {
+        return Err(SnapshotError::Corrupted(format!(
+            "expected {len} bytes of payload, but found {}",
+            payload.len()
+        )));
+    }
+    if checksum(payload) != sum {
+        return Err(SnapshotError::Corrupted(
+            "checksum doesn't match".to_string(),
+        ));
+    }
+    if version > VERSION {
+        return Err(SnapshotError::Unsupported(version, VERSION));
+    }
+    restore(version, migrate(version, payload)?)
+}
+
+/// Decodes sections of current version
+fn restore(version: u16, sections: Sections) -> Result<Restored, SnapshotError> {
+    let render = match decode(&sections.render) {
+        Some(render) => Ok(render),
+        None => Err(
+            decode_model(&sections.model).ok_or(SnapshotError::Corrupted(
+                "fail to read model of snapshot".to_string(),
+            ))?,
+        ),
+    };
+    Ok(Restored {
+        version,
+        options: decode(&sections.options),
+        state: decode(&sections.state),
+        render,
+        history: sections.history.as_deref().map(decode),
+    })
+}
+
+/// Reads JSON snapshot; sections, which cannot be read, are skipped the same way as
+/// for binary snapshot
+pub fn read_json(json: &str) -> Result<Restored, SnapshotError> {
+    let mut snapshot: Value =
+        serde_json::from_str(json).map_err(|e| SnapshotError::Corrupted(e.to_string()))?;
+    if snapshot.get("format").and_then(Value::as_str) != Some(JSON_FORMAT) {
+        return Err(SnapshotError::Corrupted(
+            "format of snapshot isn't defined".to_string(),
+        ));
+    }
+    let version = snapshot
+        .get("version")
+        .and_then(Value::as_u64)
+        .and_then(|version| u16::try_from(version).ok())
+        .ok_or(SnapshotError::Corrupted(
+            "version of snapshot isn't defined".to_string(),
+        ))?;
+    if version > VERSION {
+        return Err(SnapshotError::Unsupported(version, VERSION));
+    }
+    // JSON is self-describing, so sections of previous versions are parsed with current
+    // structures; sections, which cannot be parsed, are dropped
+    let mut section = |name: &str| snapshot.get_mut(name).map(Value::take);
+    fn parse<T: DeserializeOwned>(value: Option<Value>) -> Option<T> {
+        value.and_then(|value| serde_json::from_value(value).ok())
+    }
+    let render = match parse(section("render")) {
+        Some(render) => Ok(render),
+        None => Err(parse(section("model")).ok_or(SnapshotError::Corrupted(
+            "fail to read model of snapshot".to_string(),
+        ))?),
+    };
+    Ok(Restored {
+        version,
+        options: parse(section("options")),
+        state: parse(section("state")),
+        render,
+        history: section("history")
+            .filter(|value| !value.is_null())
+            .map(|value| parse(Some(value))),
+    })
+}
+
+/// Converts payload of given version into sections of current version
+fn migrate(version: u16, payload: &[u8]) -> Result<Sections, SnapshotError> {
+    match version {
+        1 => v1::migrate(payload),
+        VERSION => decode(payload).ok_or(SnapshotError::Corrupted(
+            "fail to read sections of snapshot".to_string(),
+        )),
+        _ => Err(SnapshotError::Outdated(version)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::dummy::Dummy;
+
+    fn model() -> Composition {
+        Composition::dummy(&mut SignatureProducer::new(0), (3..=3, 2..=2))
+    }
+
+    fn sections(model: &Composition) -> Sections {
+        Sections {
+            model: encode_model(model).unwrap(),
+            options: encode(&Options::default()).unwrap(),
+            ..Sections::default()
+        }
+    }
+
+    // Writes header of given version for any payload
+    fn frame(version: u16, payload: &[u8]) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&version.to_le_bytes());
+        bytes.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&checksum(payload).to_le_bytes());
+        bytes.extend_from_slice(payload);
+        bytes
+    }
+
+    fn corrupted(result: Result<Restored, SnapshotError>) -> String {
+        match result {
+            Err(SnapshotError::Corrupted(reason)) => reason,
+            Err(e) => panic!("unexpected error: {e}"),
+            Ok(_) => panic!("corrupted snapshot has been read"),
+        }
+    }
+
+    #[test]
+    fn restores_model_of_current_version() {
+        let model = model();
+        let restored = read(&write(&sections(&model)).unwrap()).unwrap();
+        assert_eq!(restored.version, VERSION);
+        assert!(restored.options.is_some());
+        // Empty sections are treated as dropped
+        assert!(restored.state.is_none());
+        assert!(restored.history.is_none());
+        let Err(restored) = restored.render else {
+            panic!("render has been restored from empty section");
+        };
+        assert_eq!(
+            serde_json::to_value(&restored).unwrap(),
+            serde_json::to_value(&model).unwrap()
+        );
+    }
+
+    #[test]
+    fn drops_unreadable_sections() {
+        let mut sections = sections(&model());
+        sections.options = vec![0xff; 3];
+        sections.history = Some(vec![0xff; 3]);
+        let restored = read(&write(&sections).unwrap()).unwrap();
+        assert!(restored.options.is_none());
+        assert!(matches!(restored.history, Some(None)));
+        assert!(restored.render.is_err());
+    }
+
+    #[test]
+    fn rejects_modified_payload() {
+        let mut bytes = write(&sections(&model())).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 1;
+        assert_eq!(corrupted(read(&bytes)), "checksum doesn't match");
+    }
+
+    #[test]
+    fn rejects_truncated_snapshot() {
+        let bytes = write(&sections(&model())).unwrap();
+        assert_eq!(
+            corrupted(read(&bytes[..HEADER_LEN - 1])),
+            "header is truncated"
+        );
+        assert!(corrupted(read(&bytes[..bytes.len() - 1])).starts_with("expected"));
+    }
+
+    #[test]
+    fn rejects_data_without_header() {
+        corrupted(read(b"not a snapshot"));
+        corrupted(read(&[]));
+    }
+
+    #[test]
+    fn rejects_unreadable_sections() {
+        assert_eq!(
+            corrupted(read(&frame(VERSION, &[1, 2, 3]))),
+            "fail to read sections of snapshot"
+        );
+        let sections = Sections {
+            model: b"{}".to_vec(),
+            ..Sections::default()
+        };
+        assert_eq!(
+            corrupted(read(&frame(VERSION, &encode(&sections).unwrap()))),
+            "fail to read model of snapshot"
+        );
+    }
+
+    #[test]
+    fn rejects_future_version() {
+        let payload = encode(&sections(&model())).unwrap();
+        assert!(matches!(
+            read(&frame(VERSION + 1, &payload)),
+            Err(SnapshotError::Unsupported(version, VERSION)) if version == VERSION + 1
+        ));
+    }
+
+    #[test]
+    fn rejects_outdated_version() {
+        let payload = encode(&sections(&model())).unwrap();
+        assert!(matches!(
+            read(&frame(0, &payload)),
+            Err(SnapshotError::Outdated(0))
+        ));
+    }
+
+    #[test]
+    fn reads_json_snapshot() {
+        let model = serde_json::to_value(model()).unwrap();
+        let json = |format: Value, version: Value| {
+            serde_json::json!({
+                "format": format,
+                "version": version,
+                "model": model,
+                "options": Options::default(),
+                "render": null,
+            })
+            .to_string()
+        };
+        let restored = read_json(&json(JSON_FORMAT.into(), VERSION.into())).unwrap();
+        assert!(restored.options.is_some());
+        assert!(restored.history.is_none());
+        assert_eq!(
+            serde_json::to_value(restored.render.err().unwrap()).unwrap(),
+            model
+        );
+        assert!(matches!(
+            read_json(&json(JSON_FORMAT.into(), (VERSION + 1).into())),
+            Err(SnapshotError::Unsupported(..))
+        ));
+        corrupted(read_json(&json("other".into(), VERSION.into())));
+        corrupted(read_json(&json(JSON_FORMAT.into(), "2".into())));
+        corrupted(read_json("{"));
+    }
+}
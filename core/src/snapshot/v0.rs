@@ -0,0 +1,402 @@
+// Structures of snapshot created before versioning of format (without header). Such
+// snapshot is a bincode dump of board, so structures below keep layout of board as it
+// was and should never be changed. Only model, options and state are migrated; render
+// is calculated from model.
+#![allow(dead_code)]
+
+use super::{convert, encode_model, upgrade, Sections};
+use crate::{entity, error::SnapshotError, export};
+use serde::{de::DeserializeOwned, Deserialize, Serialize, Serializer};
+use std::collections::HashMap;
+
+#[derive(Deserialize, Serialize)]
+struct Signature {
+    id: usize,
+    class_name: String,
+    short_name: String,
+}
+
+#[derive(Deserialize)]
+#[serde(bound = "T: DeserializeOwned")]
+#[allow(clippy::large_enum_variant)]
+enum Representation<T> {
+    Origin(T),
+    Render(Render<T>),
+}
+
+// Render is dropped on migration; entity is written as origin
+impl<T: Serialize> Serialize for Representation<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let origin = match self {
+            Self::Origin(entity) => entity,
+            Self::Render(render) => &render.entity,
+        };
+        serializer.serialize_newtype_variant("Representation", 0, "Origin", origin)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(bound = "T: DeserializeOwned")]
+struct Render<T> {
+    entity: T,
+    view: View,
+    hidden: bool,
+}
+
+#[derive(Deserialize, Serialize)]
+struct Composition {
+    sig: Signature,
+    components: Vec<Representation<Component>>,
+    connections: Vec<Representation<Connection>>,
+    compositions: Vec<Representation<Composition>>,
+    ports: Representation<Ports>,
+    parent: Option<usize>,
+}
+
+#[derive(Deserialize, Serialize)]
+struct Component {
+    sig: Signature,
+    ports: Representation<Ports>,
+    composition: bool,
+}
+
+#[derive(Deserialize, Serialize)]
+struct Joint {
+    port: usize,
+    component: usize,
+}
+
+#[derive(Deserialize, Serialize)]
+struct Connection {
+    sig: Signature,
+    joint_in: Joint,
+    joint_out: Joint,
+    visibility: bool,
+}
+
+#[derive(Deserialize, Serialize)]
+enum PortType {
+    Left,
+    Right,
+}
+
+#[derive(Deserialize, Serialize)]
+struct Port {
+    sig: Signature,
+    port_type: PortType,
+    provided_interface: Option<Signature>,
+    provided_required_interface: Option<Signature>,
+    required_interface: Option<Signature>,
+    contains: Vec<usize>,
+    connected: HashMap<usize, usize>,
+    visibility: bool,
+}
+
+#[derive(Deserialize, Serialize)]
+struct Ports {
+    ports: Vec<Representation<Port>>,
+    hide_invisible: bool,
+    sig: Signature,
+}
+
+#[derive(Deserialize)]
+struct View {
+    container: Container,
+    elements: Vec<Container>,
+}
+
+#[derive(Deserialize)]
+struct Container {
+    form: Form,
+    style: Style,
+}
+
+#[derive(Deserialize)]
+struct Style {
+    stroke_style: String,
+    fill_style: String,
+}
+
+#[derive(Deserialize)]
+#[allow(clippy::large_enum_variant)]
+enum Form {
+    GridRectangle(ElementType, GridRectangle),
+    Rectangle(ElementType, Rectangle),
+    Path(ElementType, Path),
+    Button(ElementType, Button),
+    Label(ElementType, Label),
+}
+
+#[derive(Deserialize)]
+struct GridRectangle {
+    x: i32,
+    y: i32,
+    w: i32,
+    h: i32,
+    cells: (u32, u32),
+    id: String,
+    params: GridRectangleParams,
+}
+
+#[derive(Deserialize)]
+struct GridRectangleParams {
+    cell: u32,
+}
+
+#[derive(Deserialize)]
+struct Rectangle {
+    x: i32,
+    y: i32,
+    w: i32,
+    h: i32,
+    id: String,
+}
+
+#[derive(Deserialize)]
+struct Path {
+    points: Vec<Point>,
+    id: String,
+    params: PathParams,
+    sdot: bool,
+    edot: bool,
+    sarrow: bool,
+    earrow: bool,
+}
+
+#[derive(Deserialize)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[derive(Deserialize)]
+struct PathParams {
+    radius: u32,
+}
+
+#[derive(Deserialize)]
+struct Button {
+    x: i32,
+    y: i32,
+    w: i32,
+    h: i32,
+    font: String,
+    label: String,
+    padding: i32,
+    id: String,
+    align: Align,
+    params: ButtonParams,
+}
+
+#[derive(Deserialize)]
+struct ButtonParams {
+    pad_hor: i32,
+    r_off: i32,
+    min_h: i32,
+    min_w: i32,
+    x_off: i32,
+    y_off: i32,
+    f_size: i32,
+}
+
+#[derive(Deserialize)]
+enum Align {
+    Left,
+    Right,
+}
+
+#[derive(Deserialize)]
+struct Label {
+    x: i32,
+    y: i32,
+    w: i32,
+    h: i32,
+    font: String,
+    label: (String, String),
+    subtitle: Option<String>,
+    badge: Option<(String, String, String)>,
+    subbadge: Option<(String, String, String)>,
+    index_label: Option<(usize, String, Option<String>)>,
+    padding: i32,
+    id: String,
+    align: Align,
+    params: LabelParams,
+}
+
+#[derive(Deserialize)]
+struct LabelParams {
+    cell: u32,
+    min_w: i32,
+    pad_v: i32,
+    pad_h: i32,
+}
+
+#[derive(Deserialize)]
+enum ElementType {
+    Unknown,
+    Component,
+    Composition,
+    Connection,
+    Port,
+    Element,
+}
+
+type ElementCoor = (ElementType, (u32, u32, u32, u32));
+
+#[derive(Deserialize)]
+struct Grid {
+    options: GridOptions,
+    size: (u32, u32),
+    map: HashMap<String, ElementCoor>,
+    id: Option<usize>,
+    cell: u32,
+    ratio: Ratio,
+}
+
+#[derive(Deserialize, Serialize)]
+enum PortsRepresentation {
+    Blocks,
+    Labels,
+}
+
+#[derive(Deserialize, Serialize)]
+struct PortsOptions {
+    representation: PortsRepresentation,
+    grouping: bool,
+    group_unbound: bool,
+}
+
+#[derive(Deserialize, Serialize)]
+struct Connections {
+    hide: bool,
+}
+
+#[derive(Deserialize, Serialize)]
+struct GridOptions {
+    cell_size_px: u32,
+    cells_space_vertical: u32,
+    cells_space_horizontal: u32,
+    visible: bool,
+    vpadding: u32,
+    hpadding: u32,
+    vmargin: u32,
+    hmargin: u32,
+}
+
+#[derive(Deserialize, Serialize)]
+struct Labels {
+    ports_short_name: bool,
+    components_short_name: bool,
+    composition_short_name: bool,
+    port_label_max_len: usize,
+    comp_label_max_len: usize,
+}
+
+#[derive(Deserialize, Serialize)]
+struct RectColor {
+    stroke: String,
+    fill: String,
+}
+
+#[derive(Deserialize, Serialize)]
+struct ColorScheme {
+    composition_rect: RectColor,
+    composition_label: RectColor,
+    composition_as_component_rect: RectColor,
+    component_rect: RectColor,
+    selected_rect: RectColor,
+    highlighted_rect: RectColor,
+    matched_rect: RectColor,
+    hovered_rect: RectColor,
+    connection_line: RectColor,
+    port_highlighted_rect: RectColor,
+    port_rect: RectColor,
+    port_unlinked_rect: RectColor,
+    port_linked_rect: RectColor,
+    port_grouped_rect: RectColor,
+    port_pri_bagde: RectColor,
+    port_pi_bagde: RectColor,
+    port_ri_bagde: RectColor,
+    port_index_label: RectColor,
+    port_subbagde: RectColor,
+    label_subtitle: RectColor,
+    label: RectColor,
+}
+
+#[derive(Deserialize, Serialize)]
+struct Options {
+    ports: PortsOptions,
+    connections: Connections,
+    grid: GridOptions,
+    labels: Labels,
+    ratio: u8,
+    font: String,
+    scheme: ColorScheme,
+}
+
+#[derive(Deserialize, Serialize)]
+struct Selection {
+    components: Vec<usize>,
+    ports: Vec<usize>,
+}
+
+#[derive(Deserialize, Serialize)]
+struct State {
+    selection: Selection,
+    components: Vec<usize>,
+    ports: Vec<usize>,
+    ports_highlighted: Vec<usize>,
+    hovered: Option<usize>,
+    hmargin: i32,
+    vmargin: i32,
+    filtered: Option<(Vec<usize>, Vec<usize>, Vec<usize>)>,
+    targeted: Option<(Vec<usize>, Vec<usize>)>,
+    matches_extended: Option<Vec<(usize, Option<usize>, usize)>>,
+    matches: Option<Vec<usize>>,
+    highlighted: Option<Vec<usize>>,
+    x: i32,
+    y: i32,
+    zoom: f64,
+}
+
+#[derive(Deserialize)]
+struct Ratio {
+    ratio: u8,
+}
+
+#[derive(Deserialize)]
+struct SignatureProducer {
+    current: usize,
+}
+
+#[derive(Deserialize)]
+struct Active {
+    grid: Grid,
+    composition: Render<Composition>,
+}
+
+#[derive(Deserialize)]
+struct Snapshot {
+    active: Active,
+    options: Options,
+    state: State,
+    ratio: Ratio,
+    sig_producer: SignatureProducer,
+}
+
+/// Converts snapshot without header into sections of current version
+pub fn migrate(bytes: &[u8]) -> Result<Sections, SnapshotError> {
+    let legacy: Snapshot = super::decode_legacy(bytes).ok_or(SnapshotError::Corrupted(
+        "snapshot has no header and isn't a snapshot of unversioned format".to_string(),
+    ))?;
+    let model: entity::Composition = convert(&legacy.active.composition.entity).ok_or(
+        SnapshotError::Corrupted("fail to migrate model of snapshot".to_string()),
+    )?;
+    Ok(Sections {
+        model: encode_model(&export::json::expand(&model, &|_| true))?,
+        options: upgrade::<_, crate::render::options::Options>(&legacy.options)?,
+        state: upgrade::<_, crate::state::State>(&legacy.state)?,
+        render: Vec::new(),
+        history: None,
+    })
+}
@@ -0,0 +1,352 @@
+// Structures of snapshot of version 1. Sections are bincode dumps, so structures below
+// keep layout of sections as it was and should never be changed. Model, options and
+// state are migrated; render is calculated from model; history is dropped, because
+// changes of model were stored as copies of model.
+#![allow(dead_code)]
+
+use super::{convert, encode_model, upgrade, Sections};
+use crate::{entity, error::SnapshotError};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Deserialize)]
+struct Payload {
+    model: Vec<u8>,
+    options: Vec<u8>,
+    state: Vec<u8>,
+    render: Vec<u8>,
+    history: Option<Vec<u8>>,
+}
+
+#[derive(Deserialize, Serialize)]
+struct Signature {
+    id: usize,
+    class_name: String,
+    short_name: String,
+}
+
+// Model is stored without render entities, so only origin is possible
+#[derive(Deserialize, Serialize)]
+#[serde(bound = "T: Serialize + DeserializeOwned")]
+enum Representation<T> {
+    Origin(T),
+}
+
+#[derive(Deserialize, Serialize)]
+struct Composition {
+    sig: Signature,
+    components: Vec<Representation<Component>>,
+    connections: Vec<Representation<Connection>>,
+    compositions: Vec<Representation<Composition>>,
+    ports: Representation<Ports>,
+    parent: Option<usize>,
+    interfaces: Vec<Interface>,
+}
+
+#[derive(Deserialize, Serialize)]
+struct Component {
+    sig: Signature,
+    ports: Representation<Ports>,
+    composition: bool,
+}
+
+#[derive(Deserialize, Serialize)]
+struct Joint {
+    port: usize,
+    component: usize,
+}
+
+#[derive(Deserialize, Serialize)]
+struct Connection {
+    sig: Signature,
+    joint_in: Joint,
+    joint_out: Joint,
+    visibility: bool,
+}
+
+#[derive(Deserialize, Serialize)]
+enum PortType {
+    Left,
+    Right,
+}
+
+#[derive(Deserialize, Serialize)]
+struct Port {
+    sig: Signature,
+    port_type: PortType,
+    provided_interface: Option<Signature>,
+    provided_required_interface: Option<Signature>,
+    required_interface: Option<Signature>,
+    contains: Vec<usize>,
+    connected: HashMap<usize, usize>,
+    visibility: bool,
+    interface_kind: Option<InterfaceKind>,
+}
+
+#[derive(Deserialize, Serialize)]
+struct Ports {
+    ports: Vec<Representation<Port>>,
+    hide_invisible: bool,
+    sig: Signature,
+}
+
+#[derive(Deserialize, Serialize)]
+enum InterfaceKind {
+    SenderReceiver,
+    ClientServer,
+    ModeSwitch,
+    Parameter,
+    NvData,
+    Trigger,
+}
+
+#[derive(Deserialize, Serialize)]
+enum ArgumentDirection {
+    In,
+    Out,
+    InOut,
+}
+
+#[derive(Deserialize, Serialize)]
+struct DataType {
+    sig: Signature,
+    category: Option<String>,
+}
+
+#[derive(Deserialize, Serialize)]
+struct DataElement {
+    sig: Signature,
+    data_type: Option<DataType>,
+}
+
+#[derive(Deserialize, Serialize)]
+struct Argument {
+    sig: Signature,
+    direction: ArgumentDirection,
+    data_type: Option<DataType>,
+}
+
+#[derive(Deserialize, Serialize)]
+struct Operation {
+    sig: Signature,
+    arguments: Vec<Argument>,
+}
+
+#[derive(Deserialize, Serialize)]
+struct Interface {
+    sig: Signature,
+    kind: InterfaceKind,
+    data_elements: Vec<DataElement>,
+    operations: Vec<Operation>,
+}
+
+#[derive(Deserialize, Serialize)]
+enum PortsRepresentation {
+    Blocks,
+    Labels,
+}
+
+#[derive(Deserialize, Serialize)]
+struct PortsOptions {
+    representation: PortsRepresentation,
+    grouping: bool,
+    group_unbound: bool,
+}
+
+#[derive(Deserialize, Serialize)]
+struct Connections {
+    hide: bool,
+    snap_distance: u32,
+}
+
+#[derive(Deserialize, Serialize)]
+struct GridOptions {
+    cell_size_px: u32,
+    cells_space_vertical: u32,
+    cells_space_horizontal: u32,
+    visible: bool,
+    vpadding: u32,
+    hpadding: u32,
+    vmargin: u32,
+    hmargin: u32,
+}
+
+#[derive(Deserialize, Serialize)]
+struct Labels {
+    ports_short_name: bool,
+    components_short_name: bool,
+    composition_short_name: bool,
+    port_label_max_len: usize,
+    comp_label_max_len: usize,
+}
+
+#[derive(Deserialize, Serialize)]
+struct RectColor {
+    stroke: String,
+    fill: String,
+}
+
+#[derive(Deserialize, Serialize)]
+struct ColorScheme {
+    composition_rect: RectColor,
+    composition_label: RectColor,
+    composition_as_component_rect: RectColor,
+    component_rect: RectColor,
+    selected_rect: RectColor,
+    highlighted_rect: RectColor,
+    matched_rect: RectColor,
+    hovered_rect: RectColor,
+    connection_line: RectColor,
+    connection_mismatch_line: RectColor,
+    connection_drag_line: RectColor,
+    connection_snapped_line: RectColor,
+    connection_rejected_line: RectColor,
+    port_highlighted_rect: RectColor,
+    diff_added_rect: RectColor,
+    diff_removed_rect: RectColor,
+    diff_changed_rect: RectColor,
+    diff_moved_rect: RectColor,
+    port_rect: RectColor,
+    port_unlinked_rect: RectColor,
+    port_linked_rect: RectColor,
+    port_grouped_rect: RectColor,
+    port_pri_bagde: RectColor,
+    port_pi_bagde: RectColor,
+    port_ri_bagde: RectColor,
+    port_index_label: RectColor,
+    port_subbagde: RectColor,
+    label_subtitle: RectColor,
+    label: RectColor,
+}
+
+#[derive(Deserialize, Serialize)]
+struct Options {
+    ports: PortsOptions,
+    connections: Connections,
+    grid: GridOptions,
+    labels: Labels,
+    ratio: u8,
+    font: String,
+    scheme: ColorScheme,
+}
+
+#[derive(Deserialize, Serialize)]
+struct Selection {
+    components: Vec<usize>,
+    ports: Vec<usize>,
+}
+
+#[derive(Deserialize, Serialize)]
+enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+    Moved,
+}
+
+#[derive(Deserialize, Serialize)]
+struct Edit {
+    enabled: bool,
+}
+
+#[derive(Deserialize, Serialize)]
+struct State {
+    selection: Selection,
+    components: Vec<usize>,
+    ports: Vec<usize>,
+    ports_highlighted: Vec<usize>,
+    hovered: Option<usize>,
+    hmargin: i32,
+    vmargin: i32,
+    filtered: Option<(Vec<usize>, Vec<usize>, Vec<usize>)>,
+    targeted: Option<(Vec<usize>, Vec<usize>)>,
+    matches_extended: Option<Vec<(usize, Option<usize>, usize)>>,
+    matches: Option<Vec<usize>>,
+    highlighted: Option<Vec<usize>>,
+    mismatches: Option<Vec<usize>>,
+    changes: Option<Vec<(usize, ChangeKind)>>,
+    edit: Edit,
+    x: i32,
+    y: i32,
+    zoom: f64,
+}
+
+/// Converts payload of version 1 into sections of current version
+pub fn migrate(payload: &[u8]) -> Result<Sections, SnapshotError> {
+    let payload: Payload = super::decode_legacy(payload).ok_or(SnapshotError::Corrupted(
+        "fail to read sections of snapshot".to_string(),
+    ))?;
+    let model: entity::Composition = super::decode_legacy::<Composition>(&payload.model)
+        .and_then(|model| convert(&model))
+        .ok_or(SnapshotError::Corrupted(
+            "fail to migrate model of snapshot".to_string(),
+        ))?;
+    let options = super::decode_legacy::<Options>(&payload.options);
+    let state = super::decode_legacy::<State>(&payload.state);
+    Ok(Sections {
+        model: encode_model(&model)?,
+        options: match options {
+            Some(options) => upgrade::<_, crate::render::options::Options>(&options)?,
+            None => Vec::new(),
+        },
+        state: match state {
+            Some(state) => upgrade::<_, crate::state::State>(&state)?,
+            None => Vec::new(),
+        },
+        render: Vec::new(),
+        history: payload.history.map(|_| Vec::new()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::dummy::{Dummy, SignatureProducer};
+
+    // Payload of version 1 with model and options converted from current structures
+    fn payload(model: &entity::Composition, history: Option<Vec<u8>>) -> Vec<u8> {
+        let model: Composition = convert(model).unwrap();
+        let options: Options = convert(&crate::render::options::Options::default()).unwrap();
+        bincode::serialize(&(
+            bincode::serialize(&model).unwrap(),
+            bincode::serialize(&options).unwrap(),
+            vec![0xffu8; 3],
+            vec![0xffu8; 3],
+            history,
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn migrates_model_and_options() {
+        let model = entity::Composition::dummy(&mut SignatureProducer::new(0), (3..=3, 2..=2));
+        let sections = migrate(&payload(&model, Some(vec![1, 2, 3]))).unwrap();
+        let migrated: entity::Composition = super::super::decode_model(&sections.model).unwrap();
+        assert_eq!(
+            serde_json::to_value(&migrated).unwrap(),
+            serde_json::to_value(&model).unwrap()
+        );
+        assert!(
+            super::super::decode::<crate::render::options::Options>(&sections.options).is_some()
+        );
+        // Unreadable state is dropped; render and history are recalculated
+        assert!(sections.state.is_empty());
+        assert!(sections.render.is_empty());
+        assert_eq!(sections.history, Some(Vec::new()));
+    }
+
+    #[test]
+    fn rejects_damaged_payload() {
+        let model = entity::Composition::dummy(&mut SignatureProducer::new(0), (1..=1, 1..=1));
+        let mut payload = payload(&model, None);
+        payload.push(0);
+        assert!(matches!(
+            migrate(&payload),
+            Err(SnapshotError::Corrupted(_))
+        ));
+        assert!(matches!(
+            migrate(&[1, 2, 3]),
+            Err(SnapshotError::Corrupted(_))
+        ));
+    }
+}
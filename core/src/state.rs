@@ -1,8 +1,16 @@
-use crate::{entity::Port, render::Relative};
+use crate::{
+    analysis::{diff::ChangeKind, interfaces::Rejection},
+    entity::{Connection, Port},
+    render::Relative,
+};
 use serde::{Deserialize, Serialize};
 use wasm_bindgen::JsValue;
 use wasm_bindgen_test::console_log;
 
+// Limits of zoom set by fitting of view (same as limits of zooming by wheel in lib)
+const MIN_ZOOM: f64 = 0.1;
+const MAX_ZOOM: f64 = 2.0;
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Selection {
     components: Vec<usize>,
@@ -55,12 +63,28 @@ impl Selection {
         self
     }
 
+    pub fn get_components(&self) -> &[usize] {
+        &self.components
+    }
+
+    pub fn get_ports(&self) -> &[usize] {
+        &self.ports
+    }
+
     pub fn clear(&mut self) -> &mut Self {
         self.ports.clear();
         self.components.clear();
         self
     }
 
+    /// Removes given ids from selection. Returns true if selection has been changed
+    pub fn forget(&mut self, ids: &[usize]) -> bool {
+        let len = self.components.len() + self.ports.len();
+        self.components.retain(|id| !ids.contains(id));
+        self.ports.retain(|id| !ids.contains(id));
+        len != self.components.len() + self.ports.len()
+    }
+
     pub fn notify(&self) {
         if let Some(selcb) = self.selcb.as_ref() {
             let selections = (&self.components, &self.ports);
@@ -73,6 +97,132 @@ impl Selection {
     }
 }
 
+/// Selection related part of state (used by history of changes)
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct SelectionState {
+    components: Vec<usize>,
+    ports: Vec<usize>,
+    ports_highlighted: Vec<usize>,
+    // (components, ports) reported with selection callback
+    selected: (Vec<usize>, Vec<usize>),
+}
+
+/// Way to apply area selection (rectangle or lasso) to current selection
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, Serialize)]
+pub enum SelectionMode {
+    #[default]
+    Replace,
+    Add,
+    Subtract,
+}
+
+/// Filter strings, which filtered, targeted and matched entities are calculated with
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct Queries {
+    pub filter: Option<String>,
+    pub targeted: Option<String>,
+    pub matches: Option<String>,
+}
+
+/// Filter related part of state (used by history of changes)
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct FilterState {
+    queries: Queries,
+    filtered: Option<(Vec<usize>, Vec<usize>, Vec<usize>)>,
+    targeted: Option<(Vec<usize>, Vec<usize>)>,
+    matches: Option<Vec<usize>>,
+    matches_extended: Option<Vec<(usize, Option<usize>, usize)>>,
+}
+
+/// Connection, which is dragged by user in edit mode
+#[derive(Debug)]
+pub struct Dragging {
+    // Port, where dragging has been started
+    pub port: usize,
+    // Start and end of rubber band (canvas coordinates)
+    pub from: (i32, i32),
+    pub to: (i32, i32),
+    // Ports compatible with source port and coordinates of its centers
+    pub candidates: Vec<(usize, (i32, i32))>,
+    pub target: Option<usize>,
+    pub rejection: Option<Rejection>,
+}
+
+/// Result of dragging step
+#[derive(Debug, Serialize)]
+pub struct DragStatus {
+    pub target: Option<usize>,
+    // Id of created connection
+    pub connection: Option<usize>,
+    pub rejection: Option<Rejection>,
+}
+
+/// Match focused by navigation over matches
+#[derive(Debug, Serialize)]
+pub struct FocusedMatch {
+    // Position of match in reading order (from 0)
+    pub index: usize,
+    pub total: usize,
+    pub id: usize,
+    // Grouped port, which contains matched port; it's highlighted instead of hidden port
+    pub holder: Option<usize>,
+}
+
+/// Position and zoom of view (position in same units as `set_view_state` takes)
+#[derive(Debug, Serialize)]
+pub struct ViewPosition {
+    pub x: i32,
+    pub y: i32,
+    pub zoom: f64,
+}
+
+/// Element focused by keyboard navigation: component or port of component
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Focus {
+    pub component: usize,
+    pub port: Option<usize>,
+}
+
+/// Direction of moving of keyboard focus between components
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub enum FocusDirection {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+/// Changes of model, which has been done in edit mode
+#[derive(Debug, Serialize)]
+pub enum EditEvent<'a> {
+    ConnectionAdded {
+        parent: usize,
+        connection: &'a Connection,
+    },
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct Edit {
+    pub enabled: bool,
+    #[serde(skip_serializing, skip_deserializing)]
+    pub dragging: Option<Dragging>,
+    #[serde(skip_serializing, skip_deserializing)]
+    // Callback to notify about changes of model
+    pub editcb: Option<js_sys::Function>,
+}
+
+impl Edit {
+    pub fn notify(&self, event: &EditEvent) {
+        if let Some(editcb) = self.editcb.as_ref() {
+            let Ok(value) = serde_wasm_bindgen::to_value(event) else {
+                console_log!("Fail to send edit event");
+                return;
+            };
+            let _ = editcb.call1(&JsValue::NULL, &value);
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct State {
     pub selection: Selection,
@@ -94,7 +244,30 @@ pub struct State {
     pub targeted: Option<(Vec<usize>, Vec<usize>)>,
     pub matches_extended: Option<Vec<(usize, Option<usize>, usize)>>,
     pub matches: Option<Vec<usize>>,
+    // Match focused by navigation over matches
+    #[serde(skip_serializing, skip_deserializing)]
+    pub focused_match: Option<usize>,
+    // Element focused by keyboard navigation
+    #[serde(skip_serializing, skip_deserializing)]
+    pub focus: Option<Focus>,
+    #[serde(default)]
+    pub queries: Queries,
     pub highlighted: Option<Vec<usize>>,
+    // Connections with incompatible interfaces
+    #[serde(default)]
+    pub mismatches: Option<Vec<usize>>,
+    // Changes between compared models (diff mode)
+    #[serde(default)]
+    pub changes: Option<Vec<(usize, ChangeKind)>>,
+    // Components, connections and ports of impact analysis; everything else is dimmed
+    #[serde(skip_serializing, skip_deserializing)]
+    pub impact: Option<Vec<usize>>,
+    // Highlighted ports before impact mode; restored on leaving it
+    #[serde(skip_serializing, skip_deserializing)]
+    ports_before_impact: Vec<usize>,
+    // Edit mode
+    #[serde(default)]
+    pub edit: Edit,
     pub x: i32,
     pub y: i32,
     pub zoom: f64,
@@ -112,7 +285,15 @@ impl State {
             targeted: None,
             matches: None,
             matches_extended: None,
+            focused_match: None,
+            focus: None,
+            queries: Queries::default(),
             highlighted: None,
+            mismatches: None,
+            changes: None,
+            impact: None,
+            ports_before_impact: Vec::new(),
+            edit: Edit::default(),
             hmargin,
             vmargin,
             x: 0,
@@ -131,11 +312,110 @@ impl State {
         self.targeted = None;
         self.matches = None;
         self.matches_extended = None;
+        self.focused_match = None;
+        self.focus = None;
+        self.queries = Queries::default();
+        self.mismatches = None;
+        self.changes = None;
+        self.impact = None;
+        self.ports_before_impact.clear();
+        self.edit.dragging = None;
         self.x = 0;
         self.y = 0;
         self.zoom = 1.0;
     }
 
+    /// Removes ids of deleted entities from state; view state and rest of selection are kept
+    pub fn forget(&mut self, ids: &[usize]) {
+        if ids.is_empty() {
+            return;
+        }
+        let retain = |list: &mut Vec<usize>| list.retain(|id| !ids.contains(id));
+        retain(&mut self.components);
+        retain(&mut self.ports);
+        retain(&mut self.ports_highlighted);
+        retain(&mut self.ports_before_impact);
+        if self.hovered.is_some_and(|id| ids.contains(&id)) {
+            self.hovered = None;
+        }
+        if let Some(focus) = self.focus.as_mut() {
+            if ids.contains(&focus.component) {
+                self.focus = None;
+            } else if focus.port.is_some_and(|port| ids.contains(&port)) {
+                focus.port = None;
+            }
+        }
+        if let Some((filtered, linked, owners)) = self.filtered.as_mut() {
+            retain(filtered);
+            retain(linked);
+            retain(owners);
+        }
+        if let Some((targeted, linked)) = self.targeted.as_mut() {
+            retain(targeted);
+            retain(linked);
+        }
+        if let Some(matches) = self.matches.as_mut() {
+            retain(matches);
+        }
+        if let Some(extended) = self.matches_extended.as_mut() {
+            extended.retain(|(id, parent, owner)| {
+                !ids.contains(id)
+                    && !ids.contains(owner)
+                    && !parent.is_some_and(|parent| ids.contains(&parent))
+            });
+        }
+        if let Some(highlighted) = self.highlighted.as_mut() {
+            retain(highlighted);
+        }
+        if let Some(mismatches) = self.mismatches.as_mut() {
+            retain(mismatches);
+        }
+        if let Some(changes) = self.changes.as_mut() {
+            changes.retain(|(id, _)| !ids.contains(id));
+        }
+        if self.selection.forget(ids) {
+            self.selection.notify();
+        }
+    }
+
+    pub fn get_selection_state(&self) -> SelectionState {
+        SelectionState {
+            components: self.components.clone(),
+            ports: self.ports.clone(),
+            ports_highlighted: self.ports_highlighted.clone(),
+            selected: (
+                self.selection.components.clone(),
+                self.selection.ports.clone(),
+            ),
+        }
+    }
+
+    pub fn set_selection_state(&mut self, selection: SelectionState) {
+        self.components = selection.components;
+        self.ports = selection.ports;
+        self.ports_highlighted = selection.ports_highlighted;
+        (self.selection.components, self.selection.ports) = selection.selected;
+        self.selection.notify();
+    }
+
+    pub fn get_filter_state(&self) -> FilterState {
+        FilterState {
+            queries: self.queries.clone(),
+            filtered: self.filtered.clone(),
+            targeted: self.targeted.clone(),
+            matches: self.matches.clone(),
+            matches_extended: self.matches_extended.clone(),
+        }
+    }
+
+    pub fn set_filter_state(&mut self, filter: FilterState) {
+        self.queries = filter.queries;
+        self.filtered = filter.filtered;
+        self.targeted = filter.targeted;
+        self.matches = filter.matches;
+        self.matches_extended = filter.matches_extended;
+    }
+
     pub fn get_view_relative(&self) -> Relative {
         Relative::new(self.x, self.y, Some(self.zoom))
     }
@@ -170,6 +450,24 @@ impl State {
         self.zoom = zoom;
     }
 
+    /// Sets zoom and position of view to fit given area (grid coordinates without margins)
+    /// into viewport of given size. `padding` - space around area in viewport.
+    pub fn fit_view(&mut self, area: (i32, i32, i32, i32), size: (u32, u32), padding: i32) {
+        let (x, y, x1, y1) = area;
+        let available = |side: u32| (side as i32 - padding * 2).max(1) as f64;
+        self.zoom = (available(size.0) / (x1 - x).max(1) as f64)
+            .min(available(size.1) / (y1 - y).max(1) as f64)
+            .clamp(MIN_ZOOM, MAX_ZOOM);
+        self.center_view(((x + x1) / 2, (y + y1) / 2), size);
+    }
+
+    /// Moves view to place given point (grid coordinates without margins) in center of
+    /// viewport of given size
+    pub fn center_view(&mut self, point: (i32, i32), size: (u32, u32)) {
+        self.x = (size.0 as f64 / 2.0 / self.zoom) as i32 - self.hmargin - point.0;
+        self.y = (size.1 as f64 / 2.0 / self.zoom) as i32 - self.vmargin - point.1;
+    }
+
     pub fn set_filtered(&mut self, filtered: Option<(Vec<usize>, Vec<usize>, Vec<usize>)>) {
         self.filtered = filtered;
     }
@@ -193,6 +491,7 @@ impl State {
     ) {
         self.matches = matches;
         self.matches_extended = extended;
+        self.focused_match = None;
     }
 
     pub fn get_matches(&self) -> Option<&Vec<usize>> {
@@ -225,6 +524,62 @@ impl State {
             .unwrap_or(false)
     }
 
+    pub fn set_mismatches(&mut self, mismatches: Option<Vec<usize>>) {
+        self.mismatches = mismatches;
+    }
+
+    pub fn is_connection_mismatched(&self, id: &usize) -> bool {
+        self.mismatches
+            .as_ref()
+            .map(|ids| ids.contains(id))
+            .unwrap_or(false)
+    }
+
+    pub fn set_changes(&mut self, changes: Option<Vec<(usize, ChangeKind)>>) {
+        self.changes = changes;
+    }
+
+    pub fn get_change(&self, id: &usize) -> Option<&ChangeKind> {
+        self.changes
+            .as_ref()
+            .and_then(|changes| changes.iter().find(|(v, _)| v == id))
+            .map(|(_, kind)| kind)
+    }
+
+    /// Returns true if component (without focused port) or port is focused
+    pub fn is_focused(&self, id: &usize) -> bool {
+        self.focus
+            .as_ref()
+            .is_some_and(|focus| focus.port.as_ref().unwrap_or(&focus.component) == id)
+    }
+
+    /// Switches into impact mode: only given entities aren't dimmed and given ports are
+    /// highlighted. Ports highlighted before impact mode are kept to be restored.
+    pub fn set_impact(&mut self, impact: Vec<usize>, ports: Vec<usize>) {
+        let before = std::mem::replace(&mut self.ports_highlighted, ports);
+        if self.impact.is_none() {
+            self.ports_before_impact = before;
+        }
+        self.impact = Some(impact);
+    }
+
+    /// Leaves impact mode and restores highlighted ports. Returns false if board wasn't
+    /// in impact mode.
+    pub fn drop_impact(&mut self) -> bool {
+        if self.impact.take().is_none() {
+            return false;
+        }
+        self.ports_highlighted = std::mem::take(&mut self.ports_before_impact);
+        true
+    }
+
+    pub fn is_dimmed(&self, id: &usize) -> bool {
+        self.impact
+            .as_ref()
+            .map(|ids| !ids.contains(id))
+            .unwrap_or(false)
+    }
+
     pub fn is_port_linked(&self, port: &Port) -> bool {
         if let Some((_filtered, linked, _owners)) = self.filtered.as_ref() {
             linked.contains(&port.sig.id)
@@ -342,6 +697,11 @@ impl State {
         }
     }
 
+    /// Replaces highlighted ports
+    pub fn set_highlighted_ports(&mut self, ports: Vec<usize>) {
+        self.ports_highlighted = ports;
+    }
+
     pub fn unhighlight_port(&mut self, id: &usize) -> bool {
         if let Some(i) = self.ports_highlighted.iter().position(|v| v == id) {
             let _ = self.ports_highlighted.remove(i);
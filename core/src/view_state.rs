@@ -0,0 +1,134 @@
+use crate::{entity::Signature, error::SnapshotError, state::Queries};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+// Version of encoded view state; stored as first byte before compression
+const VERSION: u8 = 1;
+const COMPRESSION_LEVEL: u8 = 9;
+
+/// Reference to entity, which doesn't depend on model: if id isn't found or refers to
+/// another entity, entity is looked up by path
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct EntityRef {
+    pub id: usize,
+    pub path: String,
+}
+
+/// Composition in navigation path of host (see `goToComposition`)
+#[derive(Debug, Clone, Deserialize)]
+pub struct Location {
+    pub id: usize,
+    pub sig: Signature,
+}
+
+/// View state of board (without model and layout), which can be shared as deep link
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct ViewState {
+    // Navigation path from root composition to shown one
+    pub compositions: Vec<EntityRef>,
+    pub components: Vec<EntityRef>,
+    pub ports: Vec<EntityRef>,
+    pub queries: Queries,
+    // Targeted entities; used if targeted entities were set by ids
+    pub targeted: Option<Vec<EntityRef>>,
+    pub highlighted: Option<Vec<EntityRef>>,
+    pub x: i32,
+    pub y: i32,
+    pub zoom: f64,
+}
+
+/// Summary of applying of view state
+#[derive(Debug, Default, Serialize)]
+pub struct Report {
+    // Referred entities, which don't exist in current model
+    pub missing: Vec<EntityRef>,
+}
+
+/// Converts ids into references and back using paths of entities of model
+pub struct Resolver {
+    paths: HashMap<usize, String>,
+    ids: HashMap<String, usize>,
+}
+
+impl Resolver {
+    pub fn new(paths: HashMap<usize, String>) -> Self {
+        let ids = paths
+            .iter()
+            .map(|(id, path)| (path.clone(), *id))
+            .collect::<HashMap<String, usize>>();
+        Self { paths, ids }
+    }
+
+    pub fn refs(&self, ids: &[usize]) -> Vec<EntityRef> {
+        ids.iter()
+            .map(|id| EntityRef {
+                id: *id,
+                path: self.paths.get(id).cloned().unwrap_or_default(),
+            })
+            .collect()
+    }
+
+    /// Returns ids of found entities; not found references are added into report
+    pub fn ids(&self, refs: &[EntityRef], report: &mut Report) -> Vec<usize> {
+        refs.iter()
+            .filter_map(|entity| {
+                let found = if self.paths.get(&entity.id) == Some(&entity.path) {
+                    Some(entity.id)
+                } else {
+                    self.ids.get(&entity.path).copied()
+                };
+                if found.is_none() {
+                    report.missing.push(entity.clone());
+                }
+                found
+            })
+            .collect()
+    }
+}
+
+/// Converts navigation path into references; path of composition is built from short
+/// names of compositions, the same way as paths of entities of model
+pub fn locations_refs(locations: &[Location]) -> Vec<EntityRef> {
+    let mut path = String::new();
+    locations
+        .iter()
+        .map(|location| {
+            path = format!("{path}/{}", location.sig.short_name);
+            EntityRef {
+                id: location.id,
+                path: path.clone(),
+            }
+        })
+        .collect()
+}
+
+/// Encodes view state into compressed url-safe base64 string
+pub fn encode(view: &ViewState) -> Result<String, SnapshotError> {
+    let mut bytes = vec![VERSION];
+    bytes.extend(bincode::serialize(view).map_err(|e| SnapshotError::Corrupted(e.to_string()))?);
+    Ok(
+        URL_SAFE_NO_PAD.encode(miniz_oxide::deflate::compress_to_vec(
+            &bytes,
+            COMPRESSION_LEVEL,
+        )),
+    )
+}
+
+pub fn decode(encoded: &str) -> Result<ViewState, SnapshotError> {
+    let compressed = URL_SAFE_NO_PAD
+        .decode(encoded.trim())
+        .map_err(|e| SnapshotError::Corrupted(e.to_string()))?;
+    let bytes = miniz_oxide::inflate::decompress_to_vec(&compressed)
+        .map_err(|e| SnapshotError::Corrupted(e.to_string()))?;
+    match bytes.first() {
+        Some(&VERSION) => {
+            bincode::deserialize(&bytes[1..]).map_err(|e| SnapshotError::Corrupted(e.to_string()))
+        }
+        Some(version) if *version > VERSION => {
+            Err(SnapshotError::Unsupported(*version as u16, VERSION as u16))
+        }
+        Some(version) => Err(SnapshotError::Outdated(*version as u16)),
+        None => Err(SnapshotError::Corrupted("view state is empty".to_string())),
+    }
+}
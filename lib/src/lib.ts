@@ -867,6 +867,39 @@ export class Board extends Subscriber {
         );
     }
 
+    public exportViewState(): string {
+        return this.board.export_view_state(this.data.history);
+    }
+
+    public importViewState(encoded: string): Types.ViewStateReport {
+        if (this.data.root !== undefined) {
+            const root = this.data.root;
+            const compositions: Types.EntityRef[] =
+                this.board.get_view_state_compositions(encoded);
+            const ids = compositions
+                .map((location) => {
+                    const found = Types.getComposition(root, location.id);
+                    return found !== undefined
+                        ? found
+                        : Types.getCompositionByPath(root, location.path);
+                })
+                .filter((found) => found !== undefined)
+                .map((found) => (found as Types.Composition).sig.id);
+            if (
+                ids.length > 0 &&
+                ids[ids.length - 1] !== this.data.composition
+            ) {
+                ids.forEach((id) => this.goToComposition(id));
+            }
+        }
+        const report = this.board.import_view_state(encoded);
+        const view = this.board.get_view_state();
+        this.position.x = view.x;
+        this.position.y = view.y;
+        this.position.zoom = view.zoom;
+        return report;
+    }
+
     public components(): {
         filter(filter: string | undefined): void;
         byIds(ids: number[]): void;
@@ -111,6 +111,36 @@ export function getComposition(
     return undefined;
 }
 
+// Reference to entity of model by id and path (short names from root)
+export interface EntityRef {
+    id: number;
+    path: string;
+}
+
+export interface ViewStateReport {
+    missing: EntityRef[];
+}
+
+export function getCompositionByPath(
+    composition: Composition,
+    path: string
+): Composition | undefined {
+    const names = path.split("/").filter((name) => name !== "");
+    if (names.shift() !== composition.sig.short_name) {
+        return undefined;
+    }
+    let current: Composition | undefined = composition;
+    for (let name of names) {
+        current = current.compositions.find(
+            (nested) => nested.Origin.sig.short_name === name
+        )?.Origin;
+        if (current === undefined) {
+            return undefined;
+        }
+    }
+    return current;
+}
+
 export interface EntityProps {
     class_name: string[];
     short_name: string[];